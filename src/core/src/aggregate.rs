@@ -0,0 +1,282 @@
+//! # Aggregate functions.
+//!
+//! Reduce a column of a relation to a single value, like `sum` or `max`. Work on the [Array]
+//! of the column, so the numbers keep their native type & a [Decimal] stay exact.
+//!
+//! `min` & `max` are *logical* functions: They work on any value, following the total order
+//! of [Scalar].
+//!
+//! A column of [Money] is summed only if all the values are in the same currency.
+//!
+//! For a empty column `count` & `sum` return `0`, and `avg`, `min` & `max` return
+//! [Scalar::Unit] because there is no value to return. The `sum` of a empty column of [Money]
+//! is [Scalar::Unit] too, because there is no currency for the `0`.
+use std::fmt;
+
+use crate::ops;
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    /// Find the aggregate by the name of the function, like `sum`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let x = match name {
+            "count" => Aggregate::Count,
+            "sum" => Aggregate::Sum,
+            "avg" => Aggregate::Avg,
+            "min" => Aggregate::Min,
+            "max" => Aggregate::Max,
+            _ => return None,
+        };
+        Some(x)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aggregate::Count => "count",
+            Aggregate::Sum => "sum",
+            Aggregate::Avg => "avg",
+            Aggregate::Min => "min",
+            Aggregate::Max => "max",
+        }
+    }
+
+    /// The type of the result for a column of `kind`
+    pub fn kind(&self, kind: &DataType) -> DataType {
+        match self {
+            Aggregate::Count => DataType::I64,
+            // Like the division of integers, so the average not silently lose precision
+            Aggregate::Avg if *kind == DataType::I64 => DataType::Decimal,
+            _ => kind.clone(),
+        }
+    }
+
+    /// Reduce the values of the column `of`, of type `kind`
+    pub fn apply(&self, of: &Array, kind: &DataType) -> ResultT<Scalar> {
+        match self {
+            Aggregate::Count => Ok((of.len() as i64).into()),
+            Aggregate::Sum => sum(of, kind),
+            Aggregate::Avg => avg(of, kind),
+            Aggregate::Min => Ok(values(of).min().unwrap_or(Scalar::Unit([()]))),
+            Aggregate::Max => Ok(values(of).max().unwrap_or(Scalar::Unit([()]))),
+        }
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+fn values(of: &Array) -> impl Iterator<Item = Scalar> + '_ {
+    (0..of.len()).filter_map(|x| of.get(x))
+}
+
+fn unsupported(op: &'static str, of: &Array, kind: &DataType) -> ErrorCore {
+    let kind = match kind {
+        DataType::Any => of.slice().kind(),
+        kind => kind.clone(),
+    };
+    ErrorCore::Unsupported { op, kind }
+}
+
+fn sum(of: &Array, kind: &DataType) -> ResultT<Scalar> {
+    let op = "sum";
+    let overflow = || ErrorCore::Overflow { op };
+    let x = match of {
+        Array::I64(x) => x
+            .iter()
+            .try_fold(0i64, |acc, x| acc.checked_add(*x))
+            .ok_or_else(overflow)?
+            .into(),
+        Array::Decimal(x) => x
+            .iter()
+            .try_fold(Decimal::ZERO, |acc, x| acc.checked_add(*x))
+            .ok_or_else(overflow)?
+            .into(),
+        Array::F64(x) => x.iter().fold(F64::from(0.0), |acc, x| acc + *x).into(),
+        Array::Scalar(x) if is_money(of, kind) => match x.split_first() {
+            Some((first, rest)) => rest
+                .iter()
+                .try_fold(first.clone(), |acc, x| ops::add(&acc, x))?,
+            None => Scalar::Unit([()]),
+        },
+        _ => return Err(unsupported(op, of, kind)),
+    };
+    Ok(x)
+}
+
+/// A column of [Money], that not has a specialized [Array]. The `kind` tell it even if is empty
+fn is_money(of: &Array, kind: &DataType) -> bool {
+    match of {
+        Array::Scalar(x) => *kind == DataType::Money || matches!(x.first(), Some(Scalar::Money(_))),
+        _ => false,
+    }
+}
+
+fn avg(of: &Array, kind: &DataType) -> ResultT<Scalar> {
+    let op = "avg";
+    if !matches!(of, Array::I64(_) | Array::Decimal(_) | Array::F64(_)) && !is_money(of, kind) {
+        return Err(unsupported(op, of, kind));
+    }
+    if of.is_empty() {
+        return Ok(Scalar::Unit([()]));
+    }
+
+    let total = of.len();
+    let x = match sum(of, kind)? {
+        Scalar::I64([x]) => (Decimal::from(x) / Decimal::from(total)).into(),
+        Scalar::Decimal([x]) => (x / Decimal::from(total)).into(),
+        Scalar::F64([x]) => (x / F64::from(total as f64)).into(),
+        Scalar::Money([x]) => x.checked_div(Decimal::from(total))?.into(),
+        _ => unreachable!("The sum of numbers is a number"),
+    };
+    Ok(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(x: &str) -> Scalar {
+        dsl::dec(Decimal::from_str(x).unwrap())
+    }
+
+    #[test]
+    fn numbers() {
+        let ints = Array::I64(vec![1, 2, 4]);
+        assert_eq!(
+            Aggregate::Count.apply(&ints, &DataType::I64).unwrap(),
+            dsl::int(3)
+        );
+        assert_eq!(
+            Aggregate::Sum.apply(&ints, &DataType::I64).unwrap(),
+            dsl::int(7)
+        );
+        assert_eq!(
+            Aggregate::Avg
+                .apply(&ints, &DataType::I64)
+                .unwrap()
+                .to_string(),
+            "2.3333333333333333333333333333d"
+        );
+        assert_eq!(Aggregate::Avg.kind(&DataType::I64), DataType::Decimal);
+
+        // Not lose precision like a float
+        let decs = Array::Decimal(vec![
+            Decimal::from_str("0.1").unwrap(),
+            Decimal::from_str("0.2").unwrap(),
+        ]);
+        assert_eq!(
+            Aggregate::Sum.apply(&decs, &DataType::Decimal).unwrap(),
+            dec("0.3")
+        );
+        assert_eq!(
+            Aggregate::Avg.apply(&decs, &DataType::Decimal).unwrap(),
+            dec("0.15")
+        );
+
+        let floats = Array::F64(vec![1.0.into(), 2.0.into()]);
+        assert_eq!(
+            Aggregate::Avg
+                .apply(&floats, &DataType::F64)
+                .unwrap()
+                .to_string(),
+            "1.5f"
+        );
+
+        let err = Aggregate::Sum
+            .apply(&Array::I64(vec![i64::MAX, 1]), &DataType::I64)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Arithmetic overflow in `sum`");
+
+        let err = Aggregate::Sum
+            .apply(&Array::Utf8(vec!["a".into()]), &DataType::Utf8)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The operator `sum` is not supported for Str"
+        );
+    }
+
+    #[test]
+    fn money() {
+        let usd = |x: &str| Scalar::from(format!("{x} USD").parse::<Money>().unwrap());
+        let prices = Array::Scalar(vec![usd("10"), usd("0.05"), usd("0.10")]);
+        assert_eq!(
+            Aggregate::Sum.apply(&prices, &DataType::Money).unwrap(),
+            usd("10.15")
+        );
+        // Rounded to the nearest even cent
+        assert_eq!(
+            Aggregate::Avg.apply(&prices, &DataType::Money).unwrap(),
+            usd("3.38")
+        );
+
+        let mixed = Array::Scalar(vec![usd("1"), "1 EUR".parse::<Money>().unwrap().into()]);
+        assert_eq!(
+            Aggregate::Sum
+                .apply(&mixed, &DataType::Money)
+                .unwrap_err()
+                .to_string(),
+            "Can't mix currencies: lhs = USD, rhs = EUR"
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let empty = Array::I64(vec![]);
+        assert_eq!(
+            Aggregate::Count.apply(&empty, &DataType::I64).unwrap(),
+            dsl::int(0)
+        );
+        assert_eq!(
+            Aggregate::Sum.apply(&empty, &DataType::I64).unwrap(),
+            dsl::int(0)
+        );
+        assert_eq!(
+            Aggregate::Avg.apply(&empty, &DataType::I64).unwrap(),
+            Scalar::Unit([()])
+        );
+        assert_eq!(
+            Aggregate::Min.apply(&empty, &DataType::I64).unwrap(),
+            Scalar::Unit([()])
+        );
+        assert_eq!(
+            Aggregate::Max.apply(&empty, &DataType::I64).unwrap(),
+            Scalar::Unit([()])
+        );
+
+        // There is no currency for the `0`
+        let prices = Array::Scalar(vec![]);
+        for agg in [Aggregate::Sum, Aggregate::Avg] {
+            assert_eq!(
+                agg.apply(&prices, &DataType::Money).unwrap(),
+                Scalar::Unit([()])
+            );
+        }
+    }
+
+    #[test]
+    fn total_order() {
+        let mixed = Array::Scalar(vec![dsl::int(1), Scalar::Bool([true]), dsl::str("Hello")]);
+        assert_eq!(
+            Aggregate::Min.apply(&mixed, &DataType::Any).unwrap(),
+            Scalar::Bool([true])
+        );
+        assert_eq!(
+            Aggregate::Max.apply(&mixed, &DataType::Any).unwrap(),
+            dsl::str("Hello")
+        );
+    }
+}
@@ -1,3 +1,411 @@
 //! # Algebraic data types.
 //!
-//! Define the `sum` types
+//! Define the `sum` types, declared like:
+//!
+//! ```tablam
+//! enum Option[T] do
+//!   case Some(T)
+//!   case None
+//! end
+//! ```
+//!
+//! A value of a [SumType] is a [Variant]: one of its cases with the values of its fields. The
+//! variants are ordered by the name of the type, then by the order of declaration of the cases,
+//! then by its values, so `None < Some(1)` if `None` is declared first.
+//!
+//! A variant know the types bound to the parameters, so the values of the same type can't be
+//! mixed with other types, like `Some(1) < Some("x")`.
+use std::fmt;
+
+use crate::errors::{ErrorCtx, ErrorKind};
+use crate::prelude::*;
+
+/// The type of a field of a [Case]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FieldKind {
+    Kind(DataType),
+    /// A parameter of the type, like the `T` of `Option[T]`, that accept any value
+    Param(String),
+}
+
+impl fmt::Display for FieldKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldKind::Kind(x) => write!(f, "{}", x),
+            FieldKind::Param(x) => write!(f, "{}", x),
+        }
+    }
+}
+
+/// A case of a [SumType], like `Some(T)` or `None`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Case {
+    pub name: String,
+    pub fields: Vec<FieldKind>,
+}
+
+impl Case {
+    pub fn new(name: &str, fields: &[FieldKind]) -> Self {
+        Case {
+            name: name.into(),
+            fields: fields.to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.fields.is_empty() {
+            format_list(&self.fields, self.fields.len(), "(", ")", f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A `sum` type, where a value is one of the `cases`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SumType {
+    pub name: String,
+    pub params: Vec<String>,
+    pub cases: Vec<Case>,
+    /// The type it extend, like the `Value` of `enum Value2: Value`
+    pub parent: Option<String>,
+}
+
+fn invalid(kind: ErrorKind, msg: &str, ctx: (&str, &str)) -> ErrorCore {
+    let err = ErrorLang::new(kind, Some(msg)).with_ctx(ErrorCtx::new(ctx.0, ctx.1));
+    ErrorCore::Lang(Box::new(err))
+}
+
+impl SumType {
+    /// The type `name`, the cases & parameters must not be duplicated
+    pub fn new(name: &str, params: &[String], cases: &[Case]) -> ResultT<Self> {
+        for (pos, x) in params.iter().enumerate() {
+            if params[..pos].contains(x) {
+                let msg = format!("The parameter `{x}` is duplicated");
+                return Err(invalid(ErrorKind::Duplicated, &msg, ("enum", name)));
+            }
+        }
+        for (pos, x) in cases.iter().enumerate() {
+            if cases[..pos].iter().any(|old| old.name == x.name) {
+                let msg = format!("The case `{}` is duplicated", x.name);
+                return Err(invalid(ErrorKind::Duplicated, &msg, ("enum", name)));
+            }
+        }
+
+        Ok(SumType {
+            name: name.into(),
+            params: params.to_vec(),
+            cases: cases.to_vec(),
+            parent: None,
+        })
+    }
+
+    /// Check the type can be declared after the `enums`: The name must be new, like its own
+    /// `cases`, so a case is only shared with the types extended from the same parent
+    pub fn check_new<'a>(
+        &self,
+        cases: &[Case],
+        mut enums: impl Iterator<Item = &'a SumType>,
+    ) -> ResultT<()> {
+        enums.try_for_each(|old| {
+            if old.name == self.name {
+                let msg = format!("The type `{}` is already defined", self.name);
+                return Err(invalid(ErrorKind::Duplicated, &msg, ("enum", &self.name)));
+            }
+            match cases.iter().find(|x| old.case(&x.name).is_some()) {
+                Some(x) => {
+                    let msg = format!("The case `{}` is already defined in `{}`", x.name, old.name);
+                    Err(invalid(ErrorKind::Duplicated, &msg, ("enum", &self.name)))
+                }
+                None => Ok(()),
+            }
+        })
+    }
+
+    /// A new type `name` with the cases of `self` followed by the `cases`, like
+    /// `enum Value2: Value`
+    pub fn extend(&self, name: &str, params: &[String], cases: &[Case]) -> ResultT<Self> {
+        let params: Vec<_> = self.params.iter().chain(params).cloned().collect();
+        let cases: Vec<_> = self.cases.iter().chain(cases).cloned().collect();
+        let of = Self::new(name, &params, &cases)?;
+        Ok(SumType {
+            parent: Some(self.name.clone()),
+            ..of
+        })
+    }
+
+    pub fn kind(&self) -> DataType {
+        DataType::Sum(self.name.clone())
+    }
+
+    /// Find the case by name, with its position
+    pub fn case(&self, name: &str) -> Option<(usize, &Case)> {
+        self.cases.iter().enumerate().find(|(_, x)| x.name == name)
+    }
+
+    /// Build the case `name` with the `values` of its fields, checking they match its types. The
+    /// values of the same parameter must be of the same type, like in `Pair(T, T)`
+    pub fn build(&self, name: &str, values: &[Scalar]) -> ResultT<Variant> {
+        let (tag, case) = self.case(name).ok_or_else(|| {
+            let msg = format!("The case `{name}` is not defined");
+            invalid(ErrorKind::NotFound, &msg, ("enum", &self.name))
+        })?;
+        if case.fields.len() != values.len() {
+            let msg = format!(
+                "The case `{}` take {} values, but get {}",
+                case.name,
+                case.fields.len(),
+                values.len()
+            );
+            return Err(invalid(
+                ErrorKind::Invalid,
+                &msg,
+                ("case", &case.to_string()),
+            ));
+        }
+
+        let mut params: Vec<(&str, DataType)> = Vec::new();
+        for (field, value) in case.fields.iter().zip(values) {
+            let expected = match field {
+                FieldKind::Kind(kind) => Some(kind.clone()),
+                FieldKind::Param(param) => match params.iter().find(|(x, _)| x == param) {
+                    Some((_, kind)) => Some(kind.clone()),
+                    None => {
+                        params.push((param, value.kind()));
+                        None
+                    }
+                },
+            };
+            match expected {
+                Some(expected) if expected != value.kind() => {
+                    return Err(ErrorCore::TypeMismatch {
+                        expected,
+                        get: value.kind(),
+                    })
+                }
+                _ => {}
+            }
+        }
+
+        let params = self
+            .params
+            .iter()
+            .map(|x| params.iter().find(|(name, _)| name == x))
+            .map(|x| x.map(|(_, kind)| kind.clone()))
+            .collect();
+        Ok(Variant {
+            ty: self.name.clone(),
+            tag,
+            case: case.name.clone(),
+            values: values.to_vec(),
+            params,
+        })
+    }
+}
+
+impl fmt::Display for SumType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "enum {}", self.name)?;
+        if !self.params.is_empty() {
+            format_list(&self.params, self.params.len(), "[", "]", f)?;
+        }
+        write!(f, " do")?;
+        for x in &self.cases {
+            write!(f, " case {}", x)?;
+        }
+        write!(f, " end")
+    }
+}
+
+/// A value of a [SumType], like `Some(1)`.
+///
+/// The order is by the name of the type, the position of the case, then the values.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Variant {
+    ty: String,
+    tag: usize,
+    case: String,
+    values: Vec<Scalar>,
+    /// The types bound to the parameters of the [SumType], `None` if the case not use it, like
+    /// the `T` of `None`
+    params: Box<[Option<DataType>]>,
+}
+
+impl Variant {
+    /// The name of the [SumType]
+    pub fn ty(&self) -> &str {
+        &self.ty
+    }
+
+    pub fn kind(&self) -> DataType {
+        DataType::Sum(self.ty.clone())
+    }
+
+    /// The name of the case
+    pub fn case(&self) -> &str {
+        &self.case
+    }
+
+    pub fn values(&self) -> &[Scalar] {
+        &self.values
+    }
+
+    pub fn params(&self) -> &[Option<DataType>] {
+        &self.params
+    }
+}
+
+/// Merge the types bound to the parameters of two values of the same [SumType], failing if one
+/// is bound to different types
+pub fn unify(lhs: &[Option<DataType>], rhs: &[Option<DataType>]) -> ResultT<Vec<Option<DataType>>> {
+    lhs.iter()
+        .zip(rhs)
+        .map(|(a, b)| match (a, b) {
+            (Some(a), Some(b)) if a != b => Err(ErrorCore::TypeMismatch {
+                expected: a.clone(),
+                get: b.clone(),
+            }),
+            (a, b) => Ok(a.clone().or_else(|| b.clone())),
+        })
+        .collect()
+}
+
+/// Check two values of the same [SumType] bind its parameters to the same types, so `Some(1)` &
+/// `Some("x")` can't be mixed. The other values are not checked
+pub fn check_params(lhs: &Scalar, rhs: &Scalar) -> ResultT<()> {
+    match (lhs, rhs) {
+        (Scalar::Sum([a]), Scalar::Sum([b])) if a.ty == b.ty => {
+            unify(&a.params, &b.params).map(|_| ())
+        }
+        _ => Ok(()),
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.case)?;
+        if !self.values.is_empty() {
+            format_list(&self.values, self.values.len(), "(", ")", f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option() -> SumType {
+        let t = FieldKind::Param("T".into());
+        let cases = [Case::new("None", &[]), Case::new("Some", &[t])];
+        SumType::new("Option", &["T".into()], &cases).unwrap()
+    }
+
+    #[test]
+    fn build() {
+        let opt = option();
+        assert_eq!(
+            opt.to_string(),
+            "enum Option[T] do case None case Some(T) end"
+        );
+
+        let some = opt.build("Some", &[dsl::int(1)]).unwrap();
+        assert_eq!(some.to_string(), "Some(1)");
+        assert_eq!(some.kind(), DataType::Sum("Option".into()));
+        assert_eq!(opt.build("None", &[]).unwrap().to_string(), "None");
+
+        let err = |x: ResultT<Variant>| x.unwrap_err().to_string();
+        assert_eq!(
+            err(opt.build("Some", &[])),
+            "The case `Some` take 1 values, but get 0: case = Some(T)"
+        );
+        assert_eq!(
+            err(opt.build("Ok", &[])),
+            "The case `Ok` is not defined: enum = Option"
+        );
+    }
+
+    #[test]
+    fn fields() {
+        let int = FieldKind::Kind(DataType::I64);
+        let t = FieldKind::Param("T".into());
+        let cases = [Case::new("Num", &[int]), Case::new("Pair", &[t.clone(), t])];
+        let value = SumType::new("Value", &["T".into()], &cases).unwrap();
+
+        assert!(value.build("Num", &[dsl::int(1)]).is_ok());
+        assert!(value.build("Num", &[dsl::str("1")]).is_err());
+        assert!(value.build("Pair", &[dsl::str("a"), dsl::str("b")]).is_ok());
+        assert!(value.build("Pair", &[dsl::str("a"), dsl::int(1)]).is_err());
+
+        let dup = SumType::new("Value", &[], &[Case::new("A", &[]), Case::new("A", &[])]);
+        assert_eq!(
+            dup.unwrap_err().to_string(),
+            "The case `A` is duplicated: enum = Value"
+        );
+    }
+
+    #[test]
+    fn extend() {
+        let value2 = option()
+            .extend("Option2", &[], &[Case::new("Many", &[])])
+            .unwrap();
+        let names: Vec<_> = value2.cases.iter().map(|x| x.name.as_str()).collect();
+        assert_eq!(names, ["None", "Some", "Many"]);
+        assert_eq!(value2.parent.as_deref(), Some("Option"));
+        assert!(option()
+            .extend("Option2", &[], &[Case::new("None", &[])])
+            .is_err());
+
+        let many = [Case::new("Many", &[])];
+        let enums = [option(), value2.clone()];
+        assert!(option()
+            .extend("Option3", &[], &many[..0])
+            .unwrap()
+            .check_new(&many[..0], enums.iter())
+            .is_ok());
+        assert_eq!(
+            option()
+                .check_new(&option().cases, enums.iter())
+                .unwrap_err()
+                .to_string(),
+            "The type `Option` is already defined: enum = Option"
+        );
+        let other = SumType::new("Other", &[], &many).unwrap();
+        assert_eq!(
+            other
+                .check_new(&many, enums.iter())
+                .unwrap_err()
+                .to_string(),
+            "The case `Many` is already defined in `Option2`: enum = Other"
+        );
+    }
+
+    #[test]
+    fn params() {
+        let opt = option();
+        let one = Scalar::from(opt.build("Some", &[dsl::int(1)]).unwrap());
+        let text = Scalar::from(opt.build("Some", &[dsl::str("x")]).unwrap());
+        let none = Scalar::from(opt.build("None", &[]).unwrap());
+
+        assert!(check_params(&one, &none).is_ok());
+        assert!(check_params(&none, &text).is_ok());
+        assert!(check_params(&one, &dsl::str("x")).is_ok());
+        assert_eq!(
+            check_params(&one, &text).unwrap_err().to_string(),
+            "Type mismatch: expected Int, found Str"
+        );
+    }
+
+    #[test]
+    fn order() {
+        let opt = option();
+        let none = opt.build("None", &[]).unwrap();
+        let one = opt.build("Some", &[dsl::int(1)]).unwrap();
+        let two = opt.build("Some", &[dsl::int(2)]).unwrap();
+        assert!(none < one);
+        assert!(one < two);
+        assert!(Scalar::from(one) > dsl::str("z"));
+    }
+}
@@ -0,0 +1,163 @@
+//! # Bitwise operators over [Scalar::Bit].
+//!
+//! The bits are written from the most significant, so in `0011b` the first bit is `0` and
+//! `shift_left` move the bits toward it, filling with `0` at the end.
+//!
+//! The operators between two bit strings need the same length, so `0011b and 01b` is a error
+//! instead of silently pad one of them.
+use crate::errors::{ErrorCtx, ErrorKind};
+use crate::prelude::*;
+
+/// The operators between two bit strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOp {
+    And,
+    Or,
+    Xor,
+}
+
+impl BitOp {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BitOp::And => "and",
+            BitOp::Or => "or",
+            BitOp::Xor => "xor",
+        }
+    }
+
+    /// Apply the operator to bit strings of the same length
+    pub(crate) fn apply(&self, lhs: &BitSlice, rhs: &BitSlice) -> BitVec {
+        let mut x = lhs.to_bitvec();
+        match self {
+            BitOp::And => x &= rhs,
+            BitOp::Or => x |= rhs,
+            BitOp::Xor => x ^= rhs,
+        }
+        x
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shift {
+    /// Toward the first bit, filling with `0`
+    Left,
+    /// Toward the last bit, filling with the first bit so it keep the sign
+    Right,
+    /// Toward the last bit, filling with `0`
+    RightZero,
+}
+
+impl Shift {
+    /// Find the shift by the name used in the language, like `shift_left`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let x = match name {
+            "shift_left" => Shift::Left,
+            "shift_right" => Shift::Right,
+            "shift_right_zero" => Shift::RightZero,
+            _ => return None,
+        };
+        Some(x)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shift::Left => "shift_left",
+            Shift::Right => "shift_right",
+            Shift::RightZero => "shift_right_zero",
+        }
+    }
+}
+
+fn bits<'a>(op: &'static str, of: &'a Scalar) -> ResultT<&'a BitVec> {
+    match of {
+        Scalar::Bit(x) => Ok(x),
+        _ => Err(ErrorCore::Unsupported {
+            op,
+            kind: of.kind(),
+        }),
+    }
+}
+
+fn length_mismatch(lhs: &BitVec, rhs: &BitVec) -> ErrorCore {
+    let err = ErrorLang::new(ErrorKind::Invalid, Some("The bits have a different length"))
+        .with_ctx(ErrorCtx::new("lhs", &lhs.len().to_string()))
+        .with_ctx(ErrorCtx::new("rhs", &rhs.len().to_string()));
+    ErrorCore::Lang(Box::new(err))
+}
+
+pub fn bitwise(op: BitOp, lhs: &Scalar, rhs: &Scalar) -> ResultT<Scalar> {
+    let (a, b) = (bits(op.name(), lhs)?, bits(op.name(), rhs)?);
+    if a.len() != b.len() {
+        return Err(length_mismatch(a, b));
+    }
+    Ok(Scalar::Bit(op.apply(a, b)))
+}
+
+pub fn not(of: &Scalar) -> ResultT<Scalar> {
+    Ok(Scalar::Bit(!bits("not", of)?.clone()))
+}
+
+/// Shift the bits of `of` by `by` positions, so shift by the length or more clear all of them
+pub fn shift(op: Shift, of: &Scalar, by: usize) -> ResultT<Scalar> {
+    let mut x = bits(op.name(), of)?.clone();
+    let by = by.min(x.len());
+    let fill = op == Shift::Right && x.first().map(|x| *x).unwrap_or_default();
+
+    if by == x.len() {
+        x.fill(fill);
+    } else {
+        match op {
+            Shift::Left => x.shift_left(by),
+            Shift::Right | Shift::RightZero => {
+                x.shift_right(by);
+                x[..by].fill(fill);
+            }
+        }
+    }
+    Ok(Scalar::Bit(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bit(of: &str) -> Scalar {
+        Scalar::Bit(of.chars().map(|x| x == '1').collect())
+    }
+
+    fn show(x: ResultT<Scalar>) -> String {
+        match x {
+            Ok(x) => x.to_string(),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn bitwise_ops() {
+        let (a, b) = (bit("0011"), bit("0101"));
+        assert_eq!(show(bitwise(BitOp::And, &a, &b)), "Bits[0, 0, 0, 1]");
+        assert_eq!(show(bitwise(BitOp::Or, &a, &b)), "Bits[0, 1, 1, 1]");
+        assert_eq!(show(bitwise(BitOp::Xor, &a, &b)), "Bits[0, 1, 1, 0]");
+        assert_eq!(show(not(&a)), "Bits[1, 1, 0, 0]");
+
+        assert_eq!(
+            show(bitwise(BitOp::And, &a, &bit("01"))),
+            "The bits have a different length: lhs = 4, rhs = 2"
+        );
+        assert_eq!(
+            show(bitwise(BitOp::Xor, &a, &dsl::int(1))),
+            "The operator `xor` is not supported for Int"
+        );
+    }
+
+    #[test]
+    fn shifts() {
+        let x = bit("1011");
+        assert_eq!(show(shift(Shift::Left, &x, 1)), "Bits[0, 1, 1, 0]");
+        assert_eq!(show(shift(Shift::Right, &x, 1)), "Bits[1, 1, 0, 1]");
+        assert_eq!(show(shift(Shift::RightZero, &x, 1)), "Bits[0, 1, 0, 1]");
+        assert_eq!(show(shift(Shift::Right, &x, 9)), "Bits[1, 1, 1, 1]");
+        assert_eq!(show(shift(Shift::Left, &x, 4)), "Bits[0, 0, 0, 0]");
+        assert_eq!(show(shift(Shift::Left, &x, 0)), "Bits[1, 0, 1, 1]");
+    }
+}
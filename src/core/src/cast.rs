@@ -0,0 +1,469 @@
+//! # Explicit conversions between [Scalar] types.
+//!
+//! TablaM not do invisible conversions, so `1 + 2.0` is a error. Instead, the values are
+//! converted with a *cast*, like `Int(2.0)`, that is checked: a text that is not a number or
+//! a number that not fit in the target type is a [ErrorLang], never a silent wrong value.
+//!
+//! When a [Decimal] or [F64] lose the fractional part, is rounded with a [Rounding] mode. The
+//! default is [Rounding::HalfEven], so the rounding errors not accumulate on many values.
+//!
+//! The dates are converted to text with the formats [DATE_FMT], [TIME_FMT] & [DATE_TIME_FMT],
+//! and the text without a offset is read at the offset of the session.
+use std::str::FromStr;
+
+use chrono::{FixedOffset, NaiveDate, TimeZone};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::RoundingStrategy;
+
+use crate::errors::{ErrorCtx, ErrorKind};
+use crate::money::Currency;
+use crate::prelude::*;
+use crate::types::to_date as to_date_utc;
+
+/// How to remove the fractional part of a number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// To the nearest, and `.5` to the nearest even number, like `2.5 -> 2`
+    #[default]
+    HalfEven,
+    /// To the nearest, and `.5` away from zero, like `2.5 -> 3`
+    HalfUp,
+    /// To the nearest, and `.5` toward zero, like `2.5 -> 2`
+    HalfDown,
+    /// Away from zero, like `2.1 -> 3`
+    Up,
+    /// Toward zero, like `2.9 -> 2`
+    Down,
+    /// Toward negative infinity, like `-2.1 -> -3`
+    Floor,
+    /// Toward positive infinity, like `2.1 -> 3`
+    Ceiling,
+}
+
+impl Rounding {
+    /// Find the mode by the name used in the language, like `half_up`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let x = match name {
+            "half_even" => Rounding::HalfEven,
+            "half_up" => Rounding::HalfUp,
+            "half_down" => Rounding::HalfDown,
+            "up" => Rounding::Up,
+            "down" => Rounding::Down,
+            "floor" => Rounding::Floor,
+            "ceiling" => Rounding::Ceiling,
+            _ => return None,
+        };
+        Some(x)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rounding::HalfEven => "half_even",
+            Rounding::HalfUp => "half_up",
+            Rounding::HalfDown => "half_down",
+            Rounding::Up => "up",
+            Rounding::Down => "down",
+            Rounding::Floor => "floor",
+            Rounding::Ceiling => "ceiling",
+        }
+    }
+
+    fn strategy(&self) -> RoundingStrategy {
+        match self {
+            Rounding::HalfEven => RoundingStrategy::MidpointNearestEven,
+            Rounding::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            Rounding::HalfDown => RoundingStrategy::MidpointTowardZero,
+            Rounding::Up => RoundingStrategy::AwayFromZero,
+            Rounding::Down => RoundingStrategy::ToZero,
+            Rounding::Floor => RoundingStrategy::ToNegativeInfinity,
+            Rounding::Ceiling => RoundingStrategy::ToPositiveInfinity,
+        }
+    }
+
+    /// Round `of` to `scale` decimal places
+    pub fn round(&self, of: Decimal, scale: u32) -> Decimal {
+        of.round_dp_with_strategy(scale, self.strategy())
+    }
+}
+
+fn lang(kind: ErrorKind, msg: &str, of: &Scalar) -> ErrorCore {
+    let err = ErrorLang::new(kind, Some(msg)).with_ctx(ErrorCtx::new("value", &text(of)));
+    ErrorCore::Lang(Box::new(err))
+}
+
+fn unsupported(of: &Scalar, to: &DataType) -> ErrorCore {
+    let msg = format!("Can't convert {} to {}", of.kind(), to);
+    lang(ErrorKind::Invalid, &msg, of)
+}
+
+fn invalid_text(of: &Scalar, to: &DataType) -> ErrorCore {
+    let msg = format!("The text is not a valid {}", to);
+    lang(ErrorKind::Parse, &msg, of)
+}
+
+fn out_of_range(of: &Scalar, to: &DataType) -> ErrorCore {
+    let msg = format!("The value is out of the range of {}", to);
+    lang(ErrorKind::OutOfBounds, &msg, of)
+}
+
+/// The value as text, without the quotes or suffix of the literals
+fn text(of: &Scalar) -> String {
+    match of {
+        Scalar::Utf8([x]) => x.clone(),
+        Scalar::I64([x]) => x.to_string(),
+        Scalar::Decimal([x]) => x.to_string(),
+        Scalar::F64([x]) => x.to_string(),
+        Scalar::Date([x]) => x.fmt(),
+        Scalar::Interval([x]) => x.to_string(),
+        Scalar::Char([x]) => x.to_string(),
+        Scalar::Top(_) => "Top".into(),
+        x => x.to_string(),
+    }
+}
+
+fn to_i64(of: &Scalar, round: Rounding) -> ResultT<i64> {
+    let kind = DataType::I64;
+    let x = match of {
+        Scalar::I64([x]) => Some(*x),
+        Scalar::Bool([x]) => Some(*x as i64),
+        Scalar::Char([x]) => Some(*x as i64),
+        Scalar::Decimal([x]) => round.round(*x, 0).to_i64(),
+        Scalar::F64([x]) => {
+            Decimal::from_f64(x.into_inner()).and_then(|x| round.round(x, 0).to_i64())
+        }
+        Scalar::Utf8([x]) => return x.trim().parse::<i64>().map_err(|_| invalid_text(of, &kind)),
+        _ => return Err(unsupported(of, &kind)),
+    };
+    x.ok_or_else(|| out_of_range(of, &kind))
+}
+
+fn to_decimal(of: &Scalar) -> ResultT<Decimal> {
+    let kind = DataType::Decimal;
+    let x = match of {
+        Scalar::I64([x]) => Some(Decimal::from(*x)),
+        Scalar::Decimal([x]) => Some(*x),
+        Scalar::Money([x]) => Some(x.amount()),
+        Scalar::F64([x]) => Decimal::from_f64(x.into_inner()),
+        Scalar::Utf8([x]) => {
+            return Decimal::from_str(x.trim()).map_err(|_| invalid_text(of, &kind))
+        }
+        _ => return Err(unsupported(of, &kind)),
+    };
+    x.ok_or_else(|| out_of_range(of, &kind))
+}
+
+fn to_f64(of: &Scalar) -> ResultT<F64> {
+    let kind = DataType::F64;
+    let x = match of {
+        Scalar::I64([x]) => Some(*x as f64),
+        Scalar::Decimal([x]) => x.to_f64(),
+        Scalar::F64([x]) => Some(x.into_inner()),
+        Scalar::Utf8([x]) => {
+            return x
+                .trim()
+                .parse::<f64>()
+                .map(F64::from)
+                .map_err(|_| invalid_text(of, &kind))
+        }
+        _ => return Err(unsupported(of, &kind)),
+    };
+    x.map(F64::from).ok_or_else(|| out_of_range(of, &kind))
+}
+
+fn to_bool(of: &Scalar) -> ResultT<bool> {
+    let kind = DataType::Bool;
+    match of {
+        Scalar::Bool([x]) => Ok(*x),
+        Scalar::Utf8([x]) => match x.trim() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(invalid_text(of, &kind)),
+        },
+        _ => Err(unsupported(of, &kind)),
+    }
+}
+
+/// A single unicode scalar value, from the text or the code point
+fn to_char(of: &Scalar) -> ResultT<char> {
+    let kind = DataType::Char;
+    match of {
+        Scalar::Utf8([x]) => {
+            let mut chars = x.chars();
+            match (chars.next(), chars.next()) {
+                (Some(x), None) => Ok(x),
+                _ => Err(invalid_text(of, &kind)),
+            }
+        }
+        Scalar::I64([x]) => u32::try_from(*x)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| out_of_range(of, &kind)),
+        _ => Err(unsupported(of, &kind)),
+    }
+}
+
+fn to_date(of: &Scalar, kind: DateKind, offset: &FixedOffset) -> ResultT<DateT> {
+    let to = DataType::Date(kind);
+    let x = match of {
+        Scalar::Utf8([x]) => {
+            let x = x.trim();
+            let x = match kind {
+                DateKind::Date => parse_date_at(x, offset),
+                DateKind::Time => parse_time_t(x),
+                DateKind::DateTime => parse_date_time_at(x, offset),
+            };
+            return x.map_err(|_| invalid_text(of, &to));
+        }
+        Scalar::Date([x]) => x,
+        _ => return Err(unsupported(of, &to)),
+    };
+
+    // Only is possible to drop the parts of a date, not invent them
+    let local = x.date.naive_local();
+    match (x.kind, kind) {
+        (from, to) if from == to => Ok(x.clone()),
+        (DateKind::Date, DateKind::DateTime) => Ok(DateT::datetime(x.date)),
+        (DateKind::DateTime, DateKind::Date) => {
+            let day = local.date().and_hms(0, 0, 0);
+            let date = x.date.offset().from_local_datetime(&day).unwrap();
+            Ok(DateT::date(date))
+        }
+        (DateKind::DateTime, DateKind::Time) => {
+            let time = NaiveDate::MIN.and_time(local.time());
+            Ok(DateT::time(to_date_utc(time)))
+        }
+        _ => Err(unsupported(of, &to)),
+    }
+}
+
+/// The type to convert for the name of a cast function: the name of the type like `Int(x)`,
+/// or the method like `x.to_int()`
+pub fn target(name: &str) -> Option<DataType> {
+    let kind = match name {
+        "to_int" => DataType::I64,
+        "to_dec" => DataType::Decimal,
+        "to_float" => DataType::F64,
+        "to_str" => DataType::Utf8,
+        "to_bool" => DataType::Bool,
+        "to_date" => DataType::Date(DateKind::Date),
+        "to_time" => DataType::Date(DateKind::Time),
+        "to_datetime" => DataType::Date(DateKind::DateTime),
+        "to_interval" => DataType::Interval,
+        "to_money" => DataType::Money,
+        "to_char" => DataType::Char,
+        "Unit" | "Bit" | "Any" => return None,
+        name => return DataType::from_name(name),
+    };
+    Some(kind)
+}
+
+/// Convert the value `of` to the type `to`, with `round` if lose the fractional part, and the
+/// dates without offset at `offset`
+pub fn cast(of: &Scalar, to: &DataType, round: Rounding, offset: &FixedOffset) -> ResultT<Scalar> {
+    let x = match to {
+        _ if of.kind() == *to => of.clone(),
+        DataType::I64 => to_i64(of, round)?.into(),
+        DataType::Decimal => to_decimal(of)?.into(),
+        DataType::F64 => to_f64(of)?.into(),
+        DataType::Bool => to_bool(of)?.into(),
+        DataType::Utf8 => match of {
+            Scalar::Unit(_) | Scalar::Top(_) => return Err(unsupported(of, to)),
+            x => text(x).as_str().into(),
+        },
+        DataType::Char => to_char(of)?.into(),
+        DataType::Date(kind) => to_date(of, *kind, offset)?.into(),
+        DataType::Interval => match of {
+            Scalar::Utf8([x]) => x
+                .parse::<Interval>()
+                .map_err(|_| invalid_text(of, to))?
+                .into(),
+            _ => return Err(unsupported(of, to)),
+        },
+        DataType::Money => match of {
+            Scalar::Utf8([x]) => x.parse::<Money>().map_err(|_| invalid_text(of, to))?.into(),
+            _ => return Err(unsupported(of, to)),
+        },
+        DataType::Any => of.clone(),
+        _ => return Err(unsupported(of, to)),
+    };
+    Ok(x)
+}
+
+/// Convert the number `of` to a amount in the `currency`, with `round` if it has more decimals
+/// than the currency allows
+pub fn to_money(of: &Scalar, currency: Currency, round: Rounding) -> ResultT<Money> {
+    let x = match of {
+        Scalar::I64(_) | Scalar::Decimal(_) | Scalar::F64(_) | Scalar::Utf8(_) => to_decimal(of)?,
+        Scalar::Money([x]) if x.currency() == currency => return Ok(*x),
+        Scalar::Money([x]) => {
+            let msg = format!(
+                "Can't convert {} to {}, the exchange rate is not known",
+                x.currency(),
+                currency
+            );
+            return Err(lang(ErrorKind::Invalid, &msg, of));
+        }
+        _ => return Err(unsupported(of, &DataType::Money)),
+    };
+    Ok(Money::round(x, currency, round))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(x: &str) -> Scalar {
+        dsl::dec(Decimal::from_str(x).unwrap())
+    }
+
+    fn show(of: Scalar, to: DataType, round: Rounding) -> String {
+        match cast(&of, &to, round, &utc()) {
+            Ok(x) => x.to_string(),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    fn check(of: Scalar, to: DataType) -> String {
+        show(of, to, Rounding::default())
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(check(dsl::int(1), DataType::Decimal), "1d");
+        assert_eq!(check(dsl::int(1), DataType::F64), "1f");
+        assert_eq!(check(dec("1.25"), DataType::F64), "1.25f");
+        assert_eq!(check(dsl::float(0.5.into()), DataType::Decimal), "0.5d");
+        assert_eq!(check(Scalar::Bool([true]), DataType::I64), "1");
+
+        assert_eq!(check(dec("2.5"), DataType::I64), "2");
+        assert_eq!(show(dec("2.5"), DataType::I64, Rounding::HalfUp), "3");
+        assert_eq!(show(dec("-2.1"), DataType::I64, Rounding::Floor), "-3");
+        assert_eq!(show(dec("2.9"), DataType::I64, Rounding::Down), "2");
+        assert_eq!(
+            show(dsl::float(2.1.into()), DataType::I64, Rounding::Ceiling),
+            "3"
+        );
+        assert_eq!(
+            Rounding::HalfUp
+                .round(Decimal::from_str("1.005").unwrap(), 2)
+                .to_string(),
+            "1.01"
+        );
+
+        assert_eq!(
+            check(dsl::float(1e30.into()), DataType::I64),
+            "The value is out of the range of Int: value = 1000000000000000000000000000000"
+        );
+        assert_eq!(
+            check(dsl::float(f64::NAN.into()), DataType::Decimal),
+            "The value is out of the range of Dec: value = NaN"
+        );
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(check(dsl::str(" 42 "), DataType::I64), "42");
+        assert_eq!(check(dsl::str("1.50"), DataType::Decimal), "1.50d");
+        assert_eq!(check(dsl::str("true"), DataType::Bool), "true");
+        assert_eq!(check(dec("1.50"), DataType::Utf8), "\"1.50\"");
+        assert_eq!(check(dsl::int(-1), DataType::Utf8), "\"-1\"");
+
+        assert_eq!(
+            check(dsl::str("1.5"), DataType::I64),
+            "The text is not a valid Int: value = 1.5"
+        );
+        assert_eq!(
+            check(dsl::str("abc"), DataType::F64),
+            "The text is not a valid Float: value = abc"
+        );
+        assert_eq!(
+            check(Scalar::Bool([true]), DataType::Date(DateKind::Date)),
+            "Can't convert Bool to Date: value = true"
+        );
+
+        assert_eq!(check(dsl::str("ñ"), DataType::Char), "'ñ'");
+        assert_eq!(check(dsl::int(97), DataType::Char), "'a'");
+        assert_eq!(check('a'.into(), DataType::I64), "97");
+        assert_eq!(check('a'.into(), DataType::Utf8), "\"a\"");
+        assert_eq!(
+            check(dsl::str("ab"), DataType::Char),
+            "The text is not a valid Char: value = ab"
+        );
+        assert_eq!(
+            check(dsl::int(-1), DataType::Char),
+            "The value is out of the range of Char: value = -1"
+        );
+    }
+
+    #[test]
+    fn money() {
+        let usd = Currency::from_code("USD").unwrap();
+        let money = |of: Scalar, round| match to_money(&of, usd, round) {
+            Ok(x) => x.to_string(),
+            Err(err) => err.to_string(),
+        };
+        assert_eq!(money(dec("2.345"), Rounding::HalfEven), "2.34 USD");
+        assert_eq!(money(dec("2.345"), Rounding::HalfUp), "2.35 USD");
+        assert_eq!(money(dsl::int(3), Rounding::HalfEven), "3.00 USD");
+        assert_eq!(
+            money("1 EUR".parse::<Money>().unwrap().into(), Rounding::HalfEven),
+            "Can't convert EUR to USD, the exchange rate is not known: value = 1.00 EUR"
+        );
+
+        let price = Scalar::from("12.5 USD".parse::<Money>().unwrap());
+        assert_eq!(check(dsl::str("12.5 USD"), DataType::Money), "12.50 USD");
+        assert_eq!(check(price.clone(), DataType::Decimal), "12.50d");
+        assert_eq!(check(price, DataType::Utf8), "\"12.50 USD\"");
+        assert_eq!(
+            check(dsl::str("12.5"), DataType::Money),
+            "The text is not a valid Money: value = 12.5"
+        );
+    }
+
+    #[test]
+    fn dates() {
+        let day = check(dsl::str("2000-01-02"), DataType::Date(DateKind::Date));
+        assert_eq!(day, "2000-01-02");
+
+        let at = cast(
+            &dsl::str("2000-01-02 10:11:12 +0000"),
+            &DataType::Date(DateKind::DateTime),
+            Rounding::default(),
+            &utc(),
+        )
+        .unwrap();
+        assert_eq!(
+            check(at.clone(), DataType::Date(DateKind::Date)),
+            "2000-01-02"
+        );
+        assert_eq!(
+            check(at.clone(), DataType::Date(DateKind::Time)),
+            "10:11:12"
+        );
+        assert_eq!(check(at, DataType::Utf8), "\"2000-01-02 10:11:12 +0000\"");
+
+        let time = cast(
+            &dsl::str("10:11:12"),
+            &DataType::Date(DateKind::Time),
+            Rounding::default(),
+            &utc(),
+        )
+        .unwrap();
+        assert_eq!(
+            check(time, DataType::Date(DateKind::Date)),
+            "Can't convert Time to Date: value = 10:11:12"
+        );
+        assert_eq!(
+            check(dsl::str("2000-13-01"), DataType::Date(DateKind::Date)),
+            "The text is not a valid Date: value = 2000-13-01"
+        );
+
+        let at = cast(
+            &dsl::str("2000-01-02T10:11"),
+            &DataType::Date(DateKind::DateTime),
+            Rounding::default(),
+            &FixedOffset::east(5 * 3_600),
+        );
+        assert_eq!(at.unwrap().to_string(), "2000-01-02 10:11:00 +0500");
+    }
+}
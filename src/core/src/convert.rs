@@ -1,4 +1,5 @@
 /// Macros and Utilities for making conversions from/to TablaM types
+use crate::algebraic::Variant;
 use crate::prelude::*;
 
 macro_rules! convert {
@@ -158,6 +159,30 @@ impl From<DateT> for Scalar {
     }
 }
 
+impl From<char> for Scalar {
+    fn from(x: char) -> Self {
+        Scalar::Char([x])
+    }
+}
+
+impl From<Interval> for Scalar {
+    fn from(x: Interval) -> Self {
+        Scalar::Interval([x])
+    }
+}
+
+impl From<Money> for Scalar {
+    fn from(x: Money) -> Self {
+        Scalar::Money([x])
+    }
+}
+
+impl From<Variant> for Scalar {
+    fn from(x: Variant) -> Self {
+        Scalar::Sum([x])
+    }
+}
+
 impl From<&str> for Array {
     fn from(x: &str) -> Self {
         Array::Utf8(vec![x.into()])
@@ -0,0 +1,509 @@
+//! # Date arithmetic & the [Interval] between dates.
+//!
+//! A [Interval] keep apart the months, days & seconds, because they not have a fixed length:
+//! `d'2000-01-31' + i'1 month'` is `2000-02-29`, not 30 days later.
+//!
+//! The arithmetic respect the [DateKind]: a `Date` has no time, so adding hours to it is a
+//! error, and a `Time` has no day, so adding days to it is a error. The `Time` wraps around
+//! the day, like a clock.
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{
+    Datelike, Duration, FixedOffset, Months, NaiveDate, NaiveDateTime, TimeZone, Timelike,
+};
+
+use crate::errors::{ErrorCtx, ErrorKind};
+use crate::prelude::*;
+
+const SECS_BY_DAY: i64 = 86_400;
+/// The largest amount of seconds that a [Duration] can hold
+const MAX_SECONDS: i64 = i64::MAX / 1_000;
+
+/// The time between two dates, like `i'1 month 2 days 3 hours'`.
+///
+/// The order is by months, then days, then seconds, so is total but not exact: a month is
+/// always more than any amount of days.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub seconds: i64,
+}
+
+impl Interval {
+    pub const fn new(months: i32, days: i32, seconds: i64) -> Self {
+        Interval {
+            months,
+            days,
+            seconds,
+        }
+    }
+
+    pub const fn days(days: i32) -> Self {
+        Self::new(0, days, 0)
+    }
+
+    pub const fn seconds(seconds: i64) -> Self {
+        Self::new(0, 0, seconds)
+    }
+
+    /// If it has hours, minutes or seconds
+    pub fn has_time(&self) -> bool {
+        self.seconds != 0
+    }
+
+    /// If it has years, months or days
+    pub fn has_date(&self) -> bool {
+        self.months != 0 || self.days != 0
+    }
+
+    pub fn checked_add(&self, rhs: &Interval) -> Option<Self> {
+        Some(Self::new(
+            self.months.checked_add(rhs.months)?,
+            self.days.checked_add(rhs.days)?,
+            self.seconds.checked_add(rhs.seconds)?,
+        ))
+    }
+
+    pub fn checked_sub(&self, rhs: &Interval) -> Option<Self> {
+        self.checked_add(&rhs.checked_neg()?)
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Self::new(
+            self.months.checked_neg()?,
+            self.days.checked_neg()?,
+            self.seconds.checked_neg()?,
+        ))
+    }
+
+    pub fn checked_mul(&self, by: i64) -> Option<Self> {
+        let by32 = i32::try_from(by).ok();
+        Some(Self::new(
+            if self.months == 0 {
+                0
+            } else {
+                self.months.checked_mul(by32?)?
+            },
+            if self.days == 0 {
+                0
+            } else {
+                self.days.checked_mul(by32?)?
+            },
+            self.seconds.checked_mul(by)?,
+        ))
+    }
+}
+
+/// The units of a [Interval] literal, with the long, plural & short names
+const UNITS: [(&str, &str, &str, Interval); 7] = [
+    ("year", "years", "y", Interval::new(12, 0, 0)),
+    ("month", "months", "mo", Interval::new(1, 0, 0)),
+    ("week", "weeks", "w", Interval::days(7)),
+    ("day", "days", "d", Interval::days(1)),
+    ("hour", "hours", "h", Interval::seconds(3_600)),
+    ("minute", "minutes", "min", Interval::seconds(60)),
+    ("second", "seconds", "s", Interval::seconds(1)),
+];
+
+/// Parse pairs of `number unit`, like `1 year 2 days`
+impl FromStr for Interval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err("Expected a number & a unit, like `1 day`".into());
+        }
+        let overflow = || "The interval is too large".to_string();
+
+        let mut x = Interval::default();
+        let mut parts = s.split_whitespace();
+        while let Some(num) = parts.next() {
+            let num: i64 = num
+                .parse()
+                .map_err(|_| format!("Expected a number, found `{num}`"))?;
+            let unit = parts
+                .next()
+                .ok_or_else(|| format!("Expected a unit after `{num}`, like `{num} days`"))?;
+            let (_, _, _, unit) = UNITS
+                .iter()
+                .find(|(one, many, short, _)| [*one, *many, *short].contains(&unit))
+                .ok_or_else(|| {
+                    format!("Unknown unit `{unit}`, expected one of year, month, week, day, hour, minute or second")
+                })?;
+
+            let part = unit.checked_mul(num).ok_or_else(overflow)?;
+            x = x.checked_add(&part).ok_or_else(overflow)?;
+        }
+        Ok(x)
+    }
+}
+
+/// Display as the literal, without the `i'..'`, like `1 year 2 days 3 hours`
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.seconds;
+        let parts = [
+            (self.months as i64 / 12, "year"),
+            (self.months as i64 % 12, "month"),
+            (self.days as i64, "day"),
+            (secs / 3_600, "hour"),
+            (secs % 3_600 / 60, "minute"),
+            (secs % 60, "second"),
+        ];
+        let mut parts = parts.iter().filter(|(x, _)| *x != 0).peekable();
+        if parts.peek().is_none() {
+            return write!(f, "0 seconds");
+        }
+
+        let mut first = true;
+        for (x, unit) in parts {
+            if !first {
+                write!(f, " ")?;
+            }
+            first = false;
+            let plural = if x.abs() == 1 { "" } else { "s" };
+            write!(f, "{x} {unit}{plural}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The parts of a date that can be extracted or truncated, like `x.year()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    Year,
+    Month,
+    /// The ISO week of the year
+    Week,
+    Day,
+    /// The day of the week, from `1` for monday to `7` for sunday
+    Weekday,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl Part {
+    /// Find the part by the name used in the language, like `month`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let x = match name {
+            "year" => Part::Year,
+            "month" => Part::Month,
+            "week" => Part::Week,
+            "day" => Part::Day,
+            "weekday" => Part::Weekday,
+            "hour" => Part::Hour,
+            "minute" => Part::Minute,
+            "second" => Part::Second,
+            _ => return None,
+        };
+        Some(x)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Part::Year => "year",
+            Part::Month => "month",
+            Part::Week => "week",
+            Part::Day => "day",
+            Part::Weekday => "weekday",
+            Part::Hour => "hour",
+            Part::Minute => "minute",
+            Part::Second => "second",
+        }
+    }
+
+    fn is_time(&self) -> bool {
+        matches!(self, Part::Hour | Part::Minute | Part::Second)
+    }
+
+    /// If the [DateKind] has this part, so a `Date` has no hours
+    fn is_in(&self, kind: DateKind) -> bool {
+        match kind {
+            DateKind::Date => !self.is_time(),
+            DateKind::Time => self.is_time(),
+            DateKind::DateTime => true,
+        }
+    }
+}
+
+fn lang(msg: &str, of: &DateT) -> ErrorCore {
+    let err =
+        ErrorLang::new(ErrorKind::Invalid, Some(msg)).with_ctx(ErrorCtx::new("value", &of.fmt()));
+    ErrorCore::Lang(Box::new(err))
+}
+
+fn check_part(of: &DateT, part: Part) -> ResultT<()> {
+    if part.is_in(of.kind) {
+        Ok(())
+    } else {
+        Err(lang(&format!("A {:?} has no {}", of.kind, part.name()), of))
+    }
+}
+
+/// Build the date with the same kind & offset of `of`
+fn with_local(of: &DateT, x: NaiveDateTime) -> DateT {
+    let date = of.date.offset().from_local_datetime(&x).unwrap();
+    DateT {
+        kind: of.kind,
+        date,
+    }
+}
+
+fn add_months(of: NaiveDate, months: i32) -> Option<NaiveDate> {
+    let by = Months::new(months.unsigned_abs());
+    if months < 0 {
+        of.checked_sub_months(by)
+    } else {
+        of.checked_add_months(by)
+    }
+}
+
+fn move_by(op: &'static str, of: &DateT, by: &Interval) -> ResultT<DateT> {
+    match of.kind {
+        DateKind::Date if by.has_time() => {
+            return Err(lang("Can't add hours, minutes or seconds to a Date", of))
+        }
+        DateKind::Time if by.has_date() => {
+            return Err(lang("Can't add years, months or days to a Time", of))
+        }
+        _ => {}
+    }
+    if by.seconds.abs() > MAX_SECONDS {
+        return Err(ErrorCore::Overflow { op });
+    }
+
+    let local = of.date.naive_local();
+    let x = if of.kind == DateKind::Time {
+        let (time, _) = local
+            .time()
+            .overflowing_add_signed(Duration::seconds(by.seconds));
+        Some(NaiveDate::MIN.and_time(time))
+    } else {
+        add_months(local.date(), by.months)
+            .map(|x| x.and_time(local.time()))
+            .and_then(|x| x.checked_add_signed(Duration::days(by.days as i64)))
+            .and_then(|x| x.checked_add_signed(Duration::seconds(by.seconds)))
+    };
+    let x = x.ok_or(ErrorCore::Overflow { op })?;
+    Ok(with_local(of, x))
+}
+
+/// Move the date forward by the interval
+pub fn add(of: &DateT, by: &Interval) -> ResultT<DateT> {
+    move_by("+", of, by)
+}
+
+/// Move the date backward by the interval
+pub fn sub(of: &DateT, by: &Interval) -> ResultT<DateT> {
+    let by = by.checked_neg().ok_or(ErrorCore::Overflow { op: "-" })?;
+    move_by("-", of, &by)
+}
+
+/// The [Interval] from `rhs` to `lhs`, that must be of the same [DateKind]. Is in days for
+/// `Date`, in seconds for `Time`, and both for `DateTime`
+pub fn diff(lhs: &DateT, rhs: &DateT) -> ResultT<Interval> {
+    if lhs.kind != rhs.kind {
+        return Err(ErrorCore::TypeMismatch {
+            expected: DataType::Date(lhs.kind),
+            get: DataType::Date(rhs.kind),
+        });
+    }
+    let secs = (lhs.date - rhs.date).num_seconds();
+    let x = match lhs.kind {
+        DateKind::Time => Some(Interval::seconds(secs)),
+        DateKind::Date => i32::try_from(secs / SECS_BY_DAY).ok().map(Interval::days),
+        DateKind::DateTime => i32::try_from(secs / SECS_BY_DAY)
+            .ok()
+            .map(|days| Interval::new(0, days, secs % SECS_BY_DAY)),
+    };
+    x.ok_or(ErrorCore::Overflow { op: "-" })
+}
+
+/// The same instant of the date time, shown at the `offset`, so
+/// `dt'2000-01-01 10:00 +0000'.to_offset('+05:00')` is `2000-01-01 15:00:00 +0500`
+pub fn to_offset(of: &DateT, offset: &FixedOffset) -> ResultT<DateT> {
+    match of.kind {
+        DateKind::DateTime => Ok(DateT::datetime(of.date.with_timezone(offset))),
+        kind => Err(lang(&format!("A {kind:?} has no offset"), of)),
+    }
+}
+
+/// Extract the `part` of the date, like the year
+pub fn part(of: &DateT, part: Part) -> ResultT<i64> {
+    check_part(of, part)?;
+    let x = of.date.naive_local();
+    let x = match part {
+        Part::Year => x.year() as i64,
+        Part::Month => x.month() as i64,
+        Part::Week => x.iso_week().week() as i64,
+        Part::Day => x.day() as i64,
+        Part::Weekday => x.weekday().number_from_monday() as i64,
+        Part::Hour => x.hour() as i64,
+        Part::Minute => x.minute() as i64,
+        Part::Second => x.second() as i64,
+    };
+    Ok(x)
+}
+
+/// Truncate the date to the start of the `part`, so `d'2000-02-15'.trunc('month')` is
+/// `2000-02-01`. The `week` start on monday
+pub fn trunc(of: &DateT, part: Part) -> ResultT<DateT> {
+    check_part(of, part)?;
+    let x = of.date.naive_local();
+    let (date, time) = (x.date(), x.time());
+    let x = match part {
+        Part::Year => date.with_ordinal(1).map(|x| x.and_hms(0, 0, 0)),
+        Part::Month => date.with_day(1).map(|x| x.and_hms(0, 0, 0)),
+        Part::Week => {
+            let days = date.weekday().num_days_from_monday() as i64;
+            date.checked_sub_signed(Duration::days(days))
+                .map(|x| x.and_hms(0, 0, 0))
+        }
+        Part::Day => Some(date.and_hms(0, 0, 0)),
+        Part::Weekday => {
+            return Err(lang(
+                "Can't truncate to the weekday, use `week` for the start of the week",
+                of,
+            ))
+        }
+        Part::Hour => Some(date.and_hms(time.hour(), 0, 0)),
+        Part::Minute => Some(date.and_hms(time.hour(), time.minute(), 0)),
+        Part::Second => Some(date.and_hms(time.hour(), time.minute(), time.second())),
+    };
+    let x = x.ok_or(ErrorCore::Overflow { op: "trunc" })?;
+    Ok(with_local(of, x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(x: &str) -> DateT {
+        parse_date_t(x).unwrap()
+    }
+
+    fn datetime(x: &str) -> DateT {
+        parse_date_time_t(x).unwrap()
+    }
+
+    fn interval(x: &str) -> Interval {
+        x.parse().unwrap()
+    }
+
+    fn show<T: ToString>(x: ResultT<T>) -> String {
+        match x {
+            Ok(x) => x.to_string(),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    fn show_date(x: ResultT<DateT>) -> String {
+        show(x.map(|x| x.fmt()))
+    }
+
+    #[test]
+    fn intervals() {
+        let x = interval("1 year 14 months 2 w 1 day 90 min -5 s");
+        assert_eq!(x, Interval::new(26, 15, 5_395));
+        assert_eq!(
+            x.to_string(),
+            "2 years 2 months 15 days 1 hour 29 minutes 55 seconds"
+        );
+        assert_eq!(interval("-1 day").to_string(), "-1 day");
+        assert_eq!(Interval::default().to_string(), "0 seconds");
+        assert_eq!(interval(&x.to_string()), x);
+
+        assert_eq!(
+            "1 day 2".parse::<Interval>(),
+            Err("Expected a unit after `2`, like `2 days`".into())
+        );
+        assert_eq!(
+            "1 fortnight".parse::<Interval>(),
+            Err("Unknown unit `fortnight`, expected one of year, month, week, day, hour, minute or second".into())
+        );
+        assert_eq!(
+            "99999999999 years".parse::<Interval>(),
+            Err("The interval is too large".into())
+        );
+        assert_eq!(x.checked_mul(2).unwrap(), Interval::new(52, 30, 10_790));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let day = date("2000-01-31");
+        assert_eq!(show_date(add(&day, &interval("1 month"))), "2000-02-29");
+        assert_eq!(
+            show_date(sub(&day, &interval("1 year 1 day"))),
+            "1999-01-30"
+        );
+        assert_eq!(
+            show_date(add(&day, &interval("1 hour"))),
+            "Can't add hours, minutes or seconds to a Date: value = 2000-01-31"
+        );
+
+        let at = datetime("2000-01-31 23:00:00 +0200");
+        assert_eq!(
+            show_date(add(&at, &interval("1 month 2 hours"))),
+            "2000-03-01 01:00:00 +0200"
+        );
+
+        let time = parse_time_t("23:30:00").unwrap();
+        assert_eq!(show_date(add(&time, &interval("1 hour"))), "00:30:00");
+        assert_eq!(
+            show_date(add(&time, &interval("1 day"))),
+            "Can't add years, months or days to a Time: value = 23:30:00"
+        );
+
+        assert_eq!(show(diff(&date("2000-03-01"), &day)), "30 days");
+        assert_eq!(
+            show(diff(&at, &datetime("2000-01-30 20:30:00 +0000"))),
+            "1 day 30 minutes"
+        );
+        assert_eq!(
+            show(diff(&day, &at)),
+            "Type mismatch: expected Date, found DateTime"
+        );
+    }
+
+    #[test]
+    fn parts() {
+        let at = datetime("2000-02-16 10:11:12 +0000");
+        let parts = [
+            (Part::Year, 2000),
+            (Part::Month, 2),
+            (Part::Week, 7),
+            (Part::Day, 16),
+            (Part::Weekday, 3),
+            (Part::Hour, 10),
+            (Part::Minute, 11),
+            (Part::Second, 12),
+        ];
+        for (p, x) in parts {
+            assert_eq!(part(&at, p).unwrap(), x, "{p:?}");
+        }
+        assert_eq!(
+            show(part(&date("2000-02-16"), Part::Hour)),
+            "A Date has no hour: value = 2000-02-16"
+        );
+
+        assert_eq!(
+            show_date(trunc(&at, Part::Year)),
+            "2000-01-01 00:00:00 +0000"
+        );
+        assert_eq!(
+            show_date(trunc(&at, Part::Week)),
+            "2000-02-14 00:00:00 +0000"
+        );
+        assert_eq!(
+            show_date(trunc(&at, Part::Hour)),
+            "2000-02-16 10:00:00 +0000"
+        );
+        assert_eq!(
+            show_date(trunc(&date("2000-02-16"), Part::Month)),
+            "2000-02-01"
+        );
+    }
+}
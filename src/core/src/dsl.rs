@@ -11,7 +11,7 @@ pub fn array<T: Into<Array>>(x: T) -> Array {
 
 pub fn vector<T: NativeKind + Into<Array>>(x: T) -> Vector {
     let rows = T::num_rows();
-    Vector::new(Schema::new_scalar(T::kind()), rows, x.into())
+    Vector::new(Schema::new_scalar(T::kind()), rows, x.into()).expect("A vector without keys")
 }
 
 pub fn int(x: i64) -> Scalar {
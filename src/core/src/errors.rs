@@ -5,12 +5,109 @@ use std::ops::Range;
 
 use text_size::TextRange;
 
+use crate::schema::Column;
 use crate::types::{DataType, FileId};
+use crate::utils::format_list;
+
+/// The numeric code of each [ErrorCore], as reported in the diagnostics.
+///
+/// Start at `100` so it not clash with the codes of the parser
+pub enum ErrorCoreCode {
+    TypeMismatch = 100,
+    Overflow,
+    DivisionByZero,
+    Unsupported,
+    VarNotFound,
+    VarImmutable,
+    FieldNotFound,
+    Lang,
+}
 
 /// Define the internal errors
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ErrorCore {
-    TypeMismatch { expected: DataType, get: DataType },
+    TypeMismatch {
+        expected: DataType,
+        get: DataType,
+    },
+    Overflow {
+        op: &'static str,
+    },
+    DivisionByZero,
+    Unsupported {
+        op: &'static str,
+        kind: DataType,
+    },
+    /// Like [ErrorCore::Unsupported], for a relation like a `Table`
+    UnsupportedRel {
+        op: &'static str,
+        rel: String,
+    },
+    VarNotFound {
+        name: String,
+    },
+    VarImmutable {
+        name: String,
+    },
+    FieldNotFound {
+        column: Column,
+    },
+    /// A error with its [ErrorKind] & context, like the values of a duplicated key
+    Lang(Box<ErrorLang>),
+}
+
+impl ErrorCore {
+    pub fn error_code(&self) -> ErrorCoreCode {
+        match self {
+            ErrorCore::TypeMismatch { .. } => ErrorCoreCode::TypeMismatch,
+            ErrorCore::Overflow { .. } => ErrorCoreCode::Overflow,
+            ErrorCore::DivisionByZero => ErrorCoreCode::DivisionByZero,
+            ErrorCore::Unsupported { .. } | ErrorCore::UnsupportedRel { .. } => {
+                ErrorCoreCode::Unsupported
+            }
+            ErrorCore::VarNotFound { .. } => ErrorCoreCode::VarNotFound,
+            ErrorCore::VarImmutable { .. } => ErrorCoreCode::VarImmutable,
+            ErrorCore::FieldNotFound { .. } => ErrorCoreCode::FieldNotFound,
+            ErrorCore::Lang(_) => ErrorCoreCode::Lang,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorCore::TypeMismatch { expected, get } => {
+                write!(f, "Type mismatch: expected {expected}, found {get}")
+            }
+            ErrorCore::Overflow { op } => write!(f, "Arithmetic overflow in `{op}`"),
+            ErrorCore::DivisionByZero => write!(f, "Division by zero"),
+            ErrorCore::Unsupported { op, kind } => {
+                write!(f, "The operator `{op}` is not supported for {kind}")
+            }
+            ErrorCore::UnsupportedRel { op, rel } => {
+                write!(f, "The operator `{op}` is not supported for a {rel}")
+            }
+            ErrorCore::VarNotFound { name } => write!(f, "The variable `{name}` is not defined"),
+            ErrorCore::VarImmutable { name } => write!(
+                f,
+                "The variable `{name}` is immutable, declare it with `var` to change it"
+            ),
+            ErrorCore::FieldNotFound { column } => {
+                write!(f, "The column `{column}` is not in the schema")
+            }
+            ErrorCore::Lang(err) => {
+                match err.msg() {
+                    Some(msg) => write!(f, "{msg}")?,
+                    None => write!(f, "{:?}", err.kind())?,
+                }
+                if !err.context().is_empty() {
+                    write!(f, ": ")?;
+                    format_list(err.context(), err.context().len(), "", "", f)?;
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 pub type ResultT<T> = Result<T, ErrorCore>;
@@ -39,7 +136,7 @@ pub struct RangeCode(pub TextRange);
 
 impl PartialOrd for RangeCode {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.0.len().cmp(&other.0.len()))
+        Some(self.cmp(other))
     }
 }
 
@@ -76,6 +173,14 @@ impl Span {
 
         start..end
     }
+
+    /// The span from the start of `self` to the end of `other`
+    pub fn cover(&self, other: &Span) -> Span {
+        Span {
+            range: self.range.0.cover(other.range.0).into(),
+            ..*self
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -84,6 +189,29 @@ pub struct ErrorCtx {
     value: String,
 }
 
+impl ErrorCtx {
+    pub fn new(key: &str, value: &str) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl fmt::Display for ErrorCtx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.key, self.value)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ErrorLoc {
     Custom { source: String, loc: String },
@@ -111,6 +239,18 @@ impl ErrorLang {
         }
     }
 
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn msg(&self) -> Option<&str> {
+        self.msg.as_deref()
+    }
+
+    pub fn context(&self) -> &[ErrorCtx] {
+        self.context.as_deref().unwrap_or_default()
+    }
+
     pub fn with_span(self, of: Span) -> Self {
         let mut x = self;
         if let Some(ref mut s) = x.source {
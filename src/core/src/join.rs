@@ -0,0 +1,319 @@
+//! # Relational joins.
+//!
+//! Combine the rows of two relations, implemented only with the [Rel] trait so we can do
+//! joins between anything: a [Scalar], [Vector], [Table] or [Tree].
+//!
+//! The equi-joins are *hash joins*: the rows of the `rhs` are indexed by the values of the
+//! columns to match, so each row of the `lhs` find its matches without scanning the `rhs`.
+//!
+//! The result is a [Table] without keys. When the name of a field is in both sides, it is
+//! prefixed with the name of its relation, like `products.price`.
+use std::collections::HashMap;
+
+use crate::errors::{ErrorCtx, ErrorKind};
+use crate::prelude::*;
+use crate::query::to_table;
+
+/// A side of a join: The relation & the name used to prefix its fields that collide
+#[derive(Debug, Clone, Copy)]
+pub struct Named<'a> {
+    pub name: &'a str,
+    pub rel: &'a dyn Rel,
+}
+
+impl<'a> Named<'a> {
+    pub fn new(name: &'a str, rel: &'a dyn Rel) -> Self {
+        Self { name, rel }
+    }
+}
+
+/// The columns to match of the `lhs` & `rhs`, in pairs
+pub type On = [(Column, Column)];
+
+fn prefixed(name: &str, field: &Field, collide: bool) -> Field {
+    if collide {
+        Field::new(&format!("{}.{}", name, field.name), field.kind.clone())
+    } else {
+        field.clone()
+    }
+}
+
+/// The schema with all the fields of the `lhs`, then the fields at `rhs_pos` of the `rhs`, that
+/// are nullable if `outer`
+fn join_schema(lhs: Named<'_>, rhs: Named<'_>, rhs_pos: &[usize], outer: bool) -> ResultT<Schema> {
+    let (of_lhs, of_rhs) = (lhs.rel.schema(), rhs.rel.schema());
+    let of_rhs: Vec<_> = rhs_pos.iter().map(|x| &of_rhs.fields[*x]).collect();
+    let in_lhs = |name: &str| of_lhs.fields.iter().any(|x| x.name == name);
+    let in_rhs = |name: &str| of_rhs.iter().any(|x| x.name == name);
+
+    // With the same name, the prefix not make the fields different
+    if lhs.name == rhs.name {
+        if let Some(field) = of_rhs.iter().find(|x| in_lhs(&x.name)) {
+            let err = ErrorLang::new(
+                ErrorKind::Duplicated,
+                Some("The relations need different names to join fields with the same name"),
+            )
+            .with_ctx(ErrorCtx::new(lhs.name, &field.name));
+            return Err(ErrorCore::Lang(Box::new(err)));
+        }
+    }
+
+    let fields: Vec<_> = of_lhs
+        .fields
+        .iter()
+        .map(|x| prefixed(lhs.name, x, in_rhs(&x.name)))
+        .chain(
+            of_rhs
+                .iter()
+                .map(|x| prefixed(rhs.name, x, in_lhs(&x.name)))
+                .map(|x| if outer { x.with_nullable() } else { x }),
+        )
+        .collect();
+    Ok(Schema::new(None, &fields))
+}
+
+fn resolve_on(lhs: &Schema, rhs: &Schema, on: &On) -> ResultT<(Vec<usize>, Vec<usize>)> {
+    let (of_lhs, of_rhs): (Vec<_>, Vec<_>) = on.iter().cloned().unzip();
+    Ok((lhs.resolve_all(&of_lhs)?, rhs.resolve_all(&of_rhs)?))
+}
+
+fn pick(row: &[Scalar], pos: &[usize]) -> Vec<Scalar> {
+    pos.iter().map(|x| row[*x].clone()).collect()
+}
+
+/// The rows of the `lhs`, each one with the positions of the rows of the `rhs` that have the
+/// same values for the columns `on`
+fn hash_join(
+    lhs: &dyn Rel,
+    rhs: &[Vec<Scalar>],
+    lhs_key: &[usize],
+    rhs_key: &[usize],
+) -> Vec<(Vec<Scalar>, Vec<usize>)> {
+    let mut index: HashMap<Vec<Scalar>, Vec<usize>> = HashMap::new();
+    for (pos, row) in rhs.iter().enumerate() {
+        index.entry(pick(row, rhs_key)).or_default().push(pos);
+    }
+
+    lhs.rows()
+        .map(|row| {
+            let found = index.get(&pick(&row, lhs_key)).cloned();
+            (row, found.unwrap_or_default())
+        })
+        .collect()
+}
+
+/// Build the rows of the join, with the `rhs` values at `rhs_pos`.
+///
+/// If `outer`, the rows of `lhs` without match are kept with [Scalar::Unit] for the `rhs`, so
+/// its fields are nullable
+fn join_rows(
+    lhs: Named<'_>,
+    rhs: Named<'_>,
+    on: &On,
+    rhs_pos: &[usize],
+    outer: bool,
+) -> ResultT<Table> {
+    let schema = join_schema(lhs, rhs, rhs_pos, outer)?;
+    let (lhs_key, rhs_key) = resolve_on(&lhs.rel.schema(), &rhs.rel.schema(), on)?;
+    let of_rhs: Vec<_> = rhs.rel.rows().collect();
+
+    let mut rows = Vec::new();
+    for (row, found) in hash_join(lhs.rel, &of_rhs, &lhs_key, &rhs_key) {
+        for pos in &found {
+            let mut joined = row.clone();
+            joined.extend(pick(&of_rhs[*pos], rhs_pos));
+            rows.push(joined);
+        }
+        if outer && found.is_empty() {
+            let mut joined = row;
+            joined.extend(rhs_pos.iter().map(|_| Scalar::Unit([()])));
+            rows.push(joined);
+        }
+    }
+    to_table(schema, rows.into_iter())
+}
+
+fn all_fields(of: &dyn Rel) -> Vec<usize> {
+    (0..of.schema().len()).collect()
+}
+
+/// Cross join: each row of the `lhs` with each row of the `rhs`
+pub fn cross(lhs: Named<'_>, rhs: Named<'_>) -> ResultT<Table> {
+    join_rows(lhs, rhs, &[], &all_fields(rhs.rel), false)
+}
+
+/// Inner join: the rows of the `lhs` & `rhs` that have the same values for the columns `on`
+pub fn inner(lhs: Named<'_>, rhs: Named<'_>, on: &On) -> ResultT<Table> {
+    join_rows(lhs, rhs, on, &all_fields(rhs.rel), false)
+}
+
+/// Left outer join: like [inner], but the rows of the `lhs` without a match are kept with
+/// [Scalar::Unit] for the fields of the `rhs`, that are nullable
+pub fn left(lhs: Named<'_>, rhs: Named<'_>, on: &On) -> ResultT<Table> {
+    join_rows(lhs, rhs, on, &all_fields(rhs.rel), true)
+}
+
+/// Natural join: a [inner] join on the fields with the same name, that are only kept once.
+///
+/// Without common fields, is a [cross] join
+pub fn natural(lhs: Named<'_>, rhs: Named<'_>) -> ResultT<Table> {
+    let (of_lhs, of_rhs) = (lhs.rel.schema(), rhs.rel.schema());
+    let common: Vec<_> = of_lhs
+        .fields
+        .iter()
+        .filter(|x| of_rhs.resolve_name(&x.name).is_some())
+        .map(|x| (x.name.as_str().into(), x.name.as_str().into()))
+        .collect();
+    let rhs_pos: Vec<_> = of_rhs
+        .fields
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| of_lhs.resolve_name(&x.name).is_none())
+        .map(|(pos, _)| pos)
+        .collect();
+    join_rows(lhs, rhs, &common, &rhs_pos, false)
+}
+
+/// The rows of the `lhs` that have (or not, if `!found`) a match in the `rhs`
+fn filter_matches(lhs: &dyn Rel, rhs: &dyn Rel, on: &On, found: bool) -> ResultT<Table> {
+    let schema = lhs.schema();
+    let (lhs_key, rhs_key) = resolve_on(&schema, &rhs.schema(), on)?;
+    let of_rhs: Vec<_> = rhs.rows().collect();
+    let rows = hash_join(lhs, &of_rhs, &lhs_key, &rhs_key)
+        .into_iter()
+        .filter(|(_, matches)| matches.is_empty() != found)
+        .map(|(row, _)| row);
+    to_table(schema, rows)
+}
+
+/// Semi join: the rows of the `lhs` that have a match in the `rhs`, only with the fields of
+/// the `lhs`, so each row is kept once
+pub fn semi(lhs: &dyn Rel, rhs: &dyn Rel, on: &On) -> ResultT<Table> {
+    filter_matches(lhs, rhs, on, true)
+}
+
+/// Anti join: the rows of the `lhs` that not have a match in the `rhs`
+pub fn anti(lhs: &dyn Rel, rhs: &dyn Rel, on: &On) -> ResultT<Table> {
+    filter_matches(lhs, rhs, on, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn products() -> Table {
+        let schema = Schema::new(
+            Some(0),
+            &[
+                Field::new("id", DataType::I64),
+                Field::new("name", DataType::Utf8),
+                Field::new("price", DataType::I64),
+            ],
+        );
+        let data = vec![
+            dsl::int(1),
+            dsl::str("Soda"),
+            dsl::int(2),
+            dsl::int(2),
+            dsl::str("Tea"),
+            dsl::int(3),
+            dsl::int(3),
+            dsl::str("Cake"),
+            dsl::int(5),
+        ];
+        Table::from_rows(schema, data).unwrap()
+    }
+
+    fn sales() -> Table {
+        let schema = Schema::new(
+            None,
+            &[
+                Field::new("id", DataType::I64),
+                Field::new("price", DataType::I64),
+            ],
+        );
+        let data = vec![
+            dsl::int(1),
+            dsl::int(4),
+            dsl::int(1),
+            dsl::int(6),
+            dsl::int(2),
+            dsl::int(3),
+        ];
+        Table::from_rows(schema, data).unwrap()
+    }
+
+    #[test]
+    fn cross_join() {
+        let (products, qty) = (products(), dsl::int(10));
+        let result = cross(Named::new("products", &products), Named::new("qty", &qty)).unwrap();
+        assert_eq!(
+            result.to_string(),
+            "[id:Int, name:Str, price:Int, it:Int; 1, \"Soda\", 2, 10; 2, \"Tea\", 3, 10; 3, \"Cake\", 5, 10]"
+        );
+
+        let sales = sales();
+        let result = cross(
+            Named::new("products", &products),
+            Named::new("sales", &sales),
+        )
+        .unwrap();
+        assert_eq!(result.rows, 9);
+        assert_eq!(
+            result.schema.to_string(),
+            "products.id:Int, name:Str, products.price:Int, sales.id:Int, sales.price:Int"
+        );
+    }
+
+    #[test]
+    fn equi_join() {
+        let (products, sales) = (products(), sales());
+        let (p, s) = (Named::new("p", &products), Named::new("s", &sales));
+        let on = [("id".into(), "id".into())];
+
+        assert_eq!(
+            inner(p, s, &on).unwrap().to_string(),
+            "[p.id:Int, name:Str, p.price:Int, s.id:Int, s.price:Int; 1, \"Soda\", 2, 1, 4; 1, \"Soda\", 2, 1, 6; 2, \"Tea\", 3, 2, 3]"
+        );
+        let joined = left(p, s, &on).unwrap();
+        assert_eq!(
+            joined.to_string(),
+            "[p.id:Int, name:Str, p.price:Int, s.id:Int?, s.price:Int?; 1, \"Soda\", 2, 1, 4; 1, \"Soda\", 2, 1, 6; 2, \"Tea\", 3, 2, 3; 3, \"Cake\", 5, (), ()]"
+        );
+        // Only the nullable fields are not stored as typed arrays
+        assert!(matches!(joined.cols[0], Array::I64(_)));
+        assert!(matches!(joined.cols[3], Array::Scalar(_)));
+        // Both `id` & `price` are common
+        assert_eq!(
+            natural(p, s).unwrap().to_string(),
+            "[id:Int, name:Str, price:Int; 2, \"Tea\", 3]"
+        );
+
+        let err = inner(p, s, &[("id".into(), "qty".into())]).unwrap_err();
+        assert_eq!(err.to_string(), "The column `#qty` is not in the schema");
+
+        let err = inner(p, Named::new("p", &sales), &on).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The relations need different names to join fields with the same name: p = id"
+        );
+    }
+
+    #[test]
+    fn filter_join() {
+        let (products, sales) = (products(), sales());
+        let on = [("id".into(), 0.into())];
+
+        let sold = semi(&products, &sales, &on).unwrap();
+        assert_eq!(
+            sold.to_string(),
+            "[id:Int, name:Str, price:Int; 1, \"Soda\", 2; 2, \"Tea\", 3]"
+        );
+        assert_eq!(sold.schema, products.schema);
+
+        assert_eq!(
+            anti(&products, &sales, &on).unwrap().to_string(),
+            "[id:Int, name:Str, price:Int; 3, \"Cake\", 5]"
+        );
+    }
+}
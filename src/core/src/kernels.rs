@@ -0,0 +1,852 @@
+//! # Element-wise operators over [Array].
+//!
+//! The array-language side of the operators of [crate::ops]: `[1; 2] + [3; 4]` apply `+` to
+//! each pair of values, and in `1 + [1; 2; 3]` the [Scalar] is *broadcast* to each value of
+//! the [Vector].
+//!
+//! Two vectors must have the same *rank*, the number of rows, so `[1] + [3; 4]` is a error.
+//!
+//! The `and`, `or` & `not` of [Scalar::Bit] are bitwise, like the ones of [crate::bits].
+//!
+//! The arrays of numbers, booleans & bits have specialized kernels, the others apply the operator
+//! of [crate::ops] to each pair of values, so the errors are the same of the scalars.
+use chrono::FixedOffset;
+
+use crate::bits::{self, BitOp, Shift};
+use crate::cast::{self, Rounding};
+use crate::dates::{self, Part};
+use crate::errors::{ErrorCtx, ErrorKind};
+use crate::money::Currency;
+use crate::ops::{self, checked, checked_div};
+use crate::prelude::*;
+use crate::text;
+
+/// A operand of the element-wise operators
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    Scalar(Scalar),
+    Vector(Vector),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Arith {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Arith {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Arith::Add => "+",
+            Arith::Sub => "-",
+            Arith::Mul => "*",
+            Arith::Div => "/",
+        }
+    }
+
+    fn scalar(&self, lhs: &Scalar, rhs: &Scalar) -> ResultT<Scalar> {
+        match self {
+            Arith::Add => ops::add(lhs, rhs),
+            Arith::Sub => ops::sub(lhs, rhs),
+            Arith::Mul => ops::mul(lhs, rhs),
+            Arith::Div => ops::div(lhs, rhs),
+        }
+    }
+
+    /// The type of the result, the same of [Self::scalar], so a empty vector keep it
+    fn kind(&self, lhs: &DataType, rhs: &DataType) -> DataType {
+        use DataType::*;
+        match (self, lhs, rhs) {
+            (Arith::Div, I64, I64) | (Arith::Div, Money, Money) => Decimal,
+            (_, Money, _) | (Arith::Mul, _, Money) => Money,
+            (Arith::Sub, Date(_), Date(_)) => Interval,
+            (Arith::Add | Arith::Sub, Date(kind), Interval)
+            | (Arith::Add, Interval, Date(kind)) => Date(*kind),
+            (Arith::Mul, Interval, I64) | (Arith::Mul, I64, Interval) => Interval,
+            (_, Date(_), _) | (_, _, Date(_)) => Any,
+            (_, lhs, rhs) => same(lhs, rhs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Logic {
+    And,
+    Or,
+}
+
+impl Logic {
+    fn bit(&self) -> BitOp {
+        match self {
+            Logic::And => BitOp::And,
+            Logic::Or => BitOp::Or,
+        }
+    }
+
+    fn scalar(&self, lhs: &Scalar, rhs: &Scalar) -> ResultT<Scalar> {
+        let x = match (lhs, rhs) {
+            (Scalar::Bit(_), Scalar::Bit(_)) => return bits::bitwise(self.bit(), lhs, rhs),
+            (Scalar::Bool([a]), Scalar::Bool([b])) => match self {
+                Logic::And => *a && *b,
+                Logic::Or => *a || *b,
+            },
+            (Scalar::Bool(_), x) | (x, _) => {
+                return Err(ErrorCore::TypeMismatch {
+                    expected: DataType::Bool,
+                    get: x.kind(),
+                })
+            }
+        };
+        Ok(x.into())
+    }
+
+    fn kind(&self, lhs: &DataType, rhs: &DataType) -> DataType {
+        match (lhs, rhs) {
+            (DataType::Bit, DataType::Bit) | (DataType::Bool, DataType::Bool) => lhs.clone(),
+            _ => DataType::Any,
+        }
+    }
+}
+
+/// The type of the result of a operator over values of the same type
+fn same(lhs: &DataType, rhs: &DataType) -> DataType {
+    if lhs == rhs {
+        lhs.clone()
+    } else {
+        DataType::Any
+    }
+}
+
+/// Apply `f` to each pair of values, failing on the first error
+fn zip<A: Copy, T>(a: &[A], b: &[A], f: impl Fn(A, A) -> ResultT<T>) -> ResultT<Vec<T>> {
+    a.iter().zip(b).map(|(a, b)| f(*a, *b)).collect()
+}
+
+/// Apply `f` to each pair of values of arrays without a specialized kernel
+fn zip_scalars(
+    lhs: &Array,
+    rhs: &Array,
+    f: impl Fn(&Scalar, &Scalar) -> ResultT<Scalar>,
+) -> ResultT<Array> {
+    let values = (0..lhs.len())
+        .filter_map(|x| Some((lhs.get(x)?, rhs.get(x)?)))
+        .map(|(a, b)| f(&a, &b))
+        .collect::<ResultT<Vec<_>>>()?;
+    Ok(Array::from_scalars(&DataType::Any, values))
+}
+
+/// Apply `f` to each value of arrays without a specialized kernel, building the array of `kind`
+/// so the data stay as typed as the schema
+fn map_scalars(
+    of: &Array,
+    kind: &DataType,
+    f: impl Fn(&Scalar) -> ResultT<Scalar>,
+) -> ResultT<Array> {
+    let values = (0..of.len())
+        .filter_map(|x| of.get(x))
+        .map(|x| f(&x))
+        .collect::<ResultT<Vec<_>>>()?;
+    Ok(Array::from_scalars(kind, values))
+}
+
+fn arith(op: Arith, lhs: &Array, rhs: &Array) -> ResultT<Array> {
+    let name = op.symbol();
+    let x = match (lhs, rhs) {
+        (Array::I64(a), Array::I64(b)) => match op {
+            Arith::Add => Array::I64(zip(a, b, |a, b| checked(name, a.checked_add(b)))?),
+            Arith::Sub => Array::I64(zip(a, b, |a, b| checked(name, a.checked_sub(b)))?),
+            Arith::Mul => Array::I64(zip(a, b, |a, b| checked(name, a.checked_mul(b)))?),
+            // Like the scalars, not silently lose precision
+            Arith::Div => Array::Decimal(zip(a, b, |a, b| {
+                let (a, b) = (Decimal::from(a), Decimal::from(b));
+                checked_div(name, a.checked_div(b), b.is_zero())
+            })?),
+        },
+        (Array::Decimal(a), Array::Decimal(b)) => Array::Decimal(zip(a, b, |a, b| match op {
+            Arith::Add => checked(name, a.checked_add(b)),
+            Arith::Sub => checked(name, a.checked_sub(b)),
+            Arith::Mul => checked(name, a.checked_mul(b)),
+            Arith::Div => checked_div(name, a.checked_div(b), b.is_zero()),
+        })?),
+        (Array::F64(a), Array::F64(b)) => Array::F64(zip(a, b, |a, b| match op {
+            Arith::Add => Ok(a + b),
+            Arith::Sub => Ok(a - b),
+            Arith::Mul => Ok(a * b),
+            Arith::Div => Ok(a / b),
+        })?),
+        _ => zip_scalars(lhs, rhs, |a, b| op.scalar(a, b))?,
+    };
+    Ok(x)
+}
+
+fn logic(op: Logic, lhs: &Array, rhs: &Array) -> ResultT<Array> {
+    match (lhs, rhs) {
+        (Array::Bit(a), Array::Bit(b)) => Ok(Array::Bit(op.bit().apply(a, b))),
+        (Array::Bool(a), Array::Bool(b)) => Ok(Array::Bool(zip(a, b, |a, b| match op {
+            Logic::And => Ok(a && b),
+            Logic::Or => Ok(a || b),
+        })?)),
+        _ => zip_scalars(lhs, rhs, |a, b| op.scalar(a, b)),
+    }
+}
+
+/// The vector with the `data` computed from `of`, so it keep the names of the fields with the
+/// `kinds` of the result. The types come from the operator and not from the values, so a empty
+/// vector is typed too
+fn with_data(of: &Vector, kinds: Vec<DataType>, data: Array) -> ResultT<Vector> {
+    let fields: Vec<_> = of
+        .schema
+        .fields
+        .iter()
+        .zip(&kinds)
+        .map(|(field, kind)| Field::new(&field.name, kind.clone()))
+        .collect();
+    // A column stay as typed as the schema
+    let data = match data {
+        Array::Scalar(x) if kinds.len() == 1 => Array::from_scalars(&kinds[0], x),
+        data => data,
+    };
+    // The values changed, so the keys could be repeated now
+    Vector::new(Schema::new(None, &fields), of.rows, data)
+}
+
+/// Repeat the scalar `of` for the `len` values of a vector
+fn broadcast(of: &Scalar, len: usize) -> Array {
+    Array::from_scalars(&of.kind(), vec![of.clone(); len])
+}
+
+fn rank_mismatch(lhs: &Vector, rhs: &Vector) -> ErrorCore {
+    let (msg, lhs, rhs) = if lhs.rows != rhs.rows {
+        ("The vectors have a different rank", lhs.rows, rhs.rows)
+    } else {
+        (
+            "The vectors have a different number of columns",
+            lhs.schema.len(),
+            rhs.schema.len(),
+        )
+    };
+    let err = ErrorLang::new(ErrorKind::Invalid, Some(msg))
+        .with_ctx(ErrorCtx::new("lhs", &lhs.to_string()))
+        .with_ctx(ErrorCtx::new("rhs", &rhs.to_string()));
+    ErrorCore::Lang(Box::new(err))
+}
+
+fn kinds(of: &Vector) -> impl Iterator<Item = &DataType> {
+    of.schema.fields.iter().map(|x| &x.kind)
+}
+
+/// Apply `scalar` to scalars and `kernel` to vectors, with the type of the result of each field
+/// given by `kind`
+fn binary(
+    lhs: &Operand,
+    rhs: &Operand,
+    scalar: impl Fn(&Scalar, &Scalar) -> ResultT<Scalar>,
+    kind: impl Fn(&DataType, &DataType) -> DataType,
+    kernel: impl Fn(&Array, &Array) -> ResultT<Array>,
+) -> ResultT<Operand> {
+    let x = match (lhs, rhs) {
+        (Operand::Scalar(a), Operand::Scalar(b)) => Operand::Scalar(scalar(a, b)?),
+        (Operand::Vector(a), Operand::Vector(b)) => {
+            if a.rows != b.rows || a.schema.len() != b.schema.len() {
+                return Err(rank_mismatch(a, b));
+            }
+            let kinds = kinds(a).zip(kinds(b)).map(|(a, b)| kind(a, b)).collect();
+            Operand::Vector(with_data(a, kinds, kernel(&a.data, &b.data)?)?)
+        }
+        (Operand::Scalar(a), Operand::Vector(b)) => {
+            let kinds = kinds(b).map(|b| kind(&a.kind(), b)).collect();
+            let a = broadcast(a, b.data.len());
+            Operand::Vector(with_data(b, kinds, kernel(&a, &b.data)?)?)
+        }
+        (Operand::Vector(a), Operand::Scalar(b)) => {
+            let kinds = kinds(a).map(|a| kind(a, &b.kind())).collect();
+            let b = broadcast(b, a.data.len());
+            Operand::Vector(with_data(a, kinds, kernel(&a.data, &b)?)?)
+        }
+    };
+    Ok(x)
+}
+
+/// Like [binary], for the operators of one value
+fn unary(
+    of: &Operand,
+    scalar: impl Fn(&Scalar) -> ResultT<Scalar>,
+    kind: impl Fn(&DataType) -> DataType,
+    kernel: impl Fn(&Array) -> ResultT<Array>,
+) -> ResultT<Operand> {
+    let x = match of {
+        Operand::Scalar(x) => Operand::Scalar(scalar(x)?),
+        Operand::Vector(x) => {
+            let kinds = kinds(x).map(kind).collect();
+            Operand::Vector(with_data(x, kinds, kernel(&x.data)?)?)
+        }
+    };
+    Ok(x)
+}
+
+pub fn add(lhs: &Operand, rhs: &Operand) -> ResultT<Operand> {
+    binary(
+        lhs,
+        rhs,
+        ops::add,
+        |a, b| Arith::Add.kind(a, b),
+        |a, b| arith(Arith::Add, a, b),
+    )
+}
+
+pub fn sub(lhs: &Operand, rhs: &Operand) -> ResultT<Operand> {
+    binary(
+        lhs,
+        rhs,
+        ops::sub,
+        |a, b| Arith::Sub.kind(a, b),
+        |a, b| arith(Arith::Sub, a, b),
+    )
+}
+
+pub fn mul(lhs: &Operand, rhs: &Operand) -> ResultT<Operand> {
+    binary(
+        lhs,
+        rhs,
+        ops::mul,
+        |a, b| Arith::Mul.kind(a, b),
+        |a, b| arith(Arith::Mul, a, b),
+    )
+}
+
+/// Divide the values. Like [ops::div], the division of integers return decimals
+pub fn div(lhs: &Operand, rhs: &Operand) -> ResultT<Operand> {
+    binary(
+        lhs,
+        rhs,
+        ops::div,
+        |a, b| Arith::Div.kind(a, b),
+        |a, b| arith(Arith::Div, a, b),
+    )
+}
+
+/// The `and` of each pair of values. Unlike the one of the language, is not short-circuit
+pub fn and(lhs: &Operand, rhs: &Operand) -> ResultT<Operand> {
+    let op = Logic::And;
+    let kind = |a: &DataType, b: &DataType| op.kind(a, b);
+    binary(
+        lhs,
+        rhs,
+        |a, b| op.scalar(a, b),
+        kind,
+        |a, b| logic(op, a, b),
+    )
+}
+
+/// The `or` of each pair of values. Unlike the one of the language, is not short-circuit
+pub fn or(lhs: &Operand, rhs: &Operand) -> ResultT<Operand> {
+    let op = Logic::Or;
+    let kind = |a: &DataType, b: &DataType| op.kind(a, b);
+    binary(
+        lhs,
+        rhs,
+        |a, b| op.scalar(a, b),
+        kind,
+        |a, b| logic(op, a, b),
+    )
+}
+
+pub fn neg(of: &Operand) -> ResultT<Operand> {
+    unary(of, ops::neg, DataType::clone, |of| {
+        let x = match of {
+            Array::I64(x) => Array::I64(
+                x.iter()
+                    .map(|x| checked("-", x.checked_neg()))
+                    .collect::<ResultT<_>>()?,
+            ),
+            Array::Decimal(x) => Array::Decimal(x.iter().map(|x| -*x).collect()),
+            Array::F64(x) => Array::F64(x.iter().map(|x| -*x).collect()),
+            _ => map_scalars(of, &DataType::Any, ops::neg)?,
+        };
+        Ok(x)
+    })
+}
+
+pub fn pos(of: &Operand) -> ResultT<Operand> {
+    unary(of, ops::pos, DataType::clone, |of| match of {
+        Array::I64(_) | Array::Decimal(_) | Array::F64(_) => Ok(of.clone()),
+        _ => map_scalars(of, &DataType::Any, ops::pos),
+    })
+}
+
+pub fn not(of: &Operand) -> ResultT<Operand> {
+    unary(of, ops::not, DataType::clone, |of| match of {
+        Array::Bit(x) => Ok(Array::Bit(!x.clone())),
+        Array::Bool(x) => Ok(Array::Bool(x.iter().map(|x| !*x).collect())),
+        _ => map_scalars(of, &DataType::Any, ops::not),
+    })
+}
+
+/// The bitwise `xor` of each pair of values
+pub fn xor(lhs: &Operand, rhs: &Operand) -> ResultT<Operand> {
+    let op = BitOp::Xor;
+    binary(
+        lhs,
+        rhs,
+        |a, b| bits::bitwise(op, a, b),
+        same,
+        |a, b| match (a, b) {
+            (Array::Bit(a), Array::Bit(b)) => Ok(Array::Bit(op.apply(a, b))),
+            _ => zip_scalars(a, b, |a, b| bits::bitwise(op, a, b)),
+        },
+    )
+}
+
+/// Shift the bits of each value by `by`, that must be a positive [Scalar::I64]
+pub fn shift(op: Shift, of: &Operand, by: &Operand) -> ResultT<Operand> {
+    let by = match by {
+        Operand::Scalar(Scalar::I64([x])) if *x >= 0 => *x as usize,
+        Operand::Scalar(Scalar::I64([x])) => {
+            let err = ErrorLang::new(ErrorKind::Invalid, Some("The shift can't be negative"))
+                .with_ctx(ErrorCtx::new(op.name(), &x.to_string()));
+            return Err(ErrorCore::Lang(Box::new(err)));
+        }
+        Operand::Scalar(x) => {
+            return Err(ErrorCore::TypeMismatch {
+                expected: DataType::I64,
+                get: x.kind(),
+            })
+        }
+        Operand::Vector(x) => {
+            return Err(ErrorCore::TypeMismatch {
+                expected: DataType::I64,
+                get: x.data.slice().kind(),
+            })
+        }
+    };
+    let f = |x: &Scalar| bits::shift(op, x, by);
+    // Keep the column packed
+    unary(of, f, DataType::clone, |of| {
+        map_scalars(of, &DataType::Bit, f)
+    })
+}
+
+fn date<'a>(op: &'static str, of: &'a Scalar) -> ResultT<&'a DateT> {
+    match of {
+        Scalar::Date([x]) => Ok(x),
+        _ => Err(ErrorCore::Unsupported {
+            op,
+            kind: of.kind(),
+        }),
+    }
+}
+
+/// Extract the `part` of each date, like the year, see [dates::part]
+pub fn date_part(of: &Operand, part: Part) -> ResultT<Operand> {
+    let f = |x: &Scalar| Ok(dates::part(date(part.name(), x)?, part)?.into());
+    unary(
+        of,
+        f,
+        |_| DataType::I64,
+        |of| match of {
+            Array::Date(_, x) => Ok(Array::I64(
+                x.iter()
+                    .map(|x| dates::part(x, part))
+                    .collect::<ResultT<_>>()?,
+            )),
+            _ => map_scalars(of, &DataType::I64, f),
+        },
+    )
+}
+
+/// Truncate each date to the start of the `part`, see [dates::trunc]
+pub fn trunc(of: &Operand, part: Part) -> ResultT<Operand> {
+    let f = |x: &Scalar| Ok(dates::trunc(date("trunc", x)?, part)?.into());
+    // The dates keep its kind, like a `Date` or `DateTime`
+    unary(of, f, DataType::clone, |of| match of {
+        Array::Date(kind, x) => Ok(Array::Date(
+            *kind,
+            x.iter()
+                .map(|x| dates::trunc(x, part))
+                .collect::<ResultT<_>>()?,
+        )),
+        _ => map_scalars(of, &DataType::Any, f),
+    })
+}
+
+fn as_text<'a>(op: &'static str, of: &'a Scalar) -> ResultT<&'a str> {
+    match of {
+        Scalar::Utf8([x]) => Ok(x),
+        _ => Err(ErrorCore::Unsupported {
+            op,
+            kind: of.kind(),
+        }),
+    }
+}
+
+/// Apply `f` to each text, like the functions of [crate::text], that return a value of `kind`
+pub fn map_text(
+    op: &'static str,
+    of: &Operand,
+    kind: DataType,
+    f: impl Fn(&str) -> ResultT<Scalar>,
+) -> ResultT<Operand> {
+    let scalar = |x: &Scalar| f(as_text(op, x)?);
+    unary(
+        of,
+        scalar,
+        |_| kind.clone(),
+        |of| {
+            let values = match of {
+                Array::Utf8(x) => x.iter().map(|x| f(x)).collect::<ResultT<_>>()?,
+                _ => (0..of.len())
+                    .filter_map(|x| of.get(x))
+                    .map(|x| scalar(&x))
+                    .collect::<ResultT<_>>()?,
+            };
+            Ok(Array::from_scalars(&kind, values))
+        },
+    )
+}
+
+/// Turn each value in many rows with `f`. A scalar return the rows as a single column, and a
+/// column also tell from which `row` come each one, like `[row:Int, it:Str; 0, 'a'; 1, 'b']`
+fn explode(
+    op: &'static str,
+    of: &Operand,
+    kind: DataType,
+    f: impl Fn(&Scalar) -> ResultT<Vec<Scalar>>,
+) -> ResultT<Table> {
+    match of {
+        Operand::Scalar(x) => {
+            let parts = Array::from_scalars(&kind, f(x)?);
+            Table::new(Schema::new_scalar(kind), vec![parts])
+        }
+        Operand::Vector(x) if x.schema.len() == 1 => {
+            let (mut rows, mut parts) = (Vec::new(), Vec::new());
+            for (row, value) in (0..x.rows).filter_map(|row| Some((row, x.data.get(row)?))) {
+                for part in f(&value)? {
+                    rows.push(row as i64);
+                    parts.push(part);
+                }
+            }
+            let fields = [
+                Field::new("row", DataType::I64),
+                Field::new(&x.schema.fields[0].name, kind.clone()),
+            ];
+            let cols = vec![Array::I64(rows), Array::from_scalars(&kind, parts)];
+            Table::new(Schema::new(None, &fields), cols)
+        }
+        Operand::Vector(x) => Err(ErrorCore::Unsupported {
+            op,
+            kind: x.data.slice().kind(),
+        }),
+    }
+}
+
+/// Split the text in a relation of the parts. The parts of a column are paired with the
+/// `row` of the text, like `[row:Int, it:Str; 0, 'a'; 0, 'b'; 1, 'c']`
+pub fn split(of: &Operand, sep: &str) -> ResultT<Table> {
+    let op = "split";
+    explode(op, of, DataType::Utf8, |x| {
+        let parts = text::split(as_text(op, x)?, sep);
+        Ok(parts.into_iter().map(Scalar::from).collect())
+    })
+}
+
+fn money(op: &'static str, of: &Scalar) -> ResultT<Money> {
+    match of {
+        Scalar::Money([x]) => Ok(*x),
+        _ => Err(ErrorCore::Unsupported {
+            op,
+            kind: of.kind(),
+        }),
+    }
+}
+
+/// Split each amount in `parts`, see [Money::allocate]
+pub fn allocate(of: &Operand, parts: i64) -> ResultT<Table> {
+    let op = "allocate";
+    explode(op, of, DataType::Money, |x| {
+        let parts = money(op, x)?.allocate(parts)?;
+        Ok(parts.into_iter().map(Scalar::from).collect())
+    })
+}
+
+/// The code of the currency of each amount
+pub fn currency(of: &Operand) -> ResultT<Operand> {
+    let f = |x: &Scalar| Ok(money("currency", x)?.currency().code().into());
+    unary(
+        of,
+        f,
+        |_| DataType::Utf8,
+        |of| map_scalars(of, &DataType::Utf8, f),
+    )
+}
+
+/// Convert each number to a amount in the `currency`, see [cast::to_money]
+pub fn to_money(of: &Operand, currency: Currency, round: Rounding) -> ResultT<Operand> {
+    let f = |x: &Scalar| Ok(cast::to_money(x, currency, round)?.into());
+    unary(
+        of,
+        f,
+        |_| DataType::Money,
+        |of| map_scalars(of, &DataType::Money, f),
+    )
+}
+
+/// Move each date time to the `offset`, see [dates::to_offset]
+pub fn to_offset(of: &Operand, offset: &FixedOffset) -> ResultT<Operand> {
+    let f = |x: &Scalar| Ok(dates::to_offset(date("to_offset", x)?, offset)?.into());
+    let kind = DataType::Date(DateKind::DateTime);
+    unary(of, f, |_| kind.clone(), |of| map_scalars(of, &kind, f))
+}
+
+/// Convert each value to the type `to`, see [cast::cast]
+pub fn cast(
+    of: &Operand,
+    to: &DataType,
+    round: Rounding,
+    offset: &FixedOffset,
+) -> ResultT<Operand> {
+    let f = |x: &Scalar| cast::cast(x, to, round, offset);
+    unary(of, f, |_| to.clone(), |of| map_scalars(of, to, f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector(kind: DataType, data: Array) -> Operand {
+        let rows = data.len();
+        Operand::Vector(Vector::new(Schema::new_scalar(kind), rows, data).unwrap())
+    }
+
+    fn ints(data: Vec<i64>) -> Operand {
+        vector(DataType::I64, Array::I64(data))
+    }
+
+    fn bools(data: Vec<bool>) -> Operand {
+        vector(DataType::Bool, Array::Bool(data))
+    }
+
+    fn bits(data: &str) -> Operand {
+        vector(
+            DataType::Bit,
+            Array::Bit(data.chars().map(|x| x == '1').collect()),
+        )
+    }
+
+    fn show(x: ResultT<Operand>) -> String {
+        match x {
+            Ok(Operand::Scalar(x)) => x.to_string(),
+            Ok(Operand::Vector(x)) => x.to_string(),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn broadcasting() {
+        let one = Operand::Scalar(dsl::int(1));
+        assert_eq!(show(add(&one, &one)), "2");
+        assert_eq!(show(add(&one, &ints(vec![1, 2, 3]))), "[it:Int; 2; 3; 4]");
+        assert_eq!(show(sub(&ints(vec![1, 2, 3]), &one)), "[it:Int; 0; 1; 2]");
+        assert_eq!(
+            show(mul(&ints(vec![1, 2]), &ints(vec![3, 4]))),
+            "[it:Int; 3; 8]"
+        );
+        assert_eq!(
+            show(div(&ints(vec![1, 4]), &ints(vec![2, 2]))),
+            "[it:Dec; 0.50d; 2d]"
+        );
+        assert_eq!(show(neg(&ints(vec![1, -2]))), "[it:Int; -1; 2]");
+
+        let floats = vector(DataType::F64, Array::F64(vec![1.5.into(), 2.0.into()]));
+        let half = Operand::Scalar(dsl::float(0.5.into()));
+        assert_eq!(show(mul(&floats, &half)), "[it:Float; 0.75f; 1f]");
+    }
+
+    #[test]
+    fn booleans() {
+        let (a, b) = (bools(vec![true, false]), bools(vec![true, true]));
+        assert_eq!(show(and(&a, &b)), "[it:Bool; true; false]");
+        assert_eq!(show(or(&a, &b)), "[it:Bool; true; true]");
+        assert_eq!(show(not(&a)), "[it:Bool; false; true]");
+
+        let no = Operand::Scalar(false.into());
+        assert_eq!(show(or(&no, &a)), "[it:Bool; true; false]");
+        assert_eq!(
+            show(and(&a, &ints(vec![1, 2]))),
+            "Type mismatch: expected Bool, found Int"
+        );
+    }
+
+    #[test]
+    fn bit_columns() {
+        let (a, b) = (bits("0011"), bits("0101"));
+        assert_eq!(
+            show(and(&a, &b)),
+            "[it:Bit; Bits[0]; Bits[0]; Bits[0]; Bits[1]]"
+        );
+        assert_eq!(
+            show(or(&a, &b)),
+            "[it:Bit; Bits[0]; Bits[1]; Bits[1]; Bits[1]]"
+        );
+        assert_eq!(
+            show(xor(&a, &b)),
+            "[it:Bit; Bits[0]; Bits[1]; Bits[1]; Bits[0]]"
+        );
+        assert_eq!(
+            show(not(&a)),
+            "[it:Bit; Bits[1]; Bits[1]; Bits[0]; Bits[0]]"
+        );
+
+        let one = Operand::Scalar(dsl::int(1));
+        let shifted = shift(Shift::Right, &a, &one).unwrap();
+        assert!(matches!(&shifted, Operand::Vector(x) if matches!(x.data, Array::Bit(_))));
+        assert_eq!(
+            show(Ok(shifted)),
+            "[it:Bit; Bits[0]; Bits[0]; Bits[1]; Bits[1]]"
+        );
+
+        assert_eq!(
+            show(xor(&a, &bits("01"))),
+            "The vectors have a different rank: lhs = 4, rhs = 2"
+        );
+        assert_eq!(
+            show(shift(Shift::Left, &a, &Operand::Scalar(dsl::int(-1)))),
+            "The shift can't be negative: shift_left = -1"
+        );
+    }
+
+    #[test]
+    fn date_columns() {
+        let days = ["2000-01-31", "2000-02-16"].map(|x| parse_date_t(x).unwrap());
+        let days = vector(
+            DataType::Date(DateKind::Date),
+            Array::Date(DateKind::Date, days.to_vec()),
+        );
+        let month = Operand::Scalar("1 month".parse::<dates::Interval>().unwrap().into());
+
+        assert_eq!(
+            show(add(&days, &month)),
+            "[it:Date; 2000-02-29; 2000-03-16]"
+        );
+        assert_eq!(show(date_part(&days, Part::Month)), "[it:Int; 1; 2]");
+        assert_eq!(
+            show(trunc(&days, Part::Month)),
+            "[it:Date; 2000-01-01; 2000-02-01]"
+        );
+        assert_eq!(
+            show(date_part(&days, Part::Hour)),
+            "A Date has no hour: value = 2000-01-31"
+        );
+
+        let days = ["2000-01-31"].map(|x| Scalar::from(parse_date_t(x).unwrap()));
+        let days = vector(DataType::Date(DateKind::Date), Array::Scalar(days.to_vec()));
+        let days = trunc(&days, Part::Month).unwrap();
+        assert!(matches!(&days, Operand::Vector(x) if matches!(x.data, Array::Date(..))));
+    }
+
+    #[test]
+    fn text_columns() {
+        let names = vector(DataType::Utf8, Array::from(&["ana", "Luis Díaz"][..]));
+        let upper = map_text("upper", &names, DataType::Utf8, |x| {
+            Ok(text::upper(x).into())
+        })
+        .unwrap();
+        assert!(matches!(&upper, Operand::Vector(x) if matches!(x.data, Array::Utf8(_))));
+        assert_eq!(show(Ok(upper)), "[it:Str; \"ANA\"; \"LUIS DÍAZ\"]");
+        assert_eq!(
+            show(map_text("len", &names, DataType::I64, |x| {
+                Ok((text::len(x) as i64).into())
+            })),
+            "[it:Int; 3; 9]"
+        );
+        let parts = split(&names, " ").unwrap();
+        assert!(matches!(parts.cols[..], [Array::I64(_), Array::Utf8(_)]));
+        assert_eq!(
+            parts.to_string(),
+            "[row:Int, it:Str; 0, \"ana\"; 1, \"Luis\"; 1, \"Díaz\"]"
+        );
+        assert_eq!(
+            split(&Operand::Scalar(dsl::str("a,b")), ",")
+                .unwrap()
+                .to_string(),
+            "[it:Str; \"a\"; \"b\"]"
+        );
+        assert_eq!(
+            show(map_text("upper", &ints(vec![1]), DataType::Utf8, |x| {
+                Ok(x.into())
+            })),
+            "The operator `upper` is not supported for Int"
+        );
+    }
+
+    #[test]
+    fn casts() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let round = Rounding::default();
+        let floats = cast(&ints(vec![1, 2]), &DataType::F64, round, &utc).unwrap();
+        assert!(matches!(&floats, Operand::Vector(x) if matches!(x.data, Array::F64(_))));
+        assert_eq!(show(Ok(floats)), "[it:Float; 1f; 2f]");
+
+        let usd = Currency::from_code("USD").unwrap();
+        let amounts = to_money(&ints(vec![1, 2]), usd, round).unwrap();
+        let codes = currency(&amounts).unwrap();
+        assert!(matches!(&codes, Operand::Vector(x) if matches!(x.data, Array::Utf8(_))));
+        assert_eq!(show(Ok(codes)), "[it:Str; \"USD\"; \"USD\"]");
+    }
+
+    #[test]
+    fn empty() {
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let round = Rounding::default();
+        let none = ints(vec![]);
+        let dates = vector(DataType::Date(DateKind::Date), Array::Scalar(vec![]));
+        let one = Operand::Scalar(dsl::int(1));
+        // The type come from the operator, not from the values
+        assert_eq!(show(div(&none, &none)), "[it:Dec]");
+        assert_eq!(show(div(&one, &none)), "[it:Dec]");
+        assert_eq!(show(sub(&dates, &dates)), "[it:Interval]");
+        assert_eq!(show(cast(&none, &DataType::Utf8, round, &utc)), "[it:Str]");
+        assert_eq!(
+            show(cast(&none, &DataType::Decimal, round, &utc)),
+            "[it:Dec]"
+        );
+        assert_eq!(show(date_part(&dates, Part::Year)), "[it:Int]");
+        assert_eq!(show(trunc(&dates, Part::Month)), "[it:Date]");
+        let usd = Currency::from_code("USD").unwrap();
+        assert_eq!(show(to_money(&none, usd, round)), "[it:Money]");
+        let names = vector(DataType::Utf8, Array::Utf8(vec![]));
+        assert_eq!(
+            show(map_text("len", &names, DataType::I64, |x| {
+                Ok((text::len(x) as i64).into())
+            })),
+            "[it:Int]"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            show(add(&ints(vec![1]), &ints(vec![3, 4]))),
+            "The vectors have a different rank: lhs = 1, rhs = 2"
+        );
+        assert_eq!(
+            show(add(&ints(vec![i64::MAX]), &Operand::Scalar(dsl::int(1)))),
+            "Arithmetic overflow in `+`"
+        );
+        assert_eq!(
+            show(div(&ints(vec![1]), &ints(vec![0]))),
+            "Division by zero"
+        );
+        let dec = Operand::Scalar(dsl::dec(Decimal::ONE));
+        assert_eq!(
+            show(add(&ints(vec![1]), &dec)),
+            "Type mismatch: expected Int, found Dec"
+        );
+        let names = vector(DataType::Utf8, Array::Utf8(vec!["a".into()]));
+        assert_eq!(
+            show(neg(&names)),
+            "The operator `-` is not supported for Str"
+        );
+    }
+}
@@ -6,14 +6,27 @@ pub extern crate slotmap;
 pub extern crate text_size;
 pub extern crate tree_flat;
 
+pub mod aggregate;
 pub mod algebraic;
+pub mod bits;
+pub mod cast;
 pub mod convert;
+pub mod dates;
 pub mod dsl;
 pub mod errors;
+pub mod join;
+pub mod kernels;
+pub mod money;
+pub mod ops;
+pub mod query;
 pub mod relation;
 pub mod row;
 pub mod scalar;
 pub mod schema;
+pub mod set;
+pub mod table;
+pub mod text;
+pub mod tree;
 pub mod types;
 pub mod utils;
 pub mod vector;
@@ -26,12 +39,17 @@ pub mod extra_types {
 }
 
 pub mod prelude {
+    pub use crate::dates::Interval;
     pub use crate::dsl;
     pub use crate::errors::{ErrorCore, ErrorLang, ResultT, Span};
     pub use crate::extra_types::*;
-    pub use crate::relation::Rel;
+    pub use crate::money::Money;
+    pub use crate::relation::{Rel, RowsIter};
+    pub use crate::row::{Row, RowSlice};
     pub use crate::scalar::{BitSlice, BitVec, DateKind, DateT, Scalar, ScalarSlice, F64};
     pub use crate::schema::*;
+    pub use crate::table::Table;
+    pub use crate::tree::Tree;
     pub use crate::types::*;
     pub use crate::utils::*;
     pub use crate::vector::{Array, VecPos, Vector};
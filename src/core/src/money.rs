@@ -0,0 +1,347 @@
+//! # An amount of [Money] in a [Currency].
+//!
+//! The amount is a [Decimal] with the fixed scale of the currency, so `12.5 USD` is stored as
+//! `12.50 USD`, and the results of a multiplication or division are rounded to it with
+//! [Rounding::HalfEven] (the *banker's rounding*), so the rounding errors not accumulate.
+//!
+//! The arithmetic never mix currencies: adding `USD` to `EUR` is a error, because the exchange
+//! rate is not known.
+use std::fmt;
+use std::str::FromStr;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::RoundingStrategy;
+
+use crate::cast::Rounding;
+use crate::errors::{ErrorCtx, ErrorKind};
+use crate::prelude::*;
+
+/// The [ISO 4217](https://en.wikipedia.org/wiki/ISO_4217) codes known, with the amount of
+/// decimals of each one
+const CURRENCIES: [(&str, u32); 48] = [
+    ("AED", 2),
+    ("ARS", 2),
+    ("AUD", 2),
+    ("BHD", 3),
+    ("BOB", 2),
+    ("BRL", 2),
+    ("CAD", 2),
+    ("CHF", 2),
+    ("CLP", 0),
+    ("CNY", 2),
+    ("COP", 2),
+    ("CRC", 2),
+    ("CZK", 2),
+    ("DKK", 2),
+    ("DOP", 2),
+    ("EUR", 2),
+    ("GBP", 2),
+    ("GTQ", 2),
+    ("HKD", 2),
+    ("HUF", 2),
+    ("IDR", 2),
+    ("ILS", 2),
+    ("INR", 2),
+    ("ISK", 0),
+    ("JOD", 3),
+    ("JPY", 0),
+    ("KRW", 0),
+    ("KWD", 3),
+    ("MXN", 2),
+    ("NOK", 2),
+    ("NZD", 2),
+    ("OMR", 3),
+    ("PEN", 2),
+    ("PLN", 2),
+    ("PYG", 0),
+    ("RUB", 2),
+    ("SAR", 2),
+    ("SEK", 2),
+    ("SGD", 2),
+    ("TND", 3),
+    ("TRY", 2),
+    ("USD", 2),
+    ("UYU", 2),
+    ("VES", 2),
+    ("VND", 0),
+    ("XAF", 0),
+    ("XOF", 0),
+    ("ZAR", 2),
+];
+
+/// The max `parts` of [Money::allocate], so a typo can't fill the memory
+pub const MAX_PARTS: i64 = 1_000_000;
+
+/// A currency, like `USD`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Currency {
+    code: &'static str,
+    scale: u32,
+}
+
+impl Currency {
+    /// Find the currency by its code, like `USD`
+    pub fn from_code(code: &str) -> Option<Self> {
+        CURRENCIES
+            .iter()
+            .find(|(x, _)| *x == code)
+            .map(|(code, scale)| Currency {
+                code,
+                scale: *scale,
+            })
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// The amount of decimals, like `2` for the cents of `USD`
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+/// A amount of money, like `12.50 USD`.
+///
+/// The order is by currency, then amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money {
+    currency: Currency,
+    amount: Decimal,
+}
+
+fn invalid(msg: &str, ctx: &[(&str, &str)]) -> ErrorCore {
+    let err = ctx.iter().fold(
+        ErrorLang::new(ErrorKind::Invalid, Some(msg)),
+        |err, (key, value)| err.with_ctx(ErrorCtx::new(key, value)),
+    );
+    ErrorCore::Lang(Box::new(err))
+}
+
+fn overflow(x: Option<Decimal>, op: &'static str) -> ResultT<Decimal> {
+    x.ok_or(ErrorCore::Overflow { op })
+}
+
+impl Money {
+    /// The `amount` in the `currency`, that must not have more decimals than it allows
+    pub fn new(amount: Decimal, currency: Currency) -> ResultT<Self> {
+        if amount.normalize().scale() > currency.scale {
+            let msg = format!("{} has only {} decimals", currency, currency.scale);
+            return Err(invalid(&msg, &[("amount", &amount.to_string())]));
+        }
+        Ok(Self::round(amount, currency, Rounding::HalfEven))
+    }
+
+    /// The `amount` in the `currency`, rounded to its decimals
+    pub fn round(amount: Decimal, currency: Currency, round: Rounding) -> Self {
+        let mut amount = round.round(amount, currency.scale);
+        amount.rescale(currency.scale);
+        Money { currency, amount }
+    }
+
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    fn same_currency(&self, rhs: &Money) -> ResultT<Currency> {
+        if self.currency == rhs.currency {
+            Ok(self.currency)
+        } else {
+            Err(invalid(
+                "Can't mix currencies",
+                &[("lhs", self.currency.code), ("rhs", rhs.currency.code)],
+            ))
+        }
+    }
+
+    pub fn checked_add(&self, rhs: &Money) -> ResultT<Self> {
+        let currency = self.same_currency(rhs)?;
+        let amount = overflow(self.amount.checked_add(rhs.amount), "+")?;
+        Ok(Money { currency, amount })
+    }
+
+    pub fn checked_sub(&self, rhs: &Money) -> ResultT<Self> {
+        let currency = self.same_currency(rhs)?;
+        let amount = overflow(self.amount.checked_sub(rhs.amount), "-")?;
+        Ok(Money { currency, amount })
+    }
+
+    pub fn checked_neg(&self) -> Self {
+        Money {
+            currency: self.currency,
+            amount: -self.amount,
+        }
+    }
+
+    /// Multiply by `by`, rounded to the decimals of the currency
+    pub fn checked_mul(&self, by: Decimal) -> ResultT<Self> {
+        let amount = overflow(self.amount.checked_mul(by), "*")?;
+        Ok(Self::round(amount, self.currency, Rounding::HalfEven))
+    }
+
+    /// Divide by `by`, rounded to the decimals of the currency
+    pub fn checked_div(&self, by: Decimal) -> ResultT<Self> {
+        if by.is_zero() {
+            return Err(ErrorCore::DivisionByZero);
+        }
+        let amount = overflow(self.amount.checked_div(by), "/")?;
+        Ok(Self::round(amount, self.currency, Rounding::HalfEven))
+    }
+
+    /// How many times `rhs` fit in the amount, like `10 USD / 4 USD = 2.5`
+    pub fn ratio(&self, rhs: &Money) -> ResultT<Decimal> {
+        self.same_currency(rhs)?;
+        if rhs.amount.is_zero() {
+            return Err(ErrorCore::DivisionByZero);
+        }
+        overflow(self.amount.checked_div(rhs.amount), "/")
+    }
+
+    /// Split the amount in `parts` that add up exactly to it. The cents that not divide evenly
+    /// go one by one to the first parts, so `10 USD` in 3 is `3.34, 3.33, 3.33`
+    pub fn allocate(&self, parts: i64) -> ResultT<Vec<Money>> {
+        if parts <= 0 {
+            return Err(invalid(
+                "The parts must be more than zero",
+                &[("parts", &parts.to_string())],
+            ));
+        }
+        if parts > MAX_PARTS {
+            let msg = format!("The parts can't be more than {MAX_PARTS}");
+            return Err(invalid(&msg, &[("parts", &parts.to_string())]));
+        }
+        let scale = self.currency.scale;
+        let count = Decimal::from(parts);
+        let each = (self.amount / count).round_dp_with_strategy(scale, RoundingStrategy::ToZero);
+        let cent = if self.amount.is_sign_negative() {
+            Decimal::new(-1, scale)
+        } else {
+            Decimal::new(1, scale)
+        };
+        let left = self.amount - each * count;
+        let extra = (left / cent).to_i64().unwrap_or_default();
+
+        Ok((0..parts)
+            .map(|part| {
+                let amount = if part < extra { each + cent } else { each };
+                Self::round(amount, self.currency, Rounding::HalfEven)
+            })
+            .collect())
+    }
+}
+
+/// Parse a amount and its currency, like `12.50 USD` or `12.50USD`
+impl FromStr for Money {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|x: char| x.is_ascii_alphabetic())
+            .ok_or_else(|| format!("Missing the currency of `{s}`, like `12.50 USD`"))?;
+        let (amount, code) = s.split_at(split);
+        let currency =
+            Currency::from_code(code).ok_or_else(|| format!("Unknown currency `{code}`"))?;
+        let amount = Decimal::from_str(&amount.trim().replace('_', ""))
+            .map_err(|_| format!("Invalid amount `{}`", amount.trim()))?;
+        Money::new(amount, currency).map_err(|err| err.to_string())
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.currency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money(of: &str) -> Money {
+        of.parse().unwrap()
+    }
+
+    fn usd(of: &str) -> Money {
+        money(&format!("{of} USD"))
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(usd("12.5").to_string(), "12.50 USD");
+        assert_eq!(money("1_000JPY").to_string(), "1000 JPY");
+        assert_eq!(money("-1.5 KWD").to_string(), "-1.500 KWD");
+        assert_eq!(
+            "1.5 XXX".parse::<Money>().unwrap_err(),
+            "Unknown currency `XXX`"
+        );
+        assert_eq!(
+            "12.505 USD".parse::<Money>().unwrap_err(),
+            "USD has only 2 decimals: amount = 12.505"
+        );
+        assert_eq!(
+            "1.5".parse::<Money>().unwrap_err(),
+            "Missing the currency of `1.5`, like `12.50 USD`"
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(usd("0.1").checked_add(&usd("0.2")).unwrap(), usd("0.3"));
+        assert_eq!(
+            usd("1")
+                .checked_add(&money("1 EUR"))
+                .unwrap_err()
+                .to_string(),
+            "Can't mix currencies: lhs = USD, rhs = EUR"
+        );
+        // Banker's rounding: to the nearest even cent
+        assert_eq!(usd("0.25").checked_div(2.into()).unwrap(), usd("0.12"));
+        assert_eq!(usd("0.35").checked_div(2.into()).unwrap(), usd("0.18"));
+        assert_eq!(
+            usd("10").checked_mul(Decimal::new(15, 2)).unwrap(),
+            usd("1.50")
+        );
+        assert_eq!(usd("10").ratio(&usd("4")).unwrap(), Decimal::new(25, 1));
+        assert!(usd("10").checked_div(Decimal::ZERO).is_err());
+    }
+
+    #[test]
+    fn allocate() {
+        let parts = |of: Money, n| {
+            let parts = of.allocate(n).unwrap();
+            let total = parts
+                .iter()
+                .try_fold(
+                    Money::round(Decimal::ZERO, of.currency, Rounding::HalfEven),
+                    |all, x| all.checked_add(x),
+                )
+                .unwrap();
+            assert_eq!(total, of);
+            parts
+                .iter()
+                .map(|x| x.amount.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(parts(usd("10"), 3), ["3.34", "3.33", "3.33"]);
+        assert_eq!(parts(usd("-0.05"), 2), ["-0.03", "-0.02"]);
+        assert_eq!(parts(money("100 JPY"), 3), ["34", "33", "33"]);
+        assert_eq!(parts(usd("0.01"), 3), ["0.01", "0.00", "0.00"]);
+        assert!(usd("1").allocate(0).is_err());
+        assert_eq!(
+            usd("1").allocate(100_000_000_000).unwrap_err().to_string(),
+            "The parts can't be more than 1000000: parts = 100000000000"
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! # Arithmetic operators over [Scalar]
+//!
+//! The numeric types are not implicitly converted between them, so mixing them
+//! without a cast is a [ErrorCore::TypeMismatch].
+//!
+//! The only exception is the division of integers, that return a [Decimal] so `10 / 3` not
+//! silently lose precision.
+//!
+//! The dates are moved with a [dates::Interval], and subtracting two dates return the
+//! interval between them.
+//!
+//! [crate::money::Money] is only added to the same currency, and multiplied or divided by [Scalar::I64] or
+//! [Decimal]. Dividing two amounts return the [Decimal] ratio between them.
+//!
+//! Adding two [Scalar::Utf8] join the texts.
+use rust_decimal::Decimal;
+
+use crate::bits;
+use crate::dates;
+use crate::errors::{ErrorCore, ResultT};
+use crate::scalar::Scalar;
+
+fn is_numeric(x: &Scalar) -> bool {
+    matches!(x, Scalar::I64(_) | Scalar::Decimal(_) | Scalar::F64(_))
+}
+
+/// [crate::money::Money] is multiplied or divided only by exact numbers, not [Scalar::F64]
+fn is_factor(x: &Scalar) -> bool {
+    matches!(x, Scalar::I64(_) | Scalar::Decimal(_))
+}
+
+fn factor(x: &Scalar) -> Decimal {
+    match x {
+        Scalar::I64([x]) => Decimal::from(*x),
+        Scalar::Decimal([x]) => *x,
+        _ => unreachable!("Check it with `is_factor`"),
+    }
+}
+
+/// Report why the operator `op` can't be applied to `lhs` and `rhs`
+fn mismatch(op: &'static str, lhs: &Scalar, rhs: &Scalar) -> ErrorCore {
+    if is_numeric(lhs) && is_numeric(rhs) {
+        ErrorCore::TypeMismatch {
+            expected: lhs.kind(),
+            get: rhs.kind(),
+        }
+    } else if is_numeric(lhs) {
+        ErrorCore::Unsupported {
+            op,
+            kind: rhs.kind(),
+        }
+    } else {
+        ErrorCore::Unsupported {
+            op,
+            kind: lhs.kind(),
+        }
+    }
+}
+
+pub(crate) fn checked<T>(op: &'static str, x: Option<T>) -> ResultT<T> {
+    x.ok_or(ErrorCore::Overflow { op })
+}
+
+pub(crate) fn checked_div<T>(op: &'static str, x: Option<T>, is_zero: bool) -> ResultT<T> {
+    if is_zero {
+        Err(ErrorCore::DivisionByZero)
+    } else {
+        checked(op, x)
+    }
+}
+
+pub fn add(lhs: &Scalar, rhs: &Scalar) -> ResultT<Scalar> {
+    let op = "+";
+    let x = match (lhs, rhs) {
+        (Scalar::I64([a]), Scalar::I64([b])) => checked(op, a.checked_add(*b))?.into(),
+        (Scalar::Decimal([a]), Scalar::Decimal([b])) => checked(op, a.checked_add(*b))?.into(),
+        (Scalar::F64([a]), Scalar::F64([b])) => (*a + *b).into(),
+        (Scalar::Date([a]), Scalar::Interval([b])) | (Scalar::Interval([b]), Scalar::Date([a])) => {
+            dates::add(a, b)?.into()
+        }
+        (Scalar::Interval([a]), Scalar::Interval([b])) => checked(op, a.checked_add(b))?.into(),
+        (Scalar::Money([a]), Scalar::Money([b])) => a.checked_add(b)?.into(),
+        (Scalar::Utf8([a]), Scalar::Utf8([b])) => format!("{a}{b}").into(),
+        _ => return Err(mismatch(op, lhs, rhs)),
+    };
+    Ok(x)
+}
+
+pub fn sub(lhs: &Scalar, rhs: &Scalar) -> ResultT<Scalar> {
+    let op = "-";
+    let x = match (lhs, rhs) {
+        (Scalar::I64([a]), Scalar::I64([b])) => checked(op, a.checked_sub(*b))?.into(),
+        (Scalar::Decimal([a]), Scalar::Decimal([b])) => checked(op, a.checked_sub(*b))?.into(),
+        (Scalar::F64([a]), Scalar::F64([b])) => (*a - *b).into(),
+        (Scalar::Date([a]), Scalar::Interval([b])) => dates::sub(a, b)?.into(),
+        (Scalar::Date([a]), Scalar::Date([b])) => dates::diff(a, b)?.into(),
+        (Scalar::Interval([a]), Scalar::Interval([b])) => checked(op, a.checked_sub(b))?.into(),
+        (Scalar::Money([a]), Scalar::Money([b])) => a.checked_sub(b)?.into(),
+        _ => return Err(mismatch(op, lhs, rhs)),
+    };
+    Ok(x)
+}
+
+pub fn mul(lhs: &Scalar, rhs: &Scalar) -> ResultT<Scalar> {
+    let op = "*";
+    let x = match (lhs, rhs) {
+        (Scalar::I64([a]), Scalar::I64([b])) => checked(op, a.checked_mul(*b))?.into(),
+        (Scalar::Decimal([a]), Scalar::Decimal([b])) => checked(op, a.checked_mul(*b))?.into(),
+        (Scalar::F64([a]), Scalar::F64([b])) => (*a * *b).into(),
+        (Scalar::Interval([a]), Scalar::I64([b])) | (Scalar::I64([b]), Scalar::Interval([a])) => {
+            checked(op, a.checked_mul(*b))?.into()
+        }
+        (Scalar::Money([a]), b) | (b, Scalar::Money([a])) if is_factor(b) => {
+            a.checked_mul(factor(b))?.into()
+        }
+        _ => return Err(mismatch(op, lhs, rhs)),
+    };
+    Ok(x)
+}
+
+/// Divide the values. The division of two [Scalar::I64] return a [Scalar::Decimal]
+pub fn div(lhs: &Scalar, rhs: &Scalar) -> ResultT<Scalar> {
+    let op = "/";
+    let x = match (lhs, rhs) {
+        (Scalar::I64([a]), Scalar::I64([b])) => {
+            let (a, b) = (Decimal::from(*a), Decimal::from(*b));
+            checked_div(op, a.checked_div(b), b.is_zero())?.into()
+        }
+        (Scalar::Decimal([a]), Scalar::Decimal([b])) => {
+            checked_div(op, a.checked_div(*b), b.is_zero())?.into()
+        }
+        (Scalar::F64([a]), Scalar::F64([b])) => (*a / *b).into(),
+        (Scalar::Money([a]), Scalar::Money([b])) => a.ratio(b)?.into(),
+        (Scalar::Money([a]), b) if is_factor(b) => a.checked_div(factor(b))?.into(),
+        _ => return Err(mismatch(op, lhs, rhs)),
+    };
+    Ok(x)
+}
+
+pub fn neg(of: &Scalar) -> ResultT<Scalar> {
+    let x = match of {
+        Scalar::I64([a]) => checked("-", a.checked_neg())?.into(),
+        Scalar::Decimal([a]) => (-*a).into(),
+        Scalar::F64([a]) => (-*a).into(),
+        Scalar::Interval([a]) => checked("-", a.checked_neg())?.into(),
+        Scalar::Money([a]) => a.checked_neg().into(),
+        _ => {
+            return Err(ErrorCore::Unsupported {
+                op: "-",
+                kind: of.kind(),
+            })
+        }
+    };
+    Ok(x)
+}
+
+pub fn pos(of: &Scalar) -> ResultT<Scalar> {
+    if is_numeric(of) {
+        Ok(of.clone())
+    } else {
+        Err(ErrorCore::Unsupported {
+            op: "+",
+            kind: of.kind(),
+        })
+    }
+}
+
+pub fn not(of: &Scalar) -> ResultT<Scalar> {
+    match of {
+        Scalar::Bool([a]) => Ok((!*a).into()),
+        Scalar::Bit(_) => bits::not(of),
+        _ => Err(ErrorCore::Unsupported {
+            op: "not",
+            kind: of.kind(),
+        }),
+    }
+}
@@ -0,0 +1,370 @@
+//! # Relational operators.
+//!
+//! The operators of the query language, like `?select` or `?where`, implemented only with
+//! the [Rel] trait so they work the same for a [Scalar], [Vector], [Table] or [Tree].
+//!
+//! The result is always a [Table], with the schema computed from the input: the columns that
+//! survive & the keys that are still valid.
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use crate::aggregate::Aggregate;
+use crate::prelude::*;
+
+/// Build the table with the `schema` from the `rows`
+pub(crate) fn to_table(schema: Schema, rows: impl Iterator<Item = Vec<Scalar>>) -> ResultT<Table> {
+    Table::from_rows(schema, rows.flatten().collect())
+}
+
+/// Resolve the `cols`, ignoring the repeated ones so the result not have duplicated fields
+fn resolve_unique(schema: &Schema, cols: &[Column]) -> ResultT<Vec<usize>> {
+    let mut pos = schema.resolve_all(cols)?;
+    let mut seen = BTreeSet::new();
+    pos.retain(|x| seen.insert(*x));
+    Ok(pos)
+}
+
+fn project(of: &dyn Rel, pos: &[usize]) -> ResultT<Table> {
+    let schema = of.schema().project(pos);
+    let rows = of
+        .rows()
+        .map(|row| pos.iter().map(|x| row[*x].clone()).collect());
+    to_table(schema, rows)
+}
+
+/// `?select`: only the columns `cols`, in that order
+pub fn select(of: &dyn Rel, cols: &[Column]) -> ResultT<Table> {
+    let pos = resolve_unique(&of.schema(), cols)?;
+    project(of, &pos)
+}
+
+/// `?deselect`: all the columns, except `cols`
+pub fn deselect(of: &dyn Rel, cols: &[Column]) -> ResultT<Table> {
+    let remove = resolve_unique(&of.schema(), cols)?;
+    let pos: Vec<_> = (0..of.schema().len())
+        .filter(|x| !remove.contains(x))
+        .collect();
+    project(of, &pos)
+}
+
+/// `?where`: only the rows that match the `check`
+pub fn filter(
+    of: &dyn Rel,
+    mut check: impl FnMut(&RowSlice<'_>) -> ResultT<bool>,
+) -> ResultT<Table> {
+    let schema = of.schema();
+    let mut rows = Vec::new();
+    for row in of.rows() {
+        if check(&RowSlice::from_values(&schema, &row))? {
+            rows.push(row);
+        }
+    }
+    to_table(schema, rows.into_iter())
+}
+
+/// `?limit`: only the first `total` rows
+pub fn limit(of: &dyn Rel, total: usize) -> ResultT<Table> {
+    to_table(of.schema(), of.rows().take(total))
+}
+
+/// `?skip`: all the rows, except the first `total`
+pub fn skip(of: &dyn Rel, total: usize) -> ResultT<Table> {
+    to_table(of.schema(), of.rows().skip(total))
+}
+
+/// `?distinct`: remove the repeated rows, keeping the first of each
+pub fn distinct(of: &dyn Rel) -> ResultT<Table> {
+    let mut seen = BTreeSet::new();
+    let rows = of.rows().filter(|row| seen.insert(row.clone()));
+    to_table(of.schema(), rows)
+}
+
+/// The direction of a column in `?sort`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl Order {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Order::Asc => "asc",
+            Order::Desc => "desc",
+        }
+    }
+
+    fn apply(&self, x: Ordering) -> Ordering {
+        match self {
+            Order::Asc => x,
+            Order::Desc => x.reverse(),
+        }
+    }
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// `?sort`: the rows ordered by the columns `by`, the first is the most significant.
+///
+/// The values are compared with the total order of [Scalar], so it works even for columns
+/// that mix types. The sort is stable: the rows with the same values keep their order.
+/// Without `by` the rows are ordered by all the columns
+pub fn sort(of: &dyn Rel, by: &[(Column, Order)]) -> ResultT<Table> {
+    let schema = of.schema();
+    let cols: Vec<_> = by.iter().map(|(col, _)| col.clone()).collect();
+    let mut keys: Vec<_> = schema
+        .resolve_all(&cols)?
+        .into_iter()
+        .zip(by.iter().map(|(_, order)| *order))
+        .collect();
+    if keys.is_empty() {
+        keys = (0..schema.len()).map(|x| (x, Order::Asc)).collect();
+    }
+
+    let mut rows: Vec<_> = of.rows().collect();
+    rows.sort_by(|a, b| {
+        keys.iter()
+            .map(|(pos, order)| order.apply(a[*pos].cmp(&b[*pos])))
+            .find(|x| x.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+    to_table(schema, rows.into_iter())
+}
+
+/// `?group`: one row for each distinct value of the columns `by`, with the `aggs` of
+/// the values of the rows of the group.
+///
+/// The fields `by` are the key, followed by one field for each aggregate, named like `sum_qty`.
+/// Without `by` all the rows are one group, so even a empty relation return one row
+pub fn group(of: &dyn Rel, by: &[Column], aggs: &[(Aggregate, Column)]) -> ResultT<Table> {
+    let schema = of.schema();
+    let key = resolve_unique(&schema, by)?;
+    let cols: Vec<_> = aggs.iter().map(|(_, col)| col.clone()).collect();
+    let cols = schema.resolve_all(&cols)?;
+
+    // The groups are in the order of the first row of each one
+    let mut groups: Vec<(Vec<Scalar>, Vec<Vec<Scalar>>)> = Vec::new();
+    let mut found = HashMap::new();
+    if key.is_empty() {
+        groups.push((Vec::new(), vec![Vec::new(); cols.len()]));
+        found.insert(Vec::new(), 0);
+    }
+    for row in of.rows() {
+        let values: Vec<_> = key.iter().map(|x| row[*x].clone()).collect();
+        let pos = *found.entry(values.clone()).or_insert_with(|| {
+            groups.push((values, vec![Vec::new(); cols.len()]));
+            groups.len() - 1
+        });
+        for (group, col) in groups[pos].1.iter_mut().zip(&cols) {
+            group.push(row[*col].clone());
+        }
+    }
+
+    let fields: Vec<_> = key
+        .iter()
+        .map(|x| schema.fields[*x].clone())
+        .chain(aggs.iter().zip(&cols).map(|((agg, _), col)| {
+            let field = &schema.fields[*col];
+            Field::new(&format!("{}_{}", agg, field.name), agg.kind(&field.kind))
+        }))
+        .collect();
+    let pk: Vec<_> = (0..key.len()).collect();
+
+    let mut rows = Vec::with_capacity(groups.len());
+    for (mut row, values) in groups {
+        for ((agg, _), (values, col)) in aggs.iter().zip(values.into_iter().zip(&cols)) {
+            let kind = &schema.fields[*col].kind;
+            row.push(agg.apply(&Array::from_scalars(kind, values), kind)?);
+        }
+        rows.push(row);
+    }
+    to_table(Schema::new_keyed(&pk, &fields), rows.into_iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sales() -> Tree {
+        let schema = Schema::new(
+            Some(0),
+            &[
+                Field::new("id", DataType::I64),
+                Field::new("name", DataType::Utf8),
+                Field::new("qty", DataType::I64),
+            ],
+        );
+        let data = vec![
+            dsl::int(1),
+            dsl::str("Soda"),
+            dsl::int(4),
+            dsl::int(2),
+            dsl::str("Tea"),
+            dsl::int(2),
+            dsl::int(3),
+            dsl::str("Soda"),
+            dsl::int(4),
+        ];
+        Tree::from_rows(schema, data).unwrap()
+    }
+
+    fn nums(data: Vec<i64>) -> Vector {
+        let rows = data.len();
+        Vector::new(Schema::new_scalar(DataType::I64), rows, Array::I64(data)).unwrap()
+    }
+
+    #[test]
+    fn rows() {
+        let tree = sales();
+        let rel: &dyn Rel = &tree;
+        assert_eq!(rel.len(), 3);
+        assert_eq!(
+            rel.rows().next(),
+            Some(vec![dsl::int(1), dsl::str("Soda"), dsl::int(4)])
+        );
+
+        let num = dsl::int(1);
+        let rel: &dyn Rel = &num;
+        assert_eq!(rel.rows().collect::<Vec<_>>(), vec![vec![dsl::int(1)]]);
+
+        let nums = nums(vec![1, 2, 3]);
+        assert_eq!(nums.rows().count(), 3);
+    }
+
+    #[test]
+    fn project() {
+        let tree = sales();
+        let names = select(&tree, &["name".into(), "id".into()]).unwrap();
+        assert_eq!(
+            names.to_string(),
+            "[name:Str, id:Int; \"Soda\", 1; \"Tea\", 2; \"Soda\", 3]"
+        );
+        // The key survive the projection
+        assert_eq!(names.schema.pk, vec![1]);
+
+        let names = deselect(&tree, &[0.into(), "qty".into()]).unwrap();
+        assert_eq!(names.to_string(), "[name:Str; \"Soda\"; \"Tea\"; \"Soda\"]");
+        assert!(names.schema.pk.is_empty());
+
+        let err = select(&tree, &["price".into()]).unwrap_err();
+        assert_eq!(err.to_string(), "The column `#price` is not in the schema");
+
+        let num = dsl::int(1);
+        assert_eq!(
+            select(&num, &["it".into()]).unwrap().to_string(),
+            "[it:Int; 1]"
+        );
+    }
+
+    #[test]
+    fn rows_ops() {
+        let tree = sales();
+        let soda = filter(&tree, |row| {
+            Ok(row.get(&"name".into()) == Some(dsl::str("Soda")))
+        })
+        .unwrap();
+        assert_eq!(soda.rows, 2);
+        assert_eq!(soda.schema, tree.schema);
+
+        assert_eq!(limit(&tree, 1).unwrap().rows, 1);
+        assert_eq!(limit(&tree, 10).unwrap().rows, 3);
+        assert_eq!(
+            skip(&tree, 2).unwrap().to_string(),
+            "[id:Int, name:Str, qty:Int; 3, \"Soda\", 4]"
+        );
+
+        let names = deselect(&tree, &["id".into()]).unwrap();
+        assert_eq!(
+            distinct(&names).unwrap().to_string(),
+            "[name:Str, qty:Int; \"Soda\", 4; \"Tea\", 2]"
+        );
+
+        let nums = nums(vec![3, 1, 3]);
+        assert_eq!(distinct(&nums).unwrap().to_string(), "[it:Int; 3; 1]");
+    }
+
+    #[test]
+    fn sorting() {
+        let tree = sales();
+        let by_name = sort(
+            &tree,
+            &[("name".into(), Order::Asc), ("id".into(), Order::Desc)],
+        )
+        .unwrap();
+        assert_eq!(
+            by_name.to_string(),
+            "[id:Int, name:Str, qty:Int; 3, \"Soda\", 4; 1, \"Soda\", 4; 2, \"Tea\", 2]"
+        );
+        assert_eq!(by_name.schema, tree.schema);
+
+        // Stable: the rows with the same `qty` keep their order
+        let by_qty = sort(&tree, &[("qty".into(), Order::Desc)]).unwrap();
+        assert_eq!(
+            by_qty.to_string(),
+            "[id:Int, name:Str, qty:Int; 1, \"Soda\", 4; 3, \"Soda\", 4; 2, \"Tea\", 2]"
+        );
+
+        let nums = nums(vec![3, 1, 2]);
+        assert_eq!(sort(&nums, &[]).unwrap().to_string(), "[it:Int; 1; 2; 3]");
+
+        // Mixed types follow the total order of `Scalar`
+        let data = vec![
+            dsl::str("a"),
+            dsl::dec(Decimal::from(1)),
+            dsl::int(2),
+            Scalar::Bool([true]),
+            dsl::float(1.5.into()),
+            Scalar::Unit([()]),
+        ];
+        let mixed = Vector::new(
+            Schema::new_scalar(DataType::Any),
+            data.len(),
+            Array::Scalar(data),
+        )
+        .unwrap();
+        let sorted = sort(&mixed, &[(0.into(), Order::Asc)]).unwrap();
+        let values: Vec<_> = sorted.rows().map(|x| x[0].to_string()).collect();
+        assert_eq!(values, ["()", "true", "2", "1.5f", "1d", "\"a\""]);
+
+        let err = sort(&tree, &[("price".into(), Order::Asc)]).unwrap_err();
+        assert_eq!(err.to_string(), "The column `#price` is not in the schema");
+    }
+
+    #[test]
+    fn grouping() {
+        let tree = sales();
+        let by_name = group(
+            &tree,
+            &["name".into()],
+            &[
+                (Aggregate::Count, "id".into()),
+                (Aggregate::Sum, "qty".into()),
+                (Aggregate::Max, "id".into()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            by_name.to_string(),
+            "[name:Str, count_id:Int, sum_qty:Int, max_id:Int; \"Soda\", 2, 8, 3; \"Tea\", 1, 2, 2]"
+        );
+        assert_eq!(by_name.schema.pk, vec![0]);
+
+        // Without `by`, a empty relation is still a group
+        let none = limit(&tree, 0).unwrap();
+        let total = group(
+            &none,
+            &[],
+            &[(Aggregate::Count, 0.into()), (Aggregate::Avg, "qty".into())],
+        )
+        .unwrap();
+        assert_eq!(total.to_string(), "[count_id:Int, avg_qty:Dec; 0, ()]");
+
+        let err = group(&tree, &["price".into()], &[]).unwrap_err();
+        assert_eq!(err.to_string(), "The column `#price` is not in the schema");
+    }
+}
@@ -3,10 +3,23 @@ use std::fmt;
 
 use crate::prelude::*;
 
+/// The rows of a relation, each one with the values in the order of the fields of the schema
+pub type RowsIter<'a> = Box<dyn Iterator<Item = Vec<Scalar>> + 'a>;
+
 pub trait Rel: Downcast + fmt::Debug {
     fn type_name(&self) -> &str;
 
     fn schema(&self) -> Schema;
+
+    /// The number of rows
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate the rows, in the order of the relation
+    fn rows(&self) -> RowsIter<'_>;
 }
 
 impl_downcast!(Rel);
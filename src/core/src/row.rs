@@ -0,0 +1,218 @@
+//! # Relational row.
+//!
+//! A row is a single entity of a relation: one value for each [Field] of the [Schema].
+//!
+//! A [Row] own the values, a [RowSlice] borrow them from the relation, like the rows
+//! of a [Table] or [Vector].
+//!
+//! Two rows are equal if have the same schema and the same value for each field,
+//! irrespective of the *order* of the fields.
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, Eq)]
+pub struct Row {
+    pub schema: Schema,
+    /// The values, in the same order of the fields of the `schema`
+    pub data: Vec<Scalar>,
+}
+
+impl Row {
+    pub fn new(schema: Schema, data: Vec<Scalar>) -> Self {
+        assert_eq!(
+            schema.len(),
+            data.len(),
+            "The values not match the fields of the schema"
+        );
+        Self { schema, data }
+    }
+
+    pub fn get(&self, col: &Column) -> Option<&Scalar> {
+        self.schema.resolve(col).map(|x| &self.data[x])
+    }
+
+    /// Build the row with only the fields `cols`, in that order
+    pub fn project(&self, cols: &[Column]) -> ResultT<Row> {
+        let pos = self.schema.resolve_all(cols)?;
+        let data = pos.iter().map(|x| self.data[*x].clone()).collect();
+        Ok(Row::new(self.schema.project(&pos), data))
+    }
+
+    pub fn as_slice(&self) -> RowSlice<'_> {
+        RowSlice::from_values(&self.schema, &self.data)
+    }
+}
+
+/// A view of a row stored in a relation, each value is a slice of exactly one cell
+#[derive(Debug, Clone)]
+pub struct RowSlice<'a> {
+    pub schema: &'a Schema,
+    pub data: Vec<ScalarSlice<'a>>,
+}
+
+impl<'a> RowSlice<'a> {
+    pub fn new(schema: &'a Schema, data: Vec<ScalarSlice<'a>>) -> Self {
+        assert_eq!(
+            schema.len(),
+            data.len(),
+            "The values not match the fields of the schema"
+        );
+        Self { schema, data }
+    }
+
+    /// View the `values`, in the order of the fields of the `schema`
+    pub fn from_values(schema: &'a Schema, values: &'a [Scalar]) -> Self {
+        let data = values.chunks(1).map(ScalarSlice::Scalar).collect();
+        Self::new(schema, data)
+    }
+
+    /// Get a copy of the value of `col`
+    pub fn get(&self, col: &Column) -> Option<Scalar> {
+        self.schema.resolve(col).and_then(|x| self.data[x].get(0))
+    }
+
+    pub fn to_row(&self) -> Row {
+        let data = self.data.iter().filter_map(|x| x.get(0)).collect();
+        Row::new(self.schema.clone(), data)
+    }
+
+    /// Build the row with only the fields `cols`, in that order
+    pub fn project(&self, cols: &[Column]) -> ResultT<Row> {
+        let pos = self.schema.resolve_all(cols)?;
+        let data = pos.iter().filter_map(|x| self.data[*x].get(0)).collect();
+        Ok(Row::new(self.schema.project(&pos), data))
+    }
+}
+
+/// Compare the values by field name, so the order of the fields not matter
+fn eq_fields(
+    lhs: &Schema,
+    rhs: &Schema,
+    get_lhs: impl Fn(usize) -> Option<Scalar>,
+    get_rhs: impl Fn(usize) -> Option<Scalar>,
+) -> bool {
+    if lhs != rhs {
+        return false;
+    }
+    lhs.fields
+        .iter()
+        .enumerate()
+        .all(|(pos, field)| get_lhs(pos) == rhs.resolve_name(&field.name).and_then(&get_rhs))
+}
+
+impl PartialEq for Row {
+    fn eq(&self, other: &Row) -> bool {
+        eq_fields(
+            &self.schema,
+            &other.schema,
+            |x| self.data.get(x).cloned(),
+            |x| other.data.get(x).cloned(),
+        )
+    }
+}
+
+/// Must agree with the equality, so the values are hashed in the order of the field names
+impl Hash for Row {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.schema.hash(state);
+        let mut values: Vec<_> = self
+            .schema
+            .fields
+            .iter()
+            .map(|x| &x.name)
+            .zip(&self.data)
+            .collect();
+        values.sort();
+        values.hash(state);
+    }
+}
+
+impl PartialEq for RowSlice<'_> {
+    fn eq(&self, other: &RowSlice<'_>) -> bool {
+        eq_fields(
+            self.schema,
+            other.schema,
+            |x| self.data[x].get(0),
+            |x| other.data[x].get(0),
+        )
+    }
+}
+
+impl PartialEq<Row> for RowSlice<'_> {
+    fn eq(&self, other: &Row) -> bool {
+        eq_fields(
+            self.schema,
+            &other.schema,
+            |x| self.data[x].get(0),
+            |x| other.data.get(x).cloned(),
+        )
+    }
+}
+
+/// Display the row as a relation of one row, like `[name:Str, qty:Int; Soda, 4]`
+impl fmt::Display for Row {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}; ", self.schema)?;
+        format_list(&self.data, self.data.len(), "", "]", f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product() -> Row {
+        let schema = Schema::new(
+            None,
+            &[
+                Field::new("name", DataType::Utf8),
+                Field::new("qty", DataType::I64),
+            ],
+        );
+        Row::new(schema, vec![dsl::str("Soda"), dsl::int(4)])
+    }
+
+    #[test]
+    fn access() {
+        let row = product();
+        assert_eq!(row.get(&"qty".into()), Some(&dsl::int(4)));
+        assert_eq!(row.get(&0.into()), Some(&dsl::str("Soda")));
+        assert_eq!(row.get(&"price".into()), None);
+        assert_eq!(row.get(&2.into()), None);
+        assert_eq!(row.to_string(), "[name:Str, qty:Int; \"Soda\", 4]");
+    }
+
+    #[test]
+    fn equality() {
+        let row = product();
+        let swapped = Row::new(
+            Schema::new(
+                None,
+                &[
+                    Field::new("qty", DataType::I64),
+                    Field::new("name", DataType::Utf8),
+                ],
+            ),
+            vec![dsl::int(4), dsl::str("Soda")],
+        );
+        assert_eq!(row, swapped);
+        assert_eq!(row.as_slice(), swapped.as_slice());
+        assert_eq!(row.as_slice(), row);
+
+        let other = Row::new(row.schema.clone(), vec![dsl::str("Tea"), dsl::int(4)]);
+        assert_ne!(row, other);
+    }
+
+    #[test]
+    fn project() {
+        let row = product();
+        let qty = row.project(&["qty".into()]).unwrap();
+        assert_eq!(qty.to_string(), "[qty:Int; 4]");
+        assert_eq!(row.as_slice().project(&[1.into()]).unwrap(), qty);
+
+        let err = row.project(&["price".into()]).unwrap_err();
+        assert_eq!(err.to_string(), "The column `#price` is not in the schema");
+    }
+}
@@ -10,6 +10,7 @@ use std::ops::Range;
 use bitvec::prelude as bv;
 use decorum::Total;
 
+use crate::algebraic::Variant;
 use crate::prelude::*;
 
 /// The total ordered [f64]
@@ -70,16 +71,20 @@ impl DateT {
 pub enum ScalarSlice<'a> {
     /// The **BOTTOM** value
     Unit(&'a [()]),
-    Bool(&'a [bool]),
     Bit(&'a BitSlice),
+    Bool(&'a [bool]),
     //Numeric
     I64(&'a [i64]),
-    Decimal(&'a [Decimal]),
     F64(&'a [F64]),
+    Decimal(&'a [Decimal]),
+    Money(&'a [Money]),
     //Date
     Date(DateKind, &'a [DateT]),
+    Interval(&'a [Interval]),
     //Strings
+    Char(&'a [char]),
     Utf8(&'a [String]),
+    Sum(&'a [Variant]),
     // General
     Scalar(&'a [Scalar]),
     /// The **TOP** value
@@ -87,16 +92,20 @@ pub enum ScalarSlice<'a> {
 }
 
 impl<'a> ScalarSlice<'a> {
-    fn len(&self) -> usize {
+    pub(crate) fn len(&self) -> usize {
         match self {
             Self::Unit(x) => x.len(),
             Self::Bool(x) => x.len(),
             Self::Bit(x) => x.len(),
             Self::I64(x) => x.len(),
             Self::Decimal(x) => x.len(),
+            Self::Money(x) => x.len(),
             Self::F64(x) => x.len(),
             Self::Date(_, x) => x.len(),
+            Self::Interval(x) => x.len(),
+            Self::Char(x) => x.len(),
             Self::Utf8(x) => x.len(),
+            Self::Sum(x) => x.len(),
             Self::Scalar(x) => x.len(),
             Self::Top(x) => x.len(),
         }
@@ -117,9 +126,13 @@ impl<'a> ScalarSlice<'a> {
             Self::Bit(_) => DataType::Bit,
             Self::I64(_) => DataType::I64,
             Self::Decimal(_) => DataType::Decimal,
+            Self::Money(_) => DataType::Money,
             Self::F64(_) => DataType::F64,
             Self::Date(x, _) => DataType::Date(*x),
+            Self::Interval(_) => DataType::Interval,
+            Self::Char(_) => DataType::Char,
             Self::Utf8(_) => DataType::Utf8,
+            Self::Sum(x) => x.first().map(Variant::kind).unwrap_or(DataType::Any),
             Self::Scalar(_) => DataType::Any,
             Self::Top(_) => DataType::Any,
         }
@@ -132,17 +145,43 @@ impl<'a> ScalarSlice<'a> {
             Self::Bit(x) => Self::Bit(&x[r]),
             Self::I64(x) => Self::I64(&x[r]),
             Self::Decimal(x) => Self::Decimal(&x[r]),
+            Self::Money(x) => Self::Money(&x[r]),
             Self::F64(x) => Self::F64(&x[r]),
             Self::Date(kind, x) => Self::Date(*kind, &x[r]),
+            Self::Interval(x) => Self::Interval(&x[r]),
+            Self::Char(x) => Self::Char(&x[r]),
             Self::Utf8(x) => Self::Utf8(&x[r]),
+            Self::Sum(x) => Self::Sum(&x[r]),
             Self::Scalar(x) => Self::Scalar(&x[r]),
             Self::Top(x) => Self::Top(&x[r]),
         }
     }
 
-    /// Get a row range
+    /// Get a copy of the value at `pos`
+    pub fn get(&self, pos: usize) -> Option<Scalar> {
+        let x = match self {
+            Self::Unit(x) => Scalar::Unit([*x.get(pos)?]),
+            Self::Bool(x) => Scalar::Bool([*x.get(pos)?]),
+            Self::Bit(x) => Scalar::Bit(x.get(pos..pos + 1)?.to_bitvec()),
+            Self::I64(x) => Scalar::I64([*x.get(pos)?]),
+            Self::Decimal(x) => Scalar::Decimal([*x.get(pos)?]),
+            Self::Money(x) => Scalar::Money([*x.get(pos)?]),
+            Self::F64(x) => Scalar::F64([*x.get(pos)?]),
+            Self::Date(_, x) => Scalar::Date([x.get(pos)?.clone()]),
+            Self::Interval(x) => Scalar::Interval([*x.get(pos)?]),
+            Self::Char(x) => Scalar::Char([*x.get(pos)?]),
+            Self::Utf8(x) => Scalar::Utf8([x.get(pos)?.clone()]),
+            Self::Sum(x) => Scalar::Sum([x.get(pos)?.clone()]),
+            Self::Scalar(x) => x.get(pos)?.clone(),
+            Self::Top(x) => Scalar::Top([*x.get(pos)?]),
+        };
+        Some(x)
+    }
+
+    /// Get a row range of a *row-major* slice of `cols` columns
     pub fn to_row(self, row: usize, cols: usize) -> Self {
-        self.range(row * cols..cols)
+        let start = row * cols;
+        self.range(start..start + cols)
     }
 }
 
@@ -150,20 +189,28 @@ impl<'a> ScalarSlice<'a> {
 //NOTE: This defines a total order, so it matter what is the order of the enum!
 //Must match DataType
 //The overall sorting order is defined as:
+//  Unit < Bit < Bool < Int < Float < Dec < Money < Date < Interval < Char < Str < Sum < Top
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Scalar {
     /// The **BOTTOM** value
     Unit([(); 1]),
-    Bool([bool; 1]),
     Bit(BitVec),
+    Bool([bool; 1]),
     //Numeric
     I64([i64; 1]),
-    Decimal([Decimal; 1]),
     F64([F64; 1]),
+    Decimal([Decimal; 1]),
+    /// A amount in a currency, like `12.50 USD`
+    Money([Money; 1]),
     //Date
     Date([DateT; 1]),
+    Interval([Interval; 1]),
     //Strings
+    /// A unicode scalar value, like `c'a'`
+    Char([char; 1]),
     Utf8([String; 1]),
+    /// A case of a `enum`, like `Some(1)`
+    Sum([Variant; 1]),
     /// The **TOP** value
     Top([(); 1]),
 }
@@ -180,9 +227,13 @@ impl Scalar {
             Self::Bit(x) => ScalarSlice::Bit(x),
             Self::I64(x) => ScalarSlice::I64(x),
             Self::Decimal(x) => ScalarSlice::Decimal(x),
+            Self::Money(x) => ScalarSlice::Money(x),
             Self::F64(x) => ScalarSlice::F64(x),
             Self::Date(x) => ScalarSlice::Date(x[0].kind, x),
+            Self::Interval(x) => ScalarSlice::Interval(x),
+            Self::Char(x) => ScalarSlice::Char(x),
             Self::Utf8(x) => ScalarSlice::Utf8(x),
+            Self::Sum(x) => ScalarSlice::Sum(x),
             Self::Top(x) => ScalarSlice::Top(x),
         }
     }
@@ -199,21 +250,33 @@ impl Rel for Scalar {
         let kind = self.slice().kind();
         Schema::new_scalar(kind)
     }
+
+    fn len(&self) -> usize {
+        1
+    }
+
+    fn rows(&self) -> RowsIter<'_> {
+        Box::new(std::iter::once(vec![self.clone()]))
+    }
 }
 
 impl fmt::Display for Scalar {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Scalar::Unit(_x) => todo!(),
+            Scalar::Unit(_x) => write!(f, "()"),
             Scalar::Bool(x) => format_slice_scalar(x, f),
             Scalar::Bit(x) => format_slice_bit(x, f),
             Scalar::I64(x) => format_slice_scalar(x, f),
             Scalar::Decimal(x) => format_slice_scalar_postfix(x, "d", f),
             Scalar::F64(x) => format_slice_scalar_postfix(x, "f", f),
+            Scalar::Money([x]) => write!(f, "{}", x),
             Scalar::Date(x) => {
                 write!(f, "{}", x[0].fmt())
             }
-            Scalar::Utf8(x) => format_slice_scalar(x, f),
+            Scalar::Interval([x]) => write!(f, "{}", x),
+            Scalar::Char([x]) => write!(f, "'{}'", x),
+            Scalar::Utf8([x]) => write!(f, "\"{}\"", x),
+            Scalar::Sum([x]) => write!(f, "{}", x),
             Scalar::Top(_x) => todo!(),
         }
     }
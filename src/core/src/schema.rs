@@ -3,13 +3,16 @@
 //! A relational schema specifies the set of [Field] (attributes) in the inner container and a [DataType] for each field,
 //! and gives the guarantee that 2 schemas are equal if (irrespective of the *order* of the fields), both match.
 
+use std::collections::BTreeSet;
+use std::fmt;
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 
+use crate::errors::{ErrorCtx, ErrorKind};
 use crate::prelude::*;
 
 /// The default field/column name for [Scalar]/[Vector] relations
-const FIELD_NAME_SCALAR: &str = "it";
+pub const FIELD_NAME_SCALAR: &str = "it";
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct FieldSlice<'a> {
@@ -27,6 +30,9 @@ impl<'a> FieldSlice<'a> {
 pub struct Field {
     pub name: String,
     pub kind: DataType,
+    /// If the values can be missing, as [Scalar::Unit], like the fields of the `rhs` of a left
+    /// join. Then the column is not stored as a typed [Array]
+    pub nullable: bool,
 }
 
 impl<'a> From<&'a Field> for FieldSlice<'a> {
@@ -43,38 +49,116 @@ impl Field {
         Field {
             name: name.to_string(),
             kind,
+            nullable: false,
         }
     }
+
+    /// The same field, but allowing missing values
+    pub fn with_nullable(mut self) -> Self {
+        self.nullable = true;
+        self
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.name, self.kind)?;
+        if self.nullable {
+            write!(f, "?")?;
+        }
+        Ok(())
+    }
+}
+
+/// Refer to a [Field] by name, like `#name`, or by position, like `#0`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Column {
+    Name(String),
+    Pos(usize),
+}
+
+impl From<&str> for Column {
+    fn from(x: &str) -> Self {
+        Column::Name(x.into())
+    }
+}
+
+impl From<usize> for Column {
+    fn from(x: usize) -> Self {
+        Column::Pos(x)
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Column::Name(x) => write!(f, "#{}", x),
+            Column::Pos(x) => write!(f, "#{}", x),
+        }
+    }
+}
+
+/// The positions of the fields that identify a row, in order
+pub type Key = Vec<usize>;
+
+/// Check the `key` is in the bounds of the fields & not repeat a field
+fn check_key(key: &[usize], total: usize) {
+    assert!(
+        key.iter().all(|x| *x < total),
+        "The selected key is out of bounds"
+    );
+    assert!(
+        key.iter()
+            .enumerate()
+            .all(|(pos, x)| !key[..pos].contains(x)),
+        "The selected key repeat a field"
+    );
 }
 
 #[derive(Debug, Clone, Eq, PartialOrd, Ord)]
 pub struct Schema {
-    pub pk: Option<usize>,
+    /// The primary key, empty if the relation not have one
+    pub pk: Key,
+    /// Other keys that, like the `pk`, can't be repeated in the relation
+    pub unique: Vec<Key>,
     pub fields: Vec<Field>,
 }
 
 impl Schema {
+    /// Build the schema with a key of a single field, if any
     pub fn new(pk: Option<usize>, fields: &[Field]) -> Self {
+        let pk: Key = pk.into_iter().collect();
+        Self::new_keyed(&pk, fields)
+    }
+
+    /// Build the schema with a key of many fields, like `invoice, line`
+    pub fn new_keyed(pk: &[usize], fields: &[Field]) -> Self {
         if !fields.is_empty() {
-            assert!(
-                pk.unwrap_or(0) < fields.len(),
-                "The selected PK is out of bounds"
-            );
+            check_key(pk, fields.len());
         }
         Self {
-            pk,
+            pk: pk.into(),
+            unique: Vec::new(),
             fields: fields.into(),
         }
     }
 
+    /// Add a unique `key`, that must not be repeated in the relation
+    pub fn with_unique(mut self, key: &[usize]) -> Self {
+        check_key(key, self.len());
+        self.unique.push(key.into());
+        self
+    }
+
+    /// A single column, without a key
     pub fn new_single(name: &str, kind: DataType) -> Self {
         let field = Field::new(name, kind);
-        Self::new(Some(0), &[field])
+        Self::new(None, &[field])
     }
 
+    /// A single column named `it`, without a key
     pub fn new_scalar(kind: DataType) -> Self {
-        let field = Field::new(FIELD_NAME_SCALAR, kind);
-        Self::new(Some(0), &[field])
+        Self::new_single(FIELD_NAME_SCALAR, kind)
     }
 
     pub fn len(&self) -> usize {
@@ -84,11 +168,140 @@ impl Schema {
     pub fn is_empty(&self) -> bool {
         self.fields.is_empty()
     }
+
+    /// Find the position of the field `name`
+    pub fn resolve_name(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|x| x.name == name)
+    }
+
+    /// Find the position of the field for `col`
+    pub fn resolve(&self, col: &Column) -> Option<usize> {
+        match col {
+            Column::Name(name) => self.resolve_name(name),
+            Column::Pos(pos) => (*pos < self.len()).then_some(*pos),
+        }
+    }
+
+    /// Find the positions of the fields for `cols`, or the first that is not in the schema
+    pub fn resolve_all(&self, cols: &[Column]) -> ResultT<Vec<usize>> {
+        cols.iter()
+            .map(|col| {
+                self.resolve(col).ok_or_else(|| ErrorCore::FieldNotFound {
+                    column: col.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Find the position in `other` of each of the fields, matching by name & type so the
+    /// order of the fields not matter.
+    ///
+    /// If the fields are not the same, the error list the ones that are only in `self` (the
+    /// `lhs`) or in `other` (the `rhs`). The keys are not compared
+    pub fn align(&self, other: &Schema) -> ResultT<Vec<usize>> {
+        let find = |of: &Schema, field: &Field| of.fields.iter().position(|x| x == field);
+        let pos: Option<Vec<_>> = self.fields.iter().map(|x| find(other, x)).collect();
+        match pos {
+            Some(pos) if self.len() == other.len() => return Ok(pos),
+            _ => {}
+        }
+
+        let missing = |side: &str, of: &Schema, other: &Schema| {
+            of.fields
+                .iter()
+                .filter(|x| find(other, x).is_none())
+                .map(|x| ErrorCtx::new(side, &x.to_string()))
+                .collect::<Vec<_>>()
+        };
+        let err = missing("lhs", self, other)
+            .into_iter()
+            .chain(missing("rhs", other, self))
+            .fold(
+                ErrorLang::new(
+                    ErrorKind::Invalid,
+                    Some("The fields of the relations not match"),
+                ),
+                |err, ctx| err.with_ctx(ctx),
+            );
+        Err(ErrorCore::Lang(Box::new(err)))
+    }
+
+    /// Build the schema with only the fields at `pos`, in that order.
+    ///
+    /// Only the keys with all their fields in `pos` are kept
+    pub fn project(&self, pos: &[usize]) -> Schema {
+        let fields: Vec<_> = pos.iter().map(|x| self.fields[*x].clone()).collect();
+        let remap = |key: &Key| -> Option<Key> {
+            key.iter()
+                .map(|k| pos.iter().position(|x| x == k))
+                .collect()
+        };
+        let mut schema = Schema::new_keyed(&remap(&self.pk).unwrap_or_default(), &fields);
+        schema.unique = self.unique.iter().filter_map(remap).collect();
+        schema
+    }
+
+    /// The keys that can't be repeated: the `pk`, if any, & the `unique` keys
+    pub fn keys(&self) -> impl Iterator<Item = &Key> {
+        std::iter::once(&self.pk)
+            .filter(|x| !x.is_empty())
+            .chain(&self.unique)
+    }
+
+    /// The values of the `key` in the `row`
+    pub fn key_values(&self, key: &[usize], row: &[Scalar]) -> Vec<Scalar> {
+        key.iter().map(|x| row[*x].clone()).collect()
+    }
+
+    /// The error for the `values` of the `key`, found in more than one row
+    pub fn duplicated(&self, key: &[usize], values: &[Scalar]) -> ErrorCore {
+        let msg = if key == self.pk.as_slice() {
+            "The primary key is duplicated"
+        } else {
+            "The unique key is duplicated"
+        };
+        let err = key.iter().zip(values).fold(
+            ErrorLang::new(ErrorKind::Duplicated, Some(msg)),
+            |err, (pos, value)| {
+                err.with_ctx(ErrorCtx::new(&self.fields[*pos].name, &value.to_string()))
+            },
+        );
+        ErrorCore::Lang(Box::new(err))
+    }
+
+    /// Check the `rows` not repeat the values of any of the keys
+    pub fn check_keys(&self, rows: impl IntoIterator<Item = Vec<Scalar>>) -> ResultT<()> {
+        let keys: Vec<_> = self.keys().collect();
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        let mut seen = vec![BTreeSet::new(); keys.len()];
+        for row in rows {
+            for (key, seen) in keys.iter().zip(seen.iter_mut()) {
+                let values = self.key_values(key, &row);
+                if seen.contains(&values) {
+                    return Err(self.duplicated(key, &values));
+                }
+                seen.insert(values);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format_list(&self.fields, self.fields.len(), "", "", f)
+    }
 }
 
 impl PartialEq for Schema {
     fn eq(&self, other: &Schema) -> bool {
-        if self.pk == other.pk && self.fields.len() == other.fields.len() {
+        if self.pk == other.pk
+            && self.unique == other.unique
+            && self.fields.len() == other.fields.len()
+        {
             let mut a = self.fields.clone();
             let mut b = other.fields.clone();
             a.sort();
@@ -103,6 +316,7 @@ impl PartialEq for Schema {
 impl Hash for Schema {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.pk.hash(state);
+        self.unique.hash(state);
         let mut a = self.fields.clone();
         a.sort();
         a.hash(state);
@@ -0,0 +1,161 @@
+//! # Set operators.
+//!
+//! Combine the rows of two relations with the same fields, like `union`. As the comparison of
+//! [Schema], the order of the fields not matter: the columns of the `rhs` are aligned by name
+//! to the ones of the `lhs`, so `[a:Int, b:Str]` & `[b:Str, a:Int]` can be combined.
+//!
+//! The result is a [Table] with the schema of the `lhs`, and like a set, without repeated rows.
+use std::collections::BTreeSet;
+
+use crate::prelude::*;
+use crate::query::to_table;
+
+/// The rows of the `rhs`, with the fields in the order of the `lhs`
+fn aligned_rows(lhs: &Schema, rhs: &dyn Rel) -> ResultT<Vec<Vec<Scalar>>> {
+    let pos = lhs.align(&rhs.schema())?;
+    Ok(rhs
+        .rows()
+        .map(|row| pos.iter().map(|x| row[*x].clone()).collect())
+        .collect())
+}
+
+/// The rows of the `lhs` that are (or not, if `!found`) in the `rhs`, without repeating them
+fn filter_rows(lhs: &dyn Rel, rhs: &dyn Rel, found: bool) -> ResultT<Table> {
+    let schema = lhs.schema();
+    let of_rhs: BTreeSet<_> = aligned_rows(&schema, rhs)?.into_iter().collect();
+    let mut seen = BTreeSet::new();
+    let rows = lhs
+        .rows()
+        .filter(|row| of_rhs.contains(row) == found && seen.insert(row.clone()));
+    to_table(schema, rows)
+}
+
+/// Union: the rows of the `lhs`, then the rows of the `rhs` that are not in the `lhs`.
+///
+/// The keys of the `lhs` are kept, so is a error if the `rhs` has other values for a key
+pub fn union(lhs: &dyn Rel, rhs: &dyn Rel) -> ResultT<Table> {
+    let schema = lhs.schema();
+    let of_rhs = aligned_rows(&schema, rhs)?;
+    let mut seen = BTreeSet::new();
+    let rows = lhs
+        .rows()
+        .chain(of_rhs)
+        .filter(|row| seen.insert(row.clone()));
+    to_table(schema, rows)
+}
+
+/// Intersection: the rows of the `lhs` that are also in the `rhs`
+pub fn intersect(lhs: &dyn Rel, rhs: &dyn Rel) -> ResultT<Table> {
+    filter_rows(lhs, rhs, true)
+}
+
+/// Difference: the rows of the `lhs` that are not in the `rhs`
+pub fn difference(lhs: &dyn Rel, rhs: &dyn Rel) -> ResultT<Table> {
+    filter_rows(lhs, rhs, false)
+}
+
+/// If the relations have the same fields & rows. Like a set, the order of the rows & the
+/// repeated ones not matter, so `[1; 2] = [2; 1; 1]`
+pub fn equals(lhs: &dyn Rel, rhs: &dyn Rel) -> bool {
+    match aligned_rows(&lhs.schema(), rhs) {
+        Ok(of_rhs) => {
+            let of_lhs: BTreeSet<_> = lhs.rows().collect();
+            of_lhs == of_rhs.into_iter().collect()
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sales(fields: &[Field], data: Vec<Scalar>) -> Table {
+        Table::from_rows(Schema::new(None, fields), data).unwrap()
+    }
+
+    fn name() -> Field {
+        Field::new("name", DataType::Utf8)
+    }
+
+    fn qty() -> Field {
+        Field::new("qty", DataType::I64)
+    }
+
+    #[test]
+    fn sets() {
+        let a = sales(
+            &[name(), qty()],
+            vec![
+                dsl::str("Soda"),
+                dsl::int(1),
+                dsl::str("Tea"),
+                dsl::int(2),
+                dsl::str("Soda"),
+                dsl::int(1),
+            ],
+        );
+        // The same fields, in other order
+        let b = sales(
+            &[qty(), name()],
+            vec![dsl::int(2), dsl::str("Tea"), dsl::int(3), dsl::str("Cake")],
+        );
+        assert_eq!(a.schema, b.schema);
+
+        assert_eq!(
+            union(&a, &b).unwrap().to_string(),
+            "[name:Str, qty:Int; \"Soda\", 1; \"Tea\", 2; \"Cake\", 3]"
+        );
+        assert_eq!(
+            intersect(&a, &b).unwrap().to_string(),
+            "[name:Str, qty:Int; \"Tea\", 2]"
+        );
+        assert_eq!(
+            difference(&a, &b).unwrap().to_string(),
+            "[name:Str, qty:Int; \"Soda\", 1]"
+        );
+        assert_eq!(
+            difference(&b, &a).unwrap().to_string(),
+            "[qty:Int, name:Str; 3, \"Cake\"]"
+        );
+
+        let c = sales(
+            &[qty(), name()],
+            vec![dsl::int(2), dsl::str("Tea"), dsl::int(1), dsl::str("Soda")],
+        );
+        assert!(equals(&a, &c));
+        assert!(!equals(&a, &b));
+        assert!(!equals(&a, &dsl::int(1)));
+
+        let nums = dsl::int(2);
+        let err = union(&a, &nums).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The fields of the relations not match: lhs = name:Str, lhs = qty:Int, rhs = it:Int"
+        );
+
+        let prices = sales(
+            &[name(), Field::new("qty", DataType::Decimal)],
+            vec![dsl::str("Tea"), dsl::dec(Decimal::from(2))],
+        );
+        let err = intersect(&a, &prices).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The fields of the relations not match: lhs = qty:Int, rhs = qty:Dec"
+        );
+    }
+
+    #[test]
+    fn keys() {
+        let schema = Schema::new(Some(0), &[name(), qty()]);
+        let a = Table::from_rows(schema.clone(), vec![dsl::str("Soda"), dsl::int(1)]).unwrap();
+        let b = Table::from_rows(schema, vec![dsl::str("Soda"), dsl::int(2)]).unwrap();
+
+        assert_eq!(union(&a, &a).unwrap().schema.pk, vec![0]);
+        let err = union(&a, &b).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The primary key is duplicated: name = \"Soda\""
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! # Columnar Table.
+//!
+//! A [Table] is a relation of *N* columns and *M* rows, stored by **column**: each [Field] of
+//! the [Schema] has its own [Array], so the values keep their native type.
+//!
+//! Getting a column is zero-copy, and a row is a view of the same position in all the columns.
+//!
+use std::fmt;
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Table {
+    pub schema: Schema,
+    pub rows: usize,
+    /// One column for each [Field] of the `schema`, in the same order
+    pub cols: Vec<Array>,
+}
+
+impl Table {
+    /// Build the table from the columns, that must match the fields of the `schema`
+    /// and have the same length.
+    ///
+    /// Fail if the rows repeat the values of a key of the `schema`
+    pub fn new(schema: Schema, cols: Vec<Array>) -> ResultT<Self> {
+        assert_eq!(
+            schema.len(),
+            cols.len(),
+            "The columns not match the fields of the schema"
+        );
+        let rows = cols.first().map(|x| x.len()).unwrap_or_default();
+        assert!(
+            cols.iter().all(|x| x.len() == rows),
+            "The columns must have the same length"
+        );
+
+        let table = Self { schema, rows, cols };
+        table
+            .schema
+            .check_keys((0..rows).map(|x| table.row(x).to_row().data))?;
+        Ok(table)
+    }
+
+    /// Build the table from the values in *row-major* order, like the cells of a literal
+    pub fn from_rows(schema: Schema, data: Vec<Scalar>) -> ResultT<Self> {
+        let total = schema.len();
+        let rows = data.len().checked_div(total).unwrap_or_default();
+
+        let mut cols: Vec<Vec<Scalar>> = (0..total).map(|_| Vec::with_capacity(rows)).collect();
+        for (pos, x) in data.into_iter().enumerate() {
+            cols[pos % total].push(x);
+        }
+
+        let cols = schema
+            .fields
+            .iter()
+            .zip(cols)
+            .map(|(field, data)| Array::from_scalars(&field.kind, data))
+            .collect();
+
+        Self::new(schema, cols)
+    }
+
+    /// Get the column at `pos`
+    pub fn col(&self, pos: usize) -> Option<ScalarSlice<'_>> {
+        self.cols.get(pos).map(|x| x.slice())
+    }
+
+    /// Get the column with the field `name`
+    pub fn col_named(&self, name: &str) -> Option<ScalarSlice<'_>> {
+        self.schema.resolve_name(name).and_then(|pos| self.col(pos))
+    }
+
+    /// Get the values of the `row`, as a view of each column
+    pub fn row(&self, row: usize) -> RowSlice<'_> {
+        let data = self
+            .cols
+            .iter()
+            .map(|x| x.slice().range(row..row + 1))
+            .collect();
+        RowSlice::new(&self.schema, data)
+    }
+}
+
+/// Display the table as is written in the language, like `[a:Int, b:Str; 1, "a"]`
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}", self.schema)?;
+        for row in 0..self.rows {
+            write!(f, "; ")?;
+            for (pos, col) in self.cols.iter().enumerate() {
+                if pos > 0 {
+                    write!(f, ", ")?;
+                }
+                match col.get(row) {
+                    Some(x) => write!(f, "{}", x)?,
+                    None => write!(f, "?")?,
+                }
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+impl Rel for Table {
+    fn type_name(&self) -> &str {
+        "Table"
+    }
+
+    fn schema(&self) -> Schema {
+        self.schema.clone()
+    }
+
+    fn len(&self) -> usize {
+        self.rows
+    }
+
+    fn rows(&self) -> RowsIter<'_> {
+        Box::new((0..self.rows).map(|x| self.row(x).to_row().data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sales() -> Table {
+        let schema = Schema::new(
+            None,
+            &[
+                Field::new("name", DataType::Utf8),
+                Field::new("price", DataType::Decimal),
+                Field::new("qty", DataType::I64),
+            ],
+        );
+        let data = vec![
+            dsl::str("Hamburger"),
+            dsl::dec(Decimal::new(102, 1)),
+            dsl::int(2),
+            dsl::str("Soda"),
+            dsl::dec(Decimal::new(30, 1)),
+            dsl::int(4),
+        ];
+        Table::from_rows(schema, data).unwrap()
+    }
+
+    #[test]
+    fn columns() {
+        let table = sales();
+        assert_eq!(table.rows, 2);
+        assert_eq!(
+            table.cols[0],
+            Array::Utf8(vec!["Hamburger".into(), "Soda".into()])
+        );
+        assert_eq!(table.cols[2], Array::I64(vec![2, 4]));
+
+        assert_eq!(table.col(2), Some(ScalarSlice::I64(&[2, 4])));
+        assert_eq!(table.col_named("qty"), table.col(2));
+        assert_eq!(table.col_named("total"), None);
+        assert_eq!(table.col(3), None);
+    }
+
+    #[test]
+    fn rows() {
+        let table = sales();
+        let row = table.row(1);
+        assert_eq!(row.data.len(), 3);
+        assert_eq!(row.data[0], ScalarSlice::Utf8(&["Soda".into()]));
+        assert_eq!(row.get(&"qty".into()), Some(dsl::int(4)));
+
+        assert_eq!(
+            table.to_string(),
+            "[name:Str, price:Dec, qty:Int; \"Hamburger\", 10.2d, 2; \"Soda\", 3.0d, 4]"
+        );
+    }
+
+    #[test]
+    fn keys() {
+        let schema = Schema::new_keyed(
+            &[0, 1],
+            &[
+                Field::new("invoice", DataType::I64),
+                Field::new("line", DataType::I64),
+            ],
+        );
+        let data = vec![dsl::int(1), dsl::int(1), dsl::int(1), dsl::int(2)];
+        assert!(Table::from_rows(schema.clone(), data).is_ok());
+
+        let data = vec![dsl::int(1), dsl::int(1), dsl::int(1), dsl::int(1)];
+        let err = Table::from_rows(schema, data).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The primary key is duplicated: invoice = 1, line = 1"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! # Functions over the text of [Scalar::Utf8].
+//!
+//! The positions & lengths are counted in *graphemes*, the characters as seen by a person, so
+//! `'🇨🇴'.len()` is `1` even if is made of 2 unicode scalar values & 8 bytes. The positions start
+//! at `0`.
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::errors::{ErrorCtx, ErrorKind};
+use crate::prelude::*;
+
+/// Which side of the text trim or pad
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Start,
+    End,
+    Both,
+}
+
+/// The max `width` of [pad], so a typo can't fill the memory
+pub const MAX_WIDTH: usize = 1_000_000;
+
+fn graphemes(of: &str) -> impl Iterator<Item = &str> {
+    of.graphemes(true)
+}
+
+fn invalid(msg: &str, name: &str, value: &str) -> ErrorCore {
+    let err = ErrorLang::new(ErrorKind::Invalid, Some(msg)).with_ctx(ErrorCtx::new(name, value));
+    ErrorCore::Lang(Box::new(err))
+}
+
+fn position(name: &str, of: i64) -> ResultT<usize> {
+    usize::try_from(of)
+        .map_err(|_| invalid("The position can't be negative", name, &of.to_string()))
+}
+
+/// The amount of graphemes
+pub fn len(of: &str) -> usize {
+    graphemes(of).count()
+}
+
+/// The graphemes from `start` until `end`, not included. Past the end is truncated, so
+/// `'abc'.slice(1, 10)` is `'bc'`
+pub fn slice(of: &str, start: i64, end: Option<i64>) -> ResultT<String> {
+    let start = position("start", start)?;
+    let end = match end {
+        Some(end) => position("end", end)?,
+        None => usize::MAX,
+    };
+    Ok(graphemes(of)
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .collect())
+}
+
+pub fn upper(of: &str) -> String {
+    of.to_uppercase()
+}
+
+pub fn lower(of: &str) -> String {
+    of.to_lowercase()
+}
+
+/// Remove the whitespace at the `side`
+pub fn trim(of: &str, side: Side) -> &str {
+    match side {
+        Side::Start => of.trim_start(),
+        Side::End => of.trim_end(),
+        Side::Both => of.trim(),
+    }
+}
+
+/// The position of the first `pattern`, or `-1` if is not found
+pub fn find(of: &str, pattern: &str) -> i64 {
+    match of.find(pattern) {
+        Some(pos) => len(&of[..pos]) as i64,
+        None => -1,
+    }
+}
+
+/// Replace all the `from` with `to`
+pub fn replace(of: &str, from: &str, to: &str) -> ResultT<String> {
+    if from.is_empty() {
+        return Err(invalid("The text to replace can't be empty", "from", from));
+    }
+    Ok(of.replace(from, to))
+}
+
+/// Fill with `fill` at the `side` until it has `width` graphemes. With [Side::Both] the extra
+/// fill goes at the end
+pub fn pad(of: &str, width: i64, fill: &str, side: Side) -> ResultT<String> {
+    let width = position("width", width)?;
+    if width > MAX_WIDTH {
+        let msg = format!("The width can't be more than {MAX_WIDTH}");
+        return Err(invalid(&msg, "width", &width.to_string()));
+    }
+    if len(fill) != 1 {
+        return Err(invalid("The fill must be a single character", "fill", fill));
+    }
+    let missing = width.saturating_sub(len(of));
+    let (start, end) = match side {
+        Side::Start => (missing, 0),
+        Side::End => (0, missing),
+        Side::Both => (missing / 2, missing - missing / 2),
+    };
+    Ok(format!("{}{of}{}", fill.repeat(start), fill.repeat(end)))
+}
+
+/// Split the text by `sep`, or in graphemes if `sep` is empty
+pub fn split(of: &str, sep: &str) -> Vec<String> {
+    if sep.is_empty() {
+        graphemes(of).map(String::from).collect()
+    } else {
+        of.split(sep).map(String::from).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions() {
+        let flag = "🇨🇴 Colombia";
+        assert_eq!(len(flag), 10);
+        assert_eq!(len("e\u{301}"), 1);
+        assert_eq!(slice(flag, 0, Some(1)).unwrap(), "🇨🇴");
+        assert_eq!(slice(flag, 2, None).unwrap(), "Colombia");
+        assert_eq!(slice("abc", 1, Some(10)).unwrap(), "bc");
+        assert_eq!(slice("abc", 2, Some(1)).unwrap(), "");
+        assert_eq!(
+            slice("abc", -1, None).unwrap_err().to_string(),
+            "The position can't be negative: start = -1"
+        );
+        assert_eq!(find(flag, "Col"), 2);
+        assert_eq!(find(flag, "Peru"), -1);
+        assert_eq!(split("a🇨🇴b", ""), ["a", "🇨🇴", "b"]);
+        assert_eq!(split("a,b,,c", ","), ["a", "b", "", "c"]);
+    }
+
+    #[test]
+    fn transform() {
+        assert_eq!(upper("straße"), "STRASSE");
+        assert_eq!(lower("ÁRBOL"), "árbol");
+        assert_eq!(trim("  a b  ", Side::Start), "a b  ");
+        assert_eq!(trim("  a b  ", Side::Both), "a b");
+        assert_eq!(replace("a-b-c", "-", "+").unwrap(), "a+b+c");
+        assert!(replace("abc", "", "+").is_err());
+
+        assert_eq!(pad("7", 3, "0", Side::Start).unwrap(), "007");
+        assert_eq!(pad("ñ", 4, "·", Side::Both).unwrap(), "·ñ··");
+        assert_eq!(pad("long", 2, " ", Side::End).unwrap(), "long");
+        assert_eq!(
+            pad("a", 3, "ab", Side::End).unwrap_err().to_string(),
+            "The fill must be a single character: fill = ab"
+        );
+        assert_eq!(
+            pad("a", i64::MAX, " ", Side::Start)
+                .unwrap_err()
+                .to_string(),
+            "The width can't be more than 1000000: width = 9223372036854775807"
+        );
+    }
+}
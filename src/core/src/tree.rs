@@ -0,0 +1,284 @@
+//! # Relational Tree.
+//!
+//! A [Tree] is a collection of rows stored in a [BTreeMap], ordered by the values of the
+//! `pk` of the [Schema], following the total order of [Scalar]. The key can be of many fields,
+//! like `invoice, line`, so a range of the first fields scan all the rows that start with them.
+//!
+//! Inserting a row with a key already in the tree **replace** the previous row, but the
+//! `unique` keys of the schema can't be repeated by rows of *another* key.
+//!
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeBounds;
+
+use crate::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tree {
+    pub schema: Schema,
+    /// The values of each row, in the order of the fields of the `schema`
+    data: BTreeMap<Vec<Scalar>, Vec<Scalar>>,
+    /// For each unique key of the `schema`, the `pk` of the row with those values
+    unique: Vec<BTreeMap<Vec<Scalar>, Vec<Scalar>>>,
+}
+
+impl Tree {
+    /// Build a empty tree, the `schema` must have a `pk`
+    pub fn new(schema: Schema) -> Self {
+        assert!(!schema.pk.is_empty(), "A Tree must have a pk");
+        Self {
+            unique: vec![BTreeMap::new(); schema.unique.len()],
+            schema,
+            data: BTreeMap::new(),
+        }
+    }
+
+    /// Build the tree from the values in *row-major* order, like the cells of a literal
+    pub fn from_rows(schema: Schema, data: Vec<Scalar>) -> ResultT<Self> {
+        let mut tree = Self::new(schema);
+        let total = tree.schema.len();
+        let mut data = data.into_iter();
+        loop {
+            let row: Vec<_> = data.by_ref().take(total).collect();
+            if row.is_empty() {
+                break;
+            }
+            tree.insert(row)?;
+        }
+        Ok(tree)
+    }
+
+    /// Insert the `row`, return the previous row with the same key.
+    ///
+    /// Fail if a row with another key has the same values of a unique key
+    pub fn insert(&mut self, row: Vec<Scalar>) -> ResultT<Option<Vec<Scalar>>> {
+        assert_eq!(
+            self.schema.len(),
+            row.len(),
+            "The values not match the fields of the schema"
+        );
+        let key = self.schema.key_values(&self.schema.pk, &row);
+
+        let mut unique = Vec::with_capacity(self.unique.len());
+        for (fields, index) in self.schema.unique.iter().zip(&self.unique) {
+            let values = self.schema.key_values(fields, &row);
+            match index.get(&values) {
+                Some(other) if *other != key => {
+                    return Err(self.schema.duplicated(fields, &values))
+                }
+                _ => unique.push(values),
+            }
+        }
+
+        let old = self.data.insert(key.clone(), row);
+        for ((fields, index), values) in self.schema.unique.iter().zip(&mut self.unique).zip(unique)
+        {
+            if let Some(old) = &old {
+                index.remove(&self.schema.key_values(fields, old));
+            }
+            index.insert(values, key.clone());
+        }
+        Ok(old)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Find the row with the values of the `key`
+    pub fn get(&self, key: &[Scalar]) -> Option<RowSlice<'_>> {
+        self.data
+            .get(key)
+            .map(|x| RowSlice::from_values(&self.schema, x))
+    }
+
+    /// The rows ordered by the key
+    pub fn iter(&self) -> impl Iterator<Item = RowSlice<'_>> {
+        self.data
+            .values()
+            .map(|x| RowSlice::from_values(&self.schema, x))
+    }
+
+    /// The rows with the key in the `range`, ordered by the key
+    pub fn range<R: RangeBounds<Vec<Scalar>>>(
+        &self,
+        range: R,
+    ) -> impl Iterator<Item = RowSlice<'_>> {
+        self.data
+            .range(range)
+            .map(|(_, x)| RowSlice::from_values(&self.schema, x))
+    }
+}
+
+/// Display the tree as is written in the language, like `Tree[|pk id:Int; 1; 2|]`
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Tree[|")?;
+        for (pos, field) in self.schema.fields.iter().enumerate() {
+            if pos > 0 {
+                write!(f, ", ")?;
+            }
+            if self.schema.pk.contains(&pos) {
+                write!(f, "pk ")?;
+            }
+            write!(f, "{}", field)?;
+        }
+        for row in self.data.values() {
+            write!(f, "; ")?;
+            format_list(row, row.len(), "", "", f)?;
+        }
+        write!(f, "|]")
+    }
+}
+
+impl Rel for Tree {
+    fn type_name(&self) -> &str {
+        "Tree"
+    }
+
+    fn schema(&self) -> Schema {
+        self.schema.clone()
+    }
+
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn rows(&self) -> RowsIter<'_> {
+        Box::new(self.data.values().cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    fn names() -> Tree {
+        let schema = Schema::new(
+            Some(0),
+            &[
+                Field::new("id", DataType::I64),
+                Field::new("name", DataType::Utf8),
+            ],
+        );
+        let data = vec![
+            dsl::int(3),
+            dsl::str("c"),
+            dsl::int(1),
+            dsl::str("a"),
+            dsl::int(2),
+            dsl::str("b"),
+            dsl::int(1),
+            dsl::str("z"),
+        ];
+        Tree::from_rows(schema, data).unwrap()
+    }
+
+    fn lines() -> Schema {
+        Schema::new_keyed(
+            &[0, 1],
+            &[
+                Field::new("invoice", DataType::I64),
+                Field::new("line", DataType::I64),
+                Field::new("code", DataType::Utf8),
+            ],
+        )
+    }
+
+    #[test]
+    fn ordered() {
+        let tree = names();
+        assert_eq!(tree.len(), 3);
+        let ids: Vec<_> = tree.iter().filter_map(|x| x.get(&0.into())).collect();
+        assert_eq!(ids, vec![dsl::int(1), dsl::int(2), dsl::int(3)]);
+        assert_eq!(
+            tree.to_string(),
+            "Tree[|pk id:Int, name:Str; 1, \"z\"; 2, \"b\"; 3, \"c\"|]"
+        );
+    }
+
+    #[test]
+    fn lookup() {
+        let mut tree = names();
+        let row = tree.get(&[dsl::int(1)]).unwrap();
+        assert_eq!(row.get(&"name".into()), Some(dsl::str("z")));
+        assert!(tree.get(&[dsl::int(4)]).is_none());
+
+        let old = tree.insert(vec![dsl::int(2), dsl::str("y")]).unwrap();
+        assert_eq!(old, Some(vec![dsl::int(2), dsl::str("b")]));
+
+        let names: Vec<_> = tree
+            .range(vec![dsl::int(2)]..)
+            .filter_map(|x| x.get(&"name".into()))
+            .collect();
+        assert_eq!(names, vec![dsl::str("y"), dsl::str("c")]);
+    }
+
+    #[test]
+    fn composite() {
+        let data = vec![
+            dsl::int(2),
+            dsl::int(1),
+            dsl::str("c"),
+            dsl::int(1),
+            dsl::int(2),
+            dsl::str("b"),
+            dsl::int(1),
+            dsl::int(1),
+            dsl::str("a"),
+        ];
+        let tree = Tree::from_rows(lines(), data).unwrap();
+        assert_eq!(
+            tree.to_string(),
+            "Tree[|pk invoice:Int, pk line:Int, code:Str; 1, 1, \"a\"; 1, 2, \"b\"; 2, 1, \"c\"|]"
+        );
+
+        let row = tree.get(&[dsl::int(1), dsl::int(2)]).unwrap();
+        assert_eq!(row.get(&"code".into()), Some(dsl::str("b")));
+
+        // The prefix of the key scan all the lines of the invoice
+        let codes: Vec<_> = tree
+            .range(vec![dsl::int(1)]..vec![dsl::int(2)])
+            .filter_map(|x| x.get(&"code".into()))
+            .collect();
+        assert_eq!(codes, vec![dsl::str("a"), dsl::str("b")]);
+    }
+
+    #[test]
+    fn unique() {
+        let mut tree = Tree::new(lines().with_unique(&[2]));
+        tree.insert(vec![dsl::int(1), dsl::int(1), dsl::str("a")])
+            .unwrap();
+        // Replace the row of the same key is not a violation
+        tree.insert(vec![dsl::int(1), dsl::int(1), dsl::str("a")])
+            .unwrap();
+
+        let err = tree
+            .insert(vec![dsl::int(1), dsl::int(2), dsl::str("a")])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The unique key is duplicated: code = \"a\""
+        );
+        match err {
+            ErrorCore::Lang(err) => {
+                assert_eq!(err.kind(), &ErrorKind::Duplicated);
+                assert_eq!(err.context()[0].key(), "code");
+                assert_eq!(err.context()[0].value(), "\"a\"");
+            }
+            err => panic!("Unexpected error {err:?}"),
+        }
+
+        // After replace the row, the old value is free
+        tree.insert(vec![dsl::int(1), dsl::int(1), dsl::str("z")])
+            .unwrap();
+        tree.insert(vec![dsl::int(1), dsl::int(2), dsl::str("a")])
+            .unwrap();
+        assert_eq!(tree.len(), 2);
+    }
+}
@@ -1,4 +1,5 @@
-use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use std::fmt;
 use std::hash::Hash;
 
 use crate::prelude::DateT;
@@ -17,25 +18,76 @@ impl<T: Clone + PartialEq + PartialOrd + Eq + Ord + Hash> Value for T {}
 //NOTE: This defines a total order, so it matter what is the order of the enum!
 //Must match Scalar
 //The overall sorting order is defined as:
+//  Unit < Bit < Bool < Int < Float < Dec < Money < Date < Interval < Char < Str < Sum < Top
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum DataType {
     //The BOTTOM type
     Unit,
-    Bool,
     Bit,
+    Bool,
     // Numeric
     I64,
-    Decimal,
     F64,
+    Decimal,
+    Money,
     // Dates
     Date(DateKind),
+    Interval,
     // Text
+    Char,
     Utf8,
+    /// A `enum`, by its name
+    Sum(String),
     //The TOP type
     //For List, dynamic
     Any,
 }
 
+/// Display the type as is written in the language
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataType::Unit => write!(f, "Unit"),
+            DataType::Bool => write!(f, "Bool"),
+            DataType::Bit => write!(f, "Bit"),
+            DataType::I64 => write!(f, "Int"),
+            DataType::Decimal => write!(f, "Dec"),
+            DataType::F64 => write!(f, "Float"),
+            DataType::Money => write!(f, "Money"),
+            DataType::Date(kind) => write!(f, "{:?}", kind),
+            DataType::Interval => write!(f, "Interval"),
+            DataType::Char => write!(f, "Char"),
+            DataType::Utf8 => write!(f, "Str"),
+            DataType::Sum(name) => write!(f, "{}", name),
+            DataType::Any => write!(f, "Any"),
+        }
+    }
+}
+
+impl DataType {
+    /// Find the type by the name used in the language, like `Int` or `Str`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let kind = match name {
+            "Unit" => DataType::Unit,
+            "Bool" => DataType::Bool,
+            "Bit" => DataType::Bit,
+            "Int" => DataType::I64,
+            "Dec" => DataType::Decimal,
+            "Float" => DataType::F64,
+            "Money" => DataType::Money,
+            "Time" => DataType::Date(DateKind::Time),
+            "Date" => DataType::Date(DateKind::Date),
+            "DateTime" => DataType::Date(DateKind::DateTime),
+            "Interval" => DataType::Interval,
+            "Char" => DataType::Char,
+            "Str" => DataType::Utf8,
+            "Any" => DataType::Any,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
 pub trait NativeKind {
     fn kind() -> DataType;
     fn num_rows() -> usize;
@@ -49,29 +101,213 @@ pub enum Arity {
 }
 
 pub const DATE_FMT: &str = "%Y-%m-%d";
-pub const TIME_FMT: &str = "%H:%M:%S";
-pub const DATE_TIME_FMT: &str = "%Y-%m-%d %H:%M:%S %z";
+/// The `%.f` show the fractional seconds only if they are not zero
+pub const TIME_FMT: &str = "%H:%M:%S%.f";
+pub const DATE_TIME_FMT: &str = "%Y-%m-%d %H:%M:%S%.f %z";
+const TIME_SHORT_FMT: &str = "%H:%M";
 
-fn to_date(of: NaiveDateTime) -> chrono::DateTime<FixedOffset> {
+/// The offsets that can be written by name, like `dt'2000-01-01 10:00 PST'`.
+///
+/// They are fixed, so `EST` is always `-05:00` even in summer.
+const NAMED_OFFSETS: [(&str, i32); 17] = [
+    ("Z", 0),
+    ("UTC", 0),
+    ("GMT", 0),
+    ("WET", 0),
+    ("CET", 1),
+    ("CEST", 2),
+    ("EET", 2),
+    ("EEST", 3),
+    ("JST", 9),
+    ("EST", -5),
+    ("EDT", -4),
+    ("CST", -6),
+    ("CDT", -5),
+    ("MST", -7),
+    ("MDT", -6),
+    ("PST", -8),
+    ("PDT", -7),
+];
+
+pub fn utc() -> FixedOffset {
+    FixedOffset::east(0)
+}
+
+pub(crate) fn to_date(of: NaiveDateTime) -> chrono::DateTime<FixedOffset> {
     let of = chrono::DateTime::<Utc>::from_utc(of, Utc);
     DateTime::from(of)
 }
 
-pub fn parse_date_t(of: &str) -> Result<DateT, chrono::ParseError> {
-    let of = NaiveDate::parse_from_str(of, DATE_FMT)?.and_hms(0, 0, 0);
+/// Parse a offset like `+05:00`, `-0800`, `+05`, `Z` or a name like `UTC` & `PST`
+pub fn parse_offset(of: &str) -> Option<FixedOffset> {
+    let of = of.trim();
+    if let Some((_, hours)) = NAMED_OFFSETS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(of))
+    {
+        return FixedOffset::east_opt(hours * 3_600);
+    }
+
+    let (sign, of) = match of.split_at(of.len().min(1)) {
+        ("+", of) => (1, of),
+        ("-", of) => (-1, of),
+        _ => return None,
+    };
+    let of = of.replace(':', "");
+    if !of.chars().all(|x| x.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match of.len() {
+        2 => (of.parse::<i32>().ok()?, 0),
+        4 => (of[..2].parse::<i32>().ok()?, of[2..].parse::<i32>().ok()?),
+        _ => return None,
+    };
+    if minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3_600 + minutes * 60))
+}
+
+/// Split the offset at the end of a date time, if any
+fn split_offset(of: &str) -> Result<(&str, Option<FixedOffset>), String> {
+    let invalid =
+        |x: &str| format!("Invalid offset `{x}`, expected one like `+05:00`, `Z` or `UTC`");
+
+    if let Some(x) = of.strip_suffix(['Z', 'z']) {
+        return Ok((x, Some(utc())));
+    }
+    // Named, like `UTC`
+    if let Some((x, name)) = of.rsplit_once(' ') {
+        if name.chars().all(|x| x.is_ascii_alphabetic()) {
+            return parse_offset(name)
+                .map(|offset| (x, Some(offset)))
+                .ok_or_else(|| invalid(name));
+        }
+    }
+    // Numeric, after the time so not confuse it with the `-` of the date
+    let time = match of.find(':') {
+        Some(pos) => pos,
+        None => return Ok((of, None)),
+    };
+    match of[time..].rfind(['+', '-']) {
+        Some(pos) => {
+            let (x, offset) = of.split_at(time + pos);
+            parse_offset(offset)
+                .map(|offset| (x, Some(offset)))
+                .ok_or_else(|| invalid(offset))
+        }
+        None => Ok((of, None)),
+    }
+}
+
+fn parse_naive_time(of: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(of, TIME_FMT)
+        .or_else(|_| NaiveTime::parse_from_str(of, TIME_SHORT_FMT))
+        .map_err(|x| x.to_string())
+}
+
+/// Parse a date like `2000-01-02`, at the `offset`
+pub fn parse_date_at(of: &str, offset: &FixedOffset) -> Result<DateT, String> {
+    let of = NaiveDate::parse_from_str(of.trim(), DATE_FMT)
+        .map_err(|x| x.to_string())?
+        .and_hms(0, 0, 0);
+    let of = offset
+        .from_local_datetime(&of)
+        .single()
+        .ok_or_else(|| "The date is out of range".to_string())?;
 
-    Ok(DateT::date(to_date(of)))
+    Ok(DateT::date(of))
 }
 
-pub fn parse_time_t(of: &str) -> Result<DateT, chrono::ParseError> {
-    let d = NaiveTime::parse_from_str(of, TIME_FMT)?;
+pub fn parse_date_t(of: &str) -> Result<DateT, String> {
+    parse_date_at(of, &utc())
+}
+
+/// Parse a time like `10:11:12`, `10:11:12.5` or `10:11`. The time has no day, so is always UTC
+pub fn parse_time_t(of: &str) -> Result<DateT, String> {
+    let d = parse_naive_time(of.trim())?;
     let d = chrono::naive::NaiveDate::MIN.and_time(d);
 
     Ok(DateT::time(to_date(d)))
 }
 
-pub fn parse_date_time_t(of: &str) -> Result<DateT, chrono::ParseError> {
-    let d = DateTime::parse_from_str(of, DATE_TIME_FMT)?;
+/// Parse a ISO-8601/RFC-3339 date time like `2000-01-02T10:11:12.5+05:00`.
+///
+/// The time can be separated by `T` or a space, and the offset can be numeric, `Z` or named
+/// like `UTC`. Without offset is at `offset`, and without time is at midnight.
+pub fn parse_date_time_at(of: &str, offset: &FixedOffset) -> Result<DateT, String> {
+    let (of, found) = split_offset(of.trim())?;
+    let of = of.trim_end();
+    let (date, time) = match of.find(['T', 't', ' ']) {
+        Some(pos) => (&of[..pos], parse_naive_time(of[pos + 1..].trim_start())?),
+        None => (of, NaiveTime::from_hms(0, 0, 0)),
+    };
+    let date = NaiveDate::parse_from_str(date, DATE_FMT).map_err(|x| x.to_string())?;
+    let of = found
+        .unwrap_or(*offset)
+        .from_local_datetime(&date.and_time(time))
+        .single()
+        .ok_or_else(|| "The date is out of range".to_string())?;
+
+    Ok(DateT::datetime(of))
+}
+
+pub fn parse_date_time_t(of: &str) -> Result<DateT, String> {
+    parse_date_time_at(of, &utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(of: &str) -> String {
+        match parse_date_time_t(of) {
+            Ok(x) => x.fmt(),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn date_times() {
+        assert_eq!(
+            check("2000-01-02 10:11:12 +0000"),
+            "2000-01-02 10:11:12 +0000"
+        );
+        assert_eq!(check("2000-01-02T10:11:12Z"), "2000-01-02 10:11:12 +0000");
+        assert_eq!(
+            check("2000-01-02t10:11:12.25+05:30"),
+            "2000-01-02 10:11:12.250 +0530"
+        );
+        assert_eq!(check("2000-01-02T10:11-08"), "2000-01-02 10:11:00 -0800");
+        assert_eq!(
+            check("2000-01-02 10:11:12 PST"),
+            "2000-01-02 10:11:12 -0800"
+        );
+        assert_eq!(check("2000-01-02T10:11:12"), "2000-01-02 10:11:12 +0000");
+        assert_eq!(check("2000-01-02"), "2000-01-02 00:00:00 +0000");
+
+        let at = parse_date_time_at("2000-01-02 10:11", &parse_offset("-03:00").unwrap());
+        assert_eq!(at.unwrap().fmt(), "2000-01-02 10:11:00 -0300");
+
+        assert_eq!(
+            check("2000-01-02 10:11:12 Mars"),
+            "Invalid offset `Mars`, expected one like `+05:00`, `Z` or `UTC`"
+        );
+        assert_eq!(
+            check("2000-01-02 10:11:12+25"),
+            "Invalid offset `+25`, expected one like `+05:00`, `Z` or `UTC`"
+        );
+        assert_eq!(check("2000-01-32"), "input is out of range");
+    }
 
-    Ok(DateT::datetime(d))
+    #[test]
+    fn offsets() {
+        assert_eq!(parse_offset("+05:30"), FixedOffset::east_opt(19_800));
+        assert_eq!(parse_offset("-0800"), FixedOffset::west_opt(28_800));
+        assert_eq!(parse_offset("utc"), Some(utc()));
+        assert_eq!(parse_offset("05:00"), None);
+        assert_eq!(parse_offset("+5"), None);
+        assert_eq!(parse_time_t("10:11:12.5").unwrap().fmt(), "10:11:12.500");
+        assert_eq!(parse_time_t("10:11").unwrap().fmt(), "10:11:00");
+    }
 }
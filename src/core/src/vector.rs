@@ -7,6 +7,8 @@
 //!
 //! It also is the encoding of `structs`-like declarations
 //!
+use std::fmt;
+
 use crate::prelude::*;
 use crate::scalar::ScalarSlice;
 
@@ -49,11 +51,13 @@ impl std::fmt::Display for VecPos {
 //Must match DataType/Scalar
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Array {
+    /// A packed column of bits, one by row
+    Bit(BitVec),
     Bool(Vec<bool>),
     //Numeric
     I64(Vec<i64>),
-    Decimal(Vec<Decimal>),
     F64(Vec<F64>),
+    Decimal(Vec<Decimal>),
     //Date
     Date(DateKind, Vec<DateT>),
     //Strings
@@ -62,9 +66,59 @@ pub enum Array {
     Scalar(Vec<Scalar>),
 }
 
+/// Collect the values of `data` if all are of the `Scalar::$of` variant
+macro_rules! collect_kind {
+    ($data:expr, $of:ident) => {
+        $data
+            .iter()
+            .map(|x| match x {
+                Scalar::$of([x]) => Some(x.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+    };
+}
+
 impl Array {
+    /// Build the array for the values of `kind`, falling back to [Array::Scalar] if
+    /// `kind` is not specialized or the values are of mixed types
+    pub fn from_scalars(kind: &DataType, data: Vec<Scalar>) -> Self {
+        let array = match kind {
+            DataType::Bit => data
+                .iter()
+                .map(|x| match x {
+                    Scalar::Bit(x) if x.len() == 1 => Some(x[0]),
+                    _ => None,
+                })
+                .collect::<Option<BitVec>>()
+                .map(Array::Bit),
+            DataType::Bool => collect_kind!(data, Bool).map(Array::Bool),
+            DataType::I64 => collect_kind!(data, I64).map(Array::I64),
+            DataType::Decimal => collect_kind!(data, Decimal).map(Array::Decimal),
+            DataType::F64 => collect_kind!(data, F64).map(Array::F64),
+            DataType::Date(kind) => collect_kind!(data, Date).map(|x| Array::Date(*kind, x)),
+            DataType::Utf8 => collect_kind!(data, Utf8).map(Array::Utf8),
+            _ => None,
+        };
+        array.unwrap_or(Array::Scalar(data))
+    }
+
+    pub fn len(&self) -> usize {
+        self.slice().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get a copy of the value at `pos`
+    pub fn get(&self, pos: usize) -> Option<Scalar> {
+        self.slice().get(pos)
+    }
+
     pub fn slice(&self) -> ScalarSlice<'_> {
         match &self {
+            Array::Bit(x) => ScalarSlice::Bit(x),
             Array::Bool(x) => ScalarSlice::Bool(x),
             Array::I64(x) => ScalarSlice::I64(x),
             Array::Decimal(x) => ScalarSlice::Decimal(x),
@@ -86,12 +140,41 @@ pub struct Vector {
 }
 
 impl Vector {
-    pub fn new(schema: Schema, rows: usize, data: Array) -> Self {
-        Self { rows, data, schema }
+    /// Fail if the rows repeat the values of a key of the `schema`
+    pub fn new(schema: Schema, rows: usize, data: Array) -> ResultT<Self> {
+        let vector = Self { rows, data, schema };
+        vector
+            .schema
+            .check_keys((0..rows).map(|x| vector.row(x).to_row().data))?;
+        Ok(vector)
     }
 
-    pub fn row(&self, row: usize) -> ScalarSlice<'_> {
-        self.data.slice().to_row(row, self.schema.len())
+    pub fn row(&self, row: usize) -> RowSlice<'_> {
+        let cols = self.schema.len();
+        let data = self.data.slice().to_row(row, cols);
+        let data = (0..cols).map(|x| data.range(x..x + 1)).collect();
+        RowSlice::new(&self.schema, data)
+    }
+}
+
+/// Display the vector as is written in the language, like `[it:Int; 1; 2]`
+impl fmt::Display for Vector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cols = self.schema.len();
+        write!(f, "[{}", self.schema)?;
+        for row in 0..self.rows {
+            write!(f, "; ")?;
+            for col in 0..cols {
+                if col > 0 {
+                    write!(f, ", ")?;
+                }
+                match self.data.get(row * cols + col) {
+                    Some(x) => write!(f, "{}", x)?,
+                    None => write!(f, "?")?,
+                }
+            }
+        }
+        write!(f, "]")
     }
 }
 
@@ -103,6 +186,14 @@ impl Rel for Vector {
     fn schema(&self) -> Schema {
         self.schema.clone()
     }
+
+    fn len(&self) -> usize {
+        self.rows
+    }
+
+    fn rows(&self) -> RowsIter<'_> {
+        Box::new((0..self.rows).map(|x| self.row(x).to_row().data))
+    }
 }
 
 #[cfg(test)]
@@ -116,4 +207,32 @@ mod tests {
 
         dbg!(nums);
     }
+
+    #[test]
+    fn rows() {
+        let schema = Schema::new(
+            None,
+            &[
+                Field::new("a", DataType::I64),
+                Field::new("b", DataType::I64),
+            ],
+        );
+        let nums = Vector::new(schema, 3, Array::I64(vec![1, 2, 3, 4, 5, 6])).unwrap();
+        assert_eq!(nums.data.slice().to_row(2, 2), ScalarSlice::I64(&[5, 6]));
+
+        let row = nums.row(1);
+        assert_eq!(row.get(&"a".into()), Some(dsl::int(3)));
+        assert_eq!(row.get(&1.into()), Some(dsl::int(4)));
+    }
+
+    #[test]
+    fn unique() {
+        let schema = Schema::new_single("code", DataType::Utf8).with_unique(&[0]);
+        let codes = Array::Utf8(vec!["a".into(), "b".into(), "a".into()]);
+        let err = Vector::new(schema, 3, codes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The unique key is duplicated: code = \"a\""
+        );
+    }
 }
@@ -1,7 +1,10 @@
 use crate::env::Env;
 use crate::errors::ErrorCode;
-use corelib::prelude::{Scalar, Span};
+use corelib::algebraic::Case;
+use corelib::prelude::{Column, Rel, Scalar, Schema, Span, Table, Vector};
+use corelib::tree;
 use corelib::tree_flat::prelude::Tree;
+use parser::ast::{BinaryOp, CmpOp, LogicOp, Order, Pattern, QueryOp, UnaryOp};
 use std::fmt;
 
 pub type CodeEx = Box<dyn FnMut(&Env) -> Code>;
@@ -12,12 +15,179 @@ pub type CodeId = usize;
 #[derive(Debug, Clone)]
 pub enum Code {
     Root,
-    Scalar { val: Scalar, span: Span },
-    If { code: CodeId, span: Span },
-    Halt { error: ErrorCode, span: Span },
+    Scalar {
+        val: Scalar,
+        span: Span,
+    },
+    Vector {
+        val: Box<Vector>,
+        span: Span,
+    },
+    Table {
+        val: Box<Table>,
+        span: Span,
+    },
+    Tree {
+        val: Box<tree::Tree>,
+        span: Span,
+    },
+    /// Build a [Vector], or a [Table] if it has many columns. The children are
+    /// the cells in *row-major* order.
+    ///
+    /// Without `schema`, is a single column of the type of the first cell
+    NewVector {
+        schema: Option<Schema>,
+        span: Span,
+    },
+    /// Build a [tree::Tree], the children are the cells in *row-major* order
+    NewTree {
+        schema: Schema,
+        span: Span,
+    },
+    /// The children are the `check`, `if_true` & `if_false` branches, in that order
+    If {
+        span: Span,
+    },
+    /// The children are the value, then the [Code::Arm] of each case in order
+    Match {
+        span: Span,
+    },
+    /// A case of a [Code::Match], the child is the body to evaluate if the `pattern` match
+    Arm {
+        pattern: Box<Pattern>,
+        span: Span,
+    },
+    /// The children are the `lhs` & `rhs` operands, in that order
+    BinOp {
+        op: BinaryOp,
+        span: Span,
+    },
+    /// The child is the operand
+    UnaryOp {
+        op: UnaryOp,
+        span: Span,
+    },
+    /// The children are the `lhs` & `rhs` operands, in that order
+    Cmp {
+        op: CmpOp,
+        span: Span,
+    },
+    /// The children are the `lhs` & `rhs` operands, in that order
+    Logic {
+        op: LogicOp,
+        span: Span,
+    },
+    /// Lookup the value of the column in the row of the enclosing query
+    Column {
+        col: Column,
+        span: Span,
+    },
+    /// A column of `?sort` with its order
+    Order {
+        col: Column,
+        order: Order,
+        span: Span,
+    },
+    /// The children are the relation & the arguments of the operator, in that order
+    Query {
+        op: QueryOp,
+        span: Span,
+    },
+    /// Call the builtin function `name`, the children are the arguments
+    Call {
+        name: String,
+        span: Span,
+    },
+    /// Lookup the value of the variable
+    Ident {
+        name: String,
+        span: Span,
+    },
+    /// Declare a variable, the child is the value
+    Define {
+        name: String,
+        mutable: bool,
+        span: Span,
+    },
+    /// Change the value of a variable, the child is the value
+    Assign {
+        name: String,
+        span: Span,
+    },
+    /// Declare a sum type, with the cases of the `parent` first
+    DefineEnum {
+        name: String,
+        params: Vec<String>,
+        parent: Option<String>,
+        cases: Vec<Case>,
+        span: Span,
+    },
+    /// Evaluate the children in a new scope, return the last value
+    Block {
+        span: Span,
+    },
+    /// The result of code that not return a value, like a declaration
+    Pass,
+    Halt {
+        error: ErrorCode,
+        span: Span,
+    },
     Eof,
 }
 
+impl Code {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Code::Root | Code::Pass | Code::Eof => None,
+            Code::Scalar { span, .. } => Some(*span),
+            Code::Vector { span, .. } => Some(*span),
+            Code::Table { span, .. } => Some(*span),
+            Code::Tree { span, .. } => Some(*span),
+            Code::NewVector { span, .. } => Some(*span),
+            Code::NewTree { span, .. } => Some(*span),
+            Code::If { span } => Some(*span),
+            Code::Match { span } => Some(*span),
+            Code::Arm { span, .. } => Some(*span),
+            Code::BinOp { span, .. } => Some(*span),
+            Code::UnaryOp { span, .. } => Some(*span),
+            Code::Cmp { span, .. } => Some(*span),
+            Code::Logic { span, .. } => Some(*span),
+            Code::Column { span, .. } => Some(*span),
+            Code::Order { span, .. } => Some(*span),
+            Code::Query { span, .. } => Some(*span),
+            Code::Call { span, .. } => Some(*span),
+            Code::Ident { span, .. } => Some(*span),
+            Code::Define { span, .. } => Some(*span),
+            Code::Assign { span, .. } => Some(*span),
+            Code::DefineEnum { span, .. } => Some(*span),
+            Code::Block { span } => Some(*span),
+            Code::Halt { span, .. } => Some(*span),
+        }
+    }
+
+    /// View the value as a relation, if it is one
+    pub fn as_rel(&self) -> Option<&dyn Rel> {
+        match self {
+            Code::Scalar { val, .. } => Some(val),
+            Code::Vector { val, .. } => Some(val.as_ref()),
+            Code::Table { val, .. } => Some(val.as_ref()),
+            Code::Tree { val, .. } => Some(val.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Relocate a value to the `span` where is used, like when read from a variable
+    pub fn with_span(self, span: Span) -> Self {
+        match self {
+            Code::Scalar { val, .. } => Code::Scalar { val, span },
+            Code::Vector { val, .. } => Code::Vector { val, span },
+            Code::Table { val, .. } => Code::Table { val, span },
+            Code::Tree { val, .. } => Code::Tree { val, span },
+            x => x,
+        }
+    }
+}
+
 pub struct CodePrinter<'a> {
     pub(crate) parsed: &'a Tree<Code>,
 }
@@ -43,9 +213,77 @@ fn fmt_node(node: &Code, level: usize, f: &mut fmt::Formatter<'_>) -> fmt::Resul
     match node {
         Code::Root => write!(f, "Root")?,
         Code::Scalar { val, .. } => fmt_t(f, level, &val)?,
-        Code::If { code: _, span } => {
+        Code::Vector { val, .. } => fmt_t(f, level, &val)?,
+        Code::Table { val, .. } => fmt_t(f, level, &val)?,
+        Code::Tree { val, .. } => fmt_t(f, level, &val)?,
+        Code::NewVector { schema, span } => {
+            let header = match schema {
+                Some(schema) => format!("[{schema}]"),
+                None => "[?]".to_string(),
+            };
+            fmt_plain(f, level, &header, span)?;
+        }
+        Code::NewTree { schema, span } => {
+            fmt_plain(f, level, &format!("Tree[|{schema}|]"), span)?;
+        }
+        Code::If { span } => {
             fmt_plain(f, level, &"if", span)?;
         }
+        Code::Match { span } => {
+            fmt_plain(f, level, &"match", span)?;
+        }
+        Code::Arm { pattern, span } => {
+            let case = match pattern.as_ref() {
+                Pattern::Any { name: None, .. } => "else".to_string(),
+                pattern => format!("case {pattern}"),
+            };
+            fmt_plain(f, level, &case, span)?;
+        }
+        Code::BinOp { op, span } => {
+            fmt_plain(f, level, op, span)?;
+        }
+        Code::UnaryOp { op, span } => {
+            fmt_plain(f, level, op, span)?;
+        }
+        Code::Cmp { op, span } => {
+            fmt_plain(f, level, op, span)?;
+        }
+        Code::Logic { op, span } => {
+            fmt_plain(f, level, op, span)?;
+        }
+        Code::Column { col, span } => {
+            fmt_plain(f, level, &col.to_string(), span)?;
+        }
+        Code::Order { col, order, span } => {
+            fmt_plain(f, level, &format!("{col} {order}"), span)?;
+        }
+        Code::Query { op, span } => {
+            fmt_plain(f, level, &op.symbol(), span)?;
+        }
+        Code::Call { name, span } => {
+            fmt_plain(f, level, &format!("{name}()"), span)?;
+        }
+        Code::Ident { name, span } => {
+            fmt_plain(f, level, name, span)?;
+        }
+        Code::Define {
+            name,
+            mutable,
+            span,
+        } => {
+            let kw = if *mutable { "var" } else { "let" };
+            fmt_plain(f, level, &format!("{kw} {name}"), span)?;
+        }
+        Code::Assign { name, span } => {
+            fmt_plain(f, level, &format!("{name} :="), span)?;
+        }
+        Code::DefineEnum { name, span, .. } => {
+            fmt_plain(f, level, &format!("enum {name}"), span)?;
+        }
+        Code::Block { span } => {
+            fmt_plain(f, level, &"do", span)?;
+        }
+        Code::Pass => write!(f, "Pass")?,
         Code::Halt { error, span } => {
             fmt_plain(f, level, &format!("{:?}", error), span)?;
         }
@@ -1,5 +1,5 @@
 use ariadne::{sources, Color, ColorGenerator, Config, Label, Report, ReportKind};
-use corelib::errors::Span;
+use corelib::errors::{ErrorCore, Span};
 use std::io;
 use std::ops::Range;
 
@@ -45,36 +45,99 @@ pub fn build_report(
             .with_label(build_label(span, named, primary, msg, with_color))
             .with_note(format!("Parsing value of type: {kind:?}"))
             .finish(),
-        _ => {
-            todo!()
+        ErrorParser::BoolExpr { span, found } => {
+            let msg = format!("Expected a boolean expression, found `{found}`");
+            diagnostic
+                .with_message(&msg)
+                .with_label(build_label(span, named, primary, &msg, with_color))
+                .finish()
         }
+        ErrorParser::NoExpr { span, found } => {
+            let msg = if found.is_empty() {
+                "Expected an expression".to_string()
+            } else {
+                format!("Expected an expression, found `{found}`")
+            };
+            diagnostic
+                .with_message(&msg)
+                .with_label(build_label(span, named, primary, &msg, with_color))
+                .finish()
+        }
+        ErrorParser::Incomplete { missing, .. } => {
+            let msg = "Incomplete expression";
+            diagnostic
+                .with_message(msg)
+                .with_label(build_label(err.span(), named, primary, msg, with_color))
+                .with_note(format!("Missing: {missing:?}"))
+                .finish()
+        }
+        ErrorParser::VectorShape {
+            span,
+            row,
+            expected,
+            found,
+        } => {
+            let msg = format!("The row {row} has {found} values, expected {expected}");
+            diagnostic
+                .with_message(&msg)
+                .with_label(build_label(span, named, primary, &msg, with_color))
+                .finish()
+        }
+        ErrorParser::VectorHeader { span, msg }
+        | ErrorParser::Query { span, msg }
+        | ErrorParser::Call { span, msg }
+        | ErrorParser::Enum { span, msg }
+        | ErrorParser::Match { span, msg } => diagnostic
+            .with_message(msg)
+            .with_label(build_label(span, named, primary, msg, with_color))
+            .finish(),
     }
 }
 
-pub fn print_diagnostic_to_str(src: &FilesDb, err: &ErrorCode) -> io::Result<String> {
-    let name = src.get_root().name();
+pub fn build_report_core(
+    named: String,
+    err: &ErrorCore,
+    span: &Span,
+    with_color: bool,
+) -> Report<(String, Range<usize>)> {
+    let mut colors = ColorGenerator::new();
+
+    let primary = colors.next();
+    let code = err.error_code();
+    let config = Config::default().with_color(with_color);
+    let msg = err.to_string();
+
+    Report::build(ReportKind::Error, named.clone(), 0)
+        .with_code(code as usize)
+        .with_config(config)
+        .with_message(&msg)
+        .with_label(build_label(span, named, primary, &msg, with_color))
+        .finish()
+}
+
+fn report(named: String, err: &ErrorCode, with_color: bool) -> Report<(String, Range<usize>)> {
     match err {
-        ErrorCode::Parser { error } => {
-            let err = build_report(name, error, false);
-            let mut c = Vec::new();
-            err.write(
-                sources(src.files().map(|x| (x.data.name(), x.data.source()))),
-                &mut c,
-            )?;
-            let s = String::from_utf8(c).unwrap();
-            Ok(s)
-        }
+        ErrorCode::Parser { error } => build_report(named, error, with_color),
+        ErrorCode::Core { error, span } => build_report_core(named, error, span, with_color),
     }
 }
 
+pub fn print_diagnostic_to_str(src: &FilesDb, err: &ErrorCode) -> io::Result<String> {
+    let name = src.get_root().name();
+    let err = report(name, err, false);
+    let mut c = Vec::new();
+    err.write(
+        sources(src.files().map(|x| (x.data.name(), x.data.source()))),
+        &mut c,
+    )?;
+    let s = String::from_utf8(c).unwrap();
+    Ok(s)
+}
+
 pub fn print_diagnostic(src: &FilesDb, err: &ErrorCode) -> io::Result<()> {
     let name = src.get_root().name();
-    match err {
-        ErrorCode::Parser { error } => {
-            let err = build_report(name, error, true);
-            err.print(sources(
-                src.files().map(|x| (x.data.name(), x.data.source())),
-            ))
-        }
-    }
+    let err = report(name, err, true);
+    err.print(sources(
+        src.files().map(|x| (x.data.name(), x.data.source())),
+    ))
 }
@@ -1,18 +1,151 @@
+use std::collections::HashMap;
+
+use corelib::algebraic::{self, SumType};
+use corelib::errors::{ErrorCore, ErrorKind, ErrorLang, ResultT};
+use corelib::prelude::{Column, Row, Scalar};
+
+use crate::code::Code;
 use crate::function::FunVm;
 
+/// A variable declared with `let` (immutable) or `var` (mutable)
+#[derive(Debug, Clone)]
+struct Var {
+    value: Code,
+    mutable: bool,
+}
+
+/// The variables & types declared in a `do ... end` block
+#[derive(Debug, Default)]
+struct Scope {
+    vars: HashMap<String, Var>,
+    /// In the order of declaration, so the last one shadow the cases with the same name
+    enums: Vec<SumType>,
+}
+
+/// The environment of the program: The functions and the stack of lexical scopes,
+/// the first is the global scope and the last the current one
 #[derive(Debug)]
 pub struct Env {
+    #[allow(dead_code)]
     pub(crate) fun: Vec<FunVm>,
-    parent: Vec<Env>,
+    scopes: Vec<Scope>,
+    /// The row of the relation in a query like `?where`, where the columns are read
+    row: Option<Row>,
 }
 
 impl Env {
     pub fn new() -> Self {
         Self {
             fun: vec![],
-            parent: vec![],
+            scopes: vec![Scope::default()],
+            row: None,
         }
     }
+
+    pub(crate) fn push_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    pub(crate) fn pop_scope(&mut self) {
+        // The global scope is never removed
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    /// Declare the variable in the current scope, shadowing any previous one with the same name
+    pub(crate) fn define(&mut self, name: &str, value: Code, mutable: bool) {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("Always exist the global scope");
+        scope.vars.insert(name.into(), Var { value, mutable });
+    }
+
+    /// Declare the sum type in the current scope
+    pub(crate) fn define_enum(&mut self, of: SumType) {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("Always exist the global scope");
+        scope.enums.push(of);
+    }
+
+    pub(crate) fn enums(&self) -> impl Iterator<Item = &SumType> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.enums.iter().rev())
+    }
+
+    /// Find the sum type by its name
+    pub(crate) fn find_enum(&self, name: &str) -> ResultT<&SumType> {
+        self.enums().find(|x| x.name == name).ok_or_else(|| {
+            let msg = format!("The type `{name}` is not defined");
+            ErrorCore::Lang(Box::new(ErrorLang::new(ErrorKind::NotFound, Some(&msg))))
+        })
+    }
+
+    /// Find the sum type that has the case `name`, the last declared if many have it
+    pub(crate) fn find_case(&self, name: &str) -> Option<&SumType> {
+        self.enums().find(|x| x.case(name).is_some())
+    }
+
+    fn find_mut(&mut self, name: &str) -> Option<&mut Var> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.vars.get_mut(name))
+    }
+
+    /// Change the value of a variable declared with `var`
+    pub(crate) fn assign(&mut self, name: &str, value: Code) -> ResultT<()> {
+        let var = self
+            .find_mut(name)
+            .ok_or_else(|| ErrorCore::VarNotFound { name: name.into() })?;
+        if !var.mutable {
+            return Err(ErrorCore::VarImmutable { name: name.into() });
+        }
+        // The type of a variable never change
+        if let (Code::Scalar { val: old, .. }, Code::Scalar { val: new, .. }) = (&var.value, &value)
+        {
+            if old.kind() != new.kind() {
+                return Err(ErrorCore::TypeMismatch {
+                    expected: old.kind(),
+                    get: new.kind(),
+                });
+            }
+            algebraic::check_params(old, new)?;
+        }
+        var.value = value;
+        Ok(())
+    }
+
+    /// Set the row for the columns, return the previous one so a nested query can restore it
+    pub(crate) fn set_row(&mut self, row: Option<Row>) -> Option<Row> {
+        std::mem::replace(&mut self.row, row)
+    }
+
+    /// Find the value of the column in the current row
+    pub(crate) fn column(&self, col: &Column) -> ResultT<Scalar> {
+        self.row
+            .as_ref()
+            .and_then(|row| row.get(col))
+            .cloned()
+            .ok_or_else(|| ErrorCore::FieldNotFound {
+                column: col.clone(),
+            })
+    }
+
+    /// Find the value of the variable, starting from the current scope to the global one
+    pub(crate) fn get(&self, name: &str) -> ResultT<&Code> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.vars.get(name))
+            .map(|var| &var.value)
+            .ok_or_else(|| ErrorCore::VarNotFound { name: name.into() })
+    }
 }
 
 impl Default for Env {
@@ -1,15 +1,18 @@
+use corelib::errors::ErrorCore;
 use corelib::prelude::Span;
 use parser::errors::ErrorParser;
 
 #[derive(Debug, Clone)]
 pub enum ErrorCode {
     Parser { error: ErrorParser },
+    Core { error: ErrorCore, span: Span },
 }
 
 impl ErrorCode {
     pub fn span(&self) -> &Span {
         match self {
             ErrorCode::Parser { error } => error.span(),
+            ErrorCode::Core { span, .. } => span,
         }
     }
 }
@@ -25,11 +25,13 @@ impl Clone for Box<dyn FunVM> {
     }
 }
 
+#[allow(dead_code)]
 pub struct FunVm {
     pub(crate) idx: FunctionId,
     pub(crate) fun: Box<dyn FunVM>,
 }
 
+#[allow(dead_code)]
 impl FunVm {
     pub fn new(idx: FunctionId, fun: Box<dyn FunVM>) -> Self {
         Self { idx, fun }
@@ -1,10 +1,25 @@
 use crate::code::Code;
 use crate::env::Env;
 use crate::errors::ErrorCode;
-use corelib::tree_flat::prelude::Tree;
-use parser::ast::Ast;
+use corelib::aggregate::Aggregate;
+use corelib::algebraic::{self, Case, FieldKind, SumType};
+use corelib::bits::Shift;
+use corelib::cast::{self, Rounding};
+use corelib::chrono::FixedOffset;
+use corelib::dates::Part;
+use corelib::errors::{ErrorCore, ErrorCtx, ErrorKind, ErrorLang, ResultT};
+use corelib::join::{self, Named};
+use corelib::kernels::{self, Operand};
+use corelib::money::Currency;
+use corelib::prelude::{Array, Column, DataType, Rel, Row, Scalar, Schema, Span, Table, Vector};
+use corelib::text::{self, Side};
+use corelib::tree_flat::iter::ChildrenIter;
+use corelib::tree_flat::prelude::{Node, Tree, TreeMut};
+use corelib::{query, set, tree, types};
+use parser::ast::{Ast, BinaryOp, CmpOp, ExprBool, LogicOp, Order, Pattern, QueryOp, UnaryOp};
 use parser::files::{File, FilesDb};
 use parser::parser::{Parsed, Parser};
+use std::collections::HashMap;
 use std::io::Read;
 use std::path::PathBuf;
 use std::{fs, io};
@@ -14,6 +29,635 @@ pub struct Program {
     code: Tree<Code>,
     env: Env,
     pub files: FilesDb,
+    /// The offset of the session, for the dates written without one
+    offset: FixedOffset,
+}
+
+/// The direct children of a [Code] node
+fn children<'a>(
+    code: &'a Tree<Code>,
+    node: &Node<'_, Code>,
+) -> impl Iterator<Item = Node<'a, Code>> {
+    let level = node.level() + 1;
+    ChildrenIter::new(node.id, code).filter(move |x| x.level() == level)
+}
+
+/// The value of a operator, or the [Code::Halt] with the error at `span`
+fn to_code(result: ResultT<Operand>, span: Span) -> Code {
+    match result {
+        Ok(Operand::Scalar(val)) => Code::Scalar { val, span },
+        Ok(Operand::Vector(val)) => Code::Vector {
+            val: Box::new(val),
+            span,
+        },
+        Err(error) => Code::Halt {
+            error: ErrorCode::Core { error, span },
+            span,
+        },
+    }
+}
+
+fn call_error(kind: ErrorKind, msg: &str, name: &str) -> ErrorCore {
+    let err = ErrorLang::new(kind, Some(msg)).with_ctx(ErrorCtx::new("function", name));
+    ErrorCore::Lang(Box::new(err))
+}
+
+/// A relation of only one field as a [Vector], like the result of `?select #qty`
+fn to_vector(rel: &dyn Rel) -> Vector {
+    let schema = rel.schema();
+    let kind = schema.fields[0].kind.clone();
+    let data: Vec<_> = rel.rows().flatten().collect();
+    Vector::new(schema, data.len(), Array::from_scalars(&kind, data))
+        .expect("The rows of a relation not repeat its keys")
+}
+
+/// The operator `op` can't be applied to the `value`, like a [Table] in `t + 1`
+fn unsupported(op: &'static str, value: &Code) -> ErrorCore {
+    match value.as_rel() {
+        Some(rel) => ErrorCore::UnsupportedRel {
+            op,
+            rel: rel.type_name().into(),
+        },
+        None => ErrorCore::Unsupported {
+            op,
+            kind: DataType::Any,
+        },
+    }
+}
+
+fn match_error(kind: ErrorKind, msg: &str) -> ErrorCore {
+    ErrorCore::Lang(Box::new(ErrorLang::new(kind, Some(msg))))
+}
+
+/// If the `value` match the `pattern`, adding the variables it binds
+fn match_value(pattern: &Pattern, value: &Scalar, binds: &mut Vec<(String, Code)>) -> bool {
+    match pattern {
+        Pattern::Any { name, span } => {
+            if let Some(name) = name {
+                let val = value.clone();
+                binds.push((name.clone(), Code::Scalar { val, span: *span }));
+            }
+            true
+        }
+        Pattern::Scalar { val, .. } => val == value,
+        Pattern::Range { start, end, .. } => {
+            start.kind() == value.kind() && start <= value && value < end
+        }
+        Pattern::Case { name, fields, .. } => match value {
+            Scalar::Sum([x]) => {
+                x.case() == name
+                    && fields.len() == x.values().len()
+                    && fields
+                        .iter()
+                        .zip(x.values())
+                        .all(|(pattern, value)| match_value(pattern, value, binds))
+            }
+            _ => false,
+        },
+        Pattern::Row { .. } => false,
+    }
+}
+
+/// Check the fields of the row patterns of the `arms` are in the `schema`, else the case could
+/// never match
+fn check_row_fields(arms: &[Node<'_, Code>], schema: &Schema) -> Result<(), Code> {
+    for arm in arms {
+        let (fields, span) = match arm.data {
+            Code::Arm { pattern, .. } => match pattern.as_ref() {
+                Pattern::Row { fields, span } => (fields, span),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let missing = fields
+            .iter()
+            .find(|(name, _)| !schema.fields.iter().any(|x| &x.name == name));
+        if let Some((name, _)) = missing {
+            let msg = format!("The field `{name}` is not in the row `[{schema}]`");
+            let error = match_error(ErrorKind::NotFound, &msg);
+            return Err(Code::Halt {
+                error: ErrorCode::Core { error, span: *span },
+                span: *span,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// If the `row` match the `pattern`: a row pattern match its fields by name, the others the
+/// value of a row of only one field. A variable bind the whole row if it has many fields
+fn match_row(pattern: &Pattern, row: &Row, binds: &mut Vec<(String, Code)>) -> bool {
+    match pattern {
+        Pattern::Row { fields, .. } => {
+            fields.iter().all(
+                |(name, pattern)| match row.get(&Column::Name(name.clone())) {
+                    Some(value) => match_value(pattern, value, binds),
+                    None => false,
+                },
+            )
+        }
+        pattern if row.data.len() == 1 => match_value(pattern, &row.data[0], binds),
+        Pattern::Any { name, span } => {
+            if let Some(name) = name {
+                match Table::from_rows(row.schema.clone(), row.data.clone()) {
+                    Ok(val) => binds.push((
+                        name.clone(),
+                        Code::Table {
+                            val: Box::new(val),
+                            span: *span,
+                        },
+                    )),
+                    Err(_) => return false,
+                }
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// The functions of [text] & how are called
+const TEXT_FNS: [(&str, &str); 11] = [
+    ("len", "x.len()"),
+    ("slice", "x.slice(start, end)"),
+    ("upper", "x.upper()"),
+    ("lower", "x.lower()"),
+    ("trim", "x.trim()"),
+    ("trim_start", "x.trim_start()"),
+    ("trim_end", "x.trim_end()"),
+    ("find", "x.find('a')"),
+    ("replace", "x.replace('a', 'b')"),
+    ("pad_start", "x.pad_start(width, ' ')"),
+    ("pad_end", "x.pad_end(width, ' ')"),
+];
+
+/// Apply the text function `op` to each text, or `None` if the `args` are not the expected
+fn call_text(op: &'static str, args: &[Operand]) -> Option<ResultT<Operand>> {
+    let int = |pos: usize| match args.get(pos) {
+        Some(Operand::Scalar(Scalar::I64([x]))) => Some(*x),
+        _ => None,
+    };
+    let txt = |pos: usize| match args.get(pos) {
+        Some(Operand::Scalar(Scalar::Utf8([x]))) => Some(x.clone()),
+        Some(Operand::Scalar(Scalar::Char([x]))) => Some(x.to_string()),
+        _ => None,
+    };
+    let of = args.first()?;
+    let kind = match op {
+        "len" | "find" => DataType::I64,
+        _ => DataType::Utf8,
+    };
+    let map = |f: &dyn Fn(&str) -> ResultT<Scalar>| kernels::map_text(op, of, kind.clone(), f);
+
+    let x = match (op, args.len()) {
+        ("len", 1) => map(&|x| Ok((text::len(x) as i64).into())),
+        ("slice", 2 | 3) => {
+            let start = int(1)?;
+            let end = if args.len() == 3 { Some(int(2)?) } else { None };
+            map(&|x| Ok(text::slice(x, start, end)?.into()))
+        }
+        ("upper", 1) => map(&|x| Ok(text::upper(x).into())),
+        ("lower", 1) => map(&|x| Ok(text::lower(x).into())),
+        ("trim", 1) => map(&|x| Ok(text::trim(x, Side::Both).into())),
+        ("trim_start", 1) => map(&|x| Ok(text::trim(x, Side::Start).into())),
+        ("trim_end", 1) => map(&|x| Ok(text::trim(x, Side::End).into())),
+        ("find", 2) => {
+            let pattern = txt(1)?;
+            map(&|x| Ok(text::find(x, &pattern).into()))
+        }
+        ("replace", 3) => {
+            let (from, to) = (txt(1)?, txt(2)?);
+            map(&|x| Ok(text::replace(x, &from, &to)?.into()))
+        }
+        ("pad_start" | "pad_end", 2 | 3) => {
+            let width = int(1)?;
+            let fill = if args.len() == 3 { txt(2)? } else { " ".into() };
+            let side = if op == "pad_start" {
+                Side::Start
+            } else {
+                Side::End
+            };
+            map(&|x| Ok(text::pad(x, width, &fill, side)?.into()))
+        }
+        _ => return None,
+    };
+    Some(x)
+}
+
+/// The functions over two relations & how are called, the columns to match follow the relations
+const REL_FNS: [(&str, &str); 9] = [
+    ("union", "union(a, b)"),
+    ("intersect", "intersect(a, b)"),
+    ("difference", "difference(a, b)"),
+    ("cross", "cross(a, b)"),
+    ("natural", "natural(a, b)"),
+    ("inner", "inner(a, b, #id)"),
+    ("left", "left(a, b, #id)"),
+    ("semi", "semi(a, b, #id)"),
+    ("anti", "anti(a, b, #id)"),
+];
+
+/// The functions that turn each value in many rows, with its usage
+const EXPLODE_FNS: [(&str, &str); 2] = [
+    (
+        "split",
+        "Expected a text & the separator, like `x.split(',')`",
+    ),
+    (
+        "allocate",
+        "Expected a amount & the parts, like `x.allocate(3)`",
+    ),
+];
+
+/// Apply the function `op` that turn each value in many rows, or `None` if the arguments are
+/// not the expected
+fn call_explode(op: &str, args: &[Operand]) -> Option<ResultT<Table>> {
+    let x = match (op, args) {
+        ("split", [of, Operand::Scalar(Scalar::Utf8([sep]))]) => kernels::split(of, sep),
+        ("allocate", [of, Operand::Scalar(Scalar::I64([parts]))]) => kernels::allocate(of, *parts),
+        _ => return None,
+    };
+    Some(x)
+}
+
+/// Apply the function `op` to the relations, or `None` if the columns `on` are not the expected
+fn call_rel(op: &str, lhs: Named<'_>, rhs: Named<'_>, on: &[Column]) -> Option<ResultT<Table>> {
+    // The columns have the same name in both relations
+    let on: Vec<_> = on.iter().map(|x| (x.clone(), x.clone())).collect();
+    let x = match (op, on.is_empty()) {
+        ("union", true) => set::union(lhs.rel, rhs.rel),
+        ("intersect", true) => set::intersect(lhs.rel, rhs.rel),
+        ("difference", true) => set::difference(lhs.rel, rhs.rel),
+        ("cross", true) => join::cross(lhs, rhs),
+        ("natural", true) => join::natural(lhs, rhs),
+        ("inner", false) => join::inner(lhs, rhs, &on),
+        ("left", false) => join::left(lhs, rhs, &on),
+        ("semi", false) => join::semi(lhs.rel, rhs.rel, &on),
+        ("anti", false) => join::anti(lhs.rel, rhs.rel, &on),
+        _ => return None,
+    };
+    Some(x)
+}
+
+/// The rounding `mode` of a cast or money function, like `'half_up'`
+fn rounding(mode: &str, name: &str) -> ResultT<Rounding> {
+    Rounding::from_name(mode).ok_or_else(|| {
+        call_error(
+            ErrorKind::Invalid,
+            "Unknown rounding mode, expected one of `half_even`, `half_up`, `half_down`, `up`, `down`, `floor` or `ceiling`",
+            name,
+        )
+    })
+}
+
+/// The builtin functions: the text, bit & date functions, the aggregates like `sum(x)`, and the
+/// casts like `Int(x)` or `x.to_dec()`, with a optional [Rounding] mode like
+/// `x.to_int('half_up')`.
+///
+/// The dates without offset are read at `offset`, the one of the session
+fn call_builtin(name: &str, args: &[Operand], offset: &FixedOffset) -> ResultT<Operand> {
+    if let Some((op, usage)) = TEXT_FNS.iter().find(|(x, _)| *x == name) {
+        return call_text(op, args).unwrap_or_else(|| {
+            let msg = format!("Expected a text & the arguments, like `{usage}`");
+            Err(call_error(ErrorKind::Invalid, &msg, name))
+        });
+    }
+    if let Some(agg) = Aggregate::from_name(name) {
+        return match args {
+            [Operand::Vector(of)] if of.schema.len() == 1 => agg
+                .apply(&of.data, &of.schema.fields[0].kind)
+                .map(Operand::Scalar),
+            [Operand::Scalar(of)] => {
+                let kind = of.kind();
+                let of = Array::from_scalars(&kind, vec![of.clone()]);
+                agg.apply(&of, &kind).map(Operand::Scalar)
+            }
+            _ => {
+                let msg = format!("Expected a relation of one column, like `{name}([1; 2; 3])`");
+                Err(call_error(ErrorKind::Invalid, &msg, name))
+            }
+        };
+    }
+    if name == "xor" || Shift::from_name(name).is_some() {
+        let [of, rhs] = args else {
+            let msg = format!("Expected two values, like `x.{name}(y)`");
+            return Err(call_error(ErrorKind::Invalid, &msg, name));
+        };
+        return match Shift::from_name(name) {
+            Some(op) => kernels::shift(op, of, rhs),
+            None => kernels::xor(of, rhs),
+        };
+    }
+
+    if let Some(part) = Part::from_name(name) {
+        let [of] = args else {
+            let msg = format!("Expected a date, like `x.{name}()`");
+            return Err(call_error(ErrorKind::Invalid, &msg, name));
+        };
+        return kernels::date_part(of, part);
+    }
+    if let "to_offset" | "to_utc" | "to_local" = name {
+        let to = match (name, args) {
+            ("to_offset", [_, Operand::Scalar(Scalar::Utf8([to]))]) => types::parse_offset(to),
+            ("to_utc", [_]) => Some(types::utc()),
+            ("to_local", [_]) => Some(*offset),
+            _ => None,
+        };
+        let to = to.ok_or_else(|| {
+            call_error(
+                ErrorKind::Invalid,
+                "Expected a date time & a offset like `+05:00`, `Z` or `UTC`, like `x.to_offset('+05:00')`",
+                name,
+            )
+        })?;
+        return kernels::to_offset(&args[0], &to);
+    }
+    if name == "trunc" {
+        let part = match args {
+            [_, Operand::Scalar(Scalar::Utf8([part]))] => Part::from_name(part),
+            _ => None,
+        };
+        let part = part.ok_or_else(|| {
+            call_error(
+                ErrorKind::Invalid,
+                "Expected a date & the part to truncate, one of `year`, `month`, `week`, `day`, `hour`, `minute` or `second`, like `x.trunc('month')`",
+                name,
+            )
+        })?;
+        return kernels::trunc(&args[0], part);
+    }
+
+    if name == "currency" {
+        let [of] = args else {
+            return Err(call_error(
+                ErrorKind::Invalid,
+                "Expected a amount, like `x.currency()`",
+                name,
+            ));
+        };
+        return kernels::currency(of);
+    }
+    // With the currency, like `x.to_money('USD')`, the single argument is a cast of a text
+    if let ("to_money", [of, Operand::Scalar(Scalar::Utf8([code])), rest @ ..]) = (name, args) {
+        let currency = Currency::from_code(code).ok_or_else(|| {
+            let msg = format!("Unknown currency `{code}`");
+            call_error(ErrorKind::Invalid, &msg, name)
+        })?;
+        let round = match rest {
+            [] => Rounding::default(),
+            [Operand::Scalar(Scalar::Utf8([mode]))] => rounding(mode, name)?,
+            _ => {
+                return Err(call_error(
+                    ErrorKind::Invalid,
+                    "Expected a number, the currency & a optional rounding mode, like `x.to_money('USD', 'half_up')`",
+                    name,
+                ))
+            }
+        };
+        return kernels::to_money(of, currency, round);
+    }
+
+    let to = cast::target(name).ok_or_else(|| {
+        let msg = format!("The function `{name}` is not defined");
+        call_error(ErrorKind::NotFound, &msg, name)
+    })?;
+
+    let round = match args {
+        [_] => Rounding::default(),
+        [_, Operand::Scalar(Scalar::Utf8([mode]))] => rounding(mode, name)?,
+        _ => {
+            let msg =
+                format!("Expected a value & a optional rounding mode, like `{name}(x, 'half_up')`");
+            return Err(call_error(ErrorKind::Invalid, &msg, name));
+        }
+    };
+    kernels::cast(&args[0], &to, round, offset)
+}
+
+/// Build the case `name` of the sum type `of`, with the `args` as the values of its fields
+fn call_case(of: &SumType, name: &str, args: &[Operand]) -> ResultT<Operand> {
+    let values = args
+        .iter()
+        .map(|x| match x {
+            Operand::Scalar(x) => Ok(x.clone()),
+            Operand::Vector(_) => Err(call_error(
+                ErrorKind::Invalid,
+                "The values of a case must be scalars",
+                name,
+            )),
+        })
+        .collect::<ResultT<Vec<_>>>()?;
+    Ok(Operand::Scalar(of.build(name, &values)?.into()))
+}
+
+/// Build the sum type `name`, after the cases of its `parent`. The types of the fields
+/// must be defined, except the type itself, like in `case Cons(Int, List)`
+fn define_enum(
+    env: &Env,
+    name: &str,
+    params: &[String],
+    parent: &Option<String>,
+    cases: &[Case],
+) -> ResultT<SumType> {
+    let of = match parent {
+        Some(parent) => env.find_enum(parent)?.extend(name, params, cases)?,
+        None => SumType::new(name, params, cases)?,
+    };
+    of.check_new(cases, env.enums())?;
+    for field in of.cases.iter().flat_map(|x| &x.fields) {
+        if let FieldKind::Kind(DataType::Sum(kind)) = field {
+            if kind != name {
+                env.find_enum(kind)?;
+            }
+        }
+    }
+    Ok(of)
+}
+
+fn compile_bool(parent: &mut TreeMut<'_, Code>, check: &ExprBool) {
+    match check {
+        ExprBool::Scalar { val, span } => {
+            parent.push(Code::Scalar {
+                val: val.clone(),
+                span: *span,
+            });
+        }
+        ExprBool::Cmp { op, lhs, rhs, span } => {
+            let mut node = parent.push(Code::Cmp {
+                op: *op,
+                span: *span,
+            });
+            compile_ast(&mut node, lhs);
+            compile_ast(&mut node, rhs);
+        }
+        ExprBool::Logic { op, lhs, rhs, span } => {
+            let mut node = parent.push(Code::Logic {
+                op: *op,
+                span: *span,
+            });
+            compile_bool(&mut node, lhs);
+            compile_bool(&mut node, rhs);
+        }
+        ExprBool::Expr(x) => compile_ast(parent, x),
+        ExprBool::Not { expr, span } => {
+            let mut node = parent.push(Code::UnaryOp {
+                op: UnaryOp::Not,
+                span: *span,
+            });
+            compile_bool(&mut node, expr);
+        }
+    }
+}
+
+fn compile_ast(parent: &mut TreeMut<'_, Code>, ast: &Ast) {
+    match ast {
+        Ast::Root(_) | Ast::Pass(_) => {}
+        Ast::Ident { name, span } => {
+            parent.push(Code::Ident {
+                name: name.clone(),
+                span: *span,
+            });
+        }
+        Ast::Let { name, value, span } | Ast::Var { name, value, span } => {
+            let mut node = parent.push(Code::Define {
+                name: name.clone(),
+                mutable: matches!(ast, Ast::Var { .. }),
+                span: *span,
+            });
+            compile_ast(&mut node, value);
+        }
+        Ast::Assign { name, value, span } => {
+            let mut node = parent.push(Code::Assign {
+                name: name.clone(),
+                span: *span,
+            });
+            compile_ast(&mut node, value);
+        }
+        Ast::Block { body, span } => {
+            let mut node = parent.push(Code::Block { span: *span });
+            for x in body {
+                compile_ast(&mut node, x);
+            }
+        }
+        Ast::Scalar { val, span } => {
+            parent.push(Code::Scalar {
+                val: val.clone(),
+                span: *span,
+            });
+        }
+        Ast::IfBlock {
+            if_span,
+            check,
+            if_true,
+            if_false,
+            ..
+        } => {
+            let mut node = parent.push(Code::If { span: *if_span });
+            compile_bool(&mut node, check);
+            compile_ast(&mut node, if_true);
+            compile_ast(&mut node, if_false);
+        }
+        Ast::Match { value, arms, span } => {
+            let mut node = parent.push(Code::Match { span: *span });
+            compile_ast(&mut node, value);
+            for arm in arms {
+                let mut case = node.push(Code::Arm {
+                    pattern: Box::new(arm.pattern.clone()),
+                    span: arm.pattern.span(),
+                });
+                compile_ast(&mut case, &arm.body);
+            }
+        }
+        Ast::BinOp { op, lhs, rhs, span } => {
+            let mut node = parent.push(Code::BinOp {
+                op: *op,
+                span: *span,
+            });
+            compile_ast(&mut node, lhs);
+            compile_ast(&mut node, rhs);
+        }
+        Ast::UnaryOp { op, expr, span } => {
+            let mut node = parent.push(Code::UnaryOp {
+                op: *op,
+                span: *span,
+            });
+            compile_ast(&mut node, expr);
+        }
+        Ast::Vector { schema, rows, span } => {
+            let mut node = parent.push(Code::NewVector {
+                schema: schema.clone(),
+                span: *span,
+            });
+            for x in rows.iter().flatten() {
+                compile_ast(&mut node, x);
+            }
+        }
+        Ast::Tree { schema, rows, span } => {
+            let mut node = parent.push(Code::NewTree {
+                schema: schema.clone(),
+                span: *span,
+            });
+            for x in rows.iter().flatten() {
+                compile_ast(&mut node, x);
+            }
+        }
+        Ast::Column { col, span } => {
+            parent.push(Code::Column {
+                col: col.clone(),
+                span: *span,
+            });
+        }
+        Ast::Order { col, order, span } => {
+            parent.push(Code::Order {
+                col: col.clone(),
+                order: *order,
+                span: *span,
+            });
+        }
+        Ast::Query {
+            op,
+            rel,
+            args,
+            span,
+        } => {
+            let mut node = parent.push(Code::Query {
+                op: *op,
+                span: *span,
+            });
+            compile_ast(&mut node, rel);
+            for x in args {
+                compile_ast(&mut node, x);
+            }
+        }
+        Ast::Call { name, args, span } => {
+            let mut node = parent.push(Code::Call {
+                name: name.clone(),
+                span: *span,
+            });
+            for x in args {
+                compile_ast(&mut node, x);
+            }
+        }
+        Ast::Enum {
+            name,
+            params,
+            parent: of,
+            cases,
+            span,
+        } => {
+            parent.push(Code::DefineEnum {
+                name: name.clone(),
+                params: params.clone(),
+                parent: of.clone(),
+                cases: cases.clone(),
+                span: *span,
+            });
+        }
+        Ast::Bool(x) => compile_bool(parent, x),
+        Ast::Eof(_) => {
+            parent.push(Code::Eof);
+        }
+    }
 }
 
 impl Program {
@@ -22,34 +666,55 @@ impl Program {
             code: Tree::new(Code::Eof),
             env: Env::new(),
             files: FilesDb::from_src(""),
+            offset: types::utc(),
         }
     }
 
     pub fn from_file(file: File) -> Self {
-        Program {
+        let source = file.source().to_string();
+        let p = Program {
             code: Tree::new(Code::Eof),
             env: Env::new(),
             files: FilesDb::new(file),
-        }
+            offset: types::utc(),
+        };
+
+        p.compile_or_halt(&source)
     }
 
     pub fn from_src(source: &str) -> Self {
-        let mut p = Program {
+        let p = Program {
             code: Tree::new(Code::Eof),
             env: Env::new(),
             files: FilesDb::from_src(source),
+            offset: types::utc(),
         };
 
-        match p.compile_from_src(source) {
-            Ok(()) => p,
+        p.compile_or_halt(source)
+    }
+
+    fn compile_or_halt(mut self, source: &str) -> Self {
+        match self.compile_from_src(source) {
+            Ok(()) => self,
             Err(error) => {
                 let span = *error.span();
-                p.code = Tree::new(Code::Halt { error, span });
-                p
+                self.code = Tree::new(Code::Halt { error, span });
+                self
             }
         }
     }
 
+    /// The offset of the session
+    pub fn offset(&self) -> FixedOffset {
+        self.offset
+    }
+
+    /// Change the offset of the session, so the next code read the dates without offset, like
+    /// `dt'2000-01-01 10:00'` or `DateTime('2000-01-01 10:00')`, at it. By default is UTC
+    pub fn set_offset(&mut self, offset: FixedOffset) {
+        self.offset = offset;
+    }
+
     pub fn compile(&mut self, parsed: &Parsed) -> Result<(), ErrorCode> {
         // Only compile valid code!
         if let Some(err) = parsed.errors() {
@@ -57,43 +722,27 @@ impl Program {
         }
 
         let mut code = Tree::with_capacity(Code::Root, parsed.ast.len());
-        let mut parent = code.root().id;
+        let mut root = code.tree_root_mut();
         // Moving forward this MUST be correct code!
         for node in parsed.ast.iter() {
-            let c = match node.data {
-                Ast::Root(_) => continue,
-                Ast::Scalar { val, span } => Code::Scalar {
-                    val: val.clone(),
-                    span: *span,
-                },
-                Ast::IfBlock {
-                    if_span,
-                    do_span,
-                    else_span,
-                    end_span,
-                    check,
-                    if_true,
-                    if_false,
-                } => {
-                    todo!()
-                }
-                Ast::Cmp { op, span } => {
-                    todo!()
-                }
-                Ast::Pass(_) => continue,
-                Ast::Eof(_) => Code::Eof,
-            };
-
-            let mut node = code.tree_node_mut(parent).expect("Invalid AST id");
-            parent = node.append(c);
+            compile_ast(&mut root, node.data);
         }
 
         self.code = code;
         Ok(())
     }
 
+    /// The parser for the `source`, knowing the session offset & the enums already declared
+    fn parser(&self, source: &str) -> Parser {
+        let mut enums: Vec<_> = self.env.enums().cloned().collect();
+        enums.reverse();
+        Parser::from_src(source)
+            .with_offset(self.offset)
+            .with_enums(enums)
+    }
+
     pub fn compile_from_src(&mut self, source: &str) -> Result<(), ErrorCode> {
-        let parse = Parser::from_src(source);
+        let parse = self.parser(source);
         let result = parse.parse();
         self.compile(&result)
     }
@@ -103,134 +752,955 @@ impl Program {
         root.append(source);
         root.append("\n");
 
-        let parse = Parser::from_src(source);
+        let parse = self.parser(source);
         let result = parse.parse();
         self.compile(&result)
     }
 
-    pub fn eval(&self) -> Code {
-        let mut result = Code::Eof;
-        for node in self.code.into_iter() {
-            result = match node.data {
-                Code::Root => Code::Root,
-                Code::Scalar { .. } => node.data.clone(),
-                Code::If { code, .. } => {
-                    todo!()
-                }
-                Code::Halt { error, .. } => {
-                    result = node.data.clone();
-                    break;
-                }
-                Code::Eof => Code::Eof,
-            };
-        }
-
-        result
+    /// Evaluate a node that must return a [Scalar::Bool], or the [Code::Halt] to return
+    fn eval_bool(&self, env: &mut Env, node: Node<'_, Code>, default: Span) -> Result<bool, Code> {
+        let value = self.eval_node(env, node);
+        self.to_bool(value, default)
     }
-}
 
-impl Default for Program {
-    fn default() -> Self {
-        Self::new()
+    fn to_bool(&self, value: Code, default: Span) -> Result<bool, Code> {
+        match value {
+            Code::Scalar {
+                val: Scalar::Bool([cond]),
+                ..
+            } => Ok(cond),
+            Code::Halt { error, span } => Err(Code::Halt { error, span }),
+            other => {
+                let get = match &other {
+                    Code::Scalar { val, .. } => val.kind(),
+                    _ => DataType::Unit,
+                };
+                let span = other.span().unwrap_or(default);
+                let error = ErrorCode::Core {
+                    error: ErrorCore::TypeMismatch {
+                        expected: DataType::Bool,
+                        get,
+                    },
+                    span,
+                };
+                Err(Code::Halt { error, span })
+            }
+        }
     }
-}
-
-pub fn read_file_to_string(f: &mut fs::File) -> Result<String, io::Error> {
-    let mut x = String::new();
-    f.read_to_string(&mut x)?;
-    Ok(x)
-}
 
-pub fn create_file(path: PathBuf, source: &str) -> File {
-    File::from_path(path, source)
-}
+    fn eval_if(&self, env: &mut Env, node: Node<'_, Code>, if_span: Span) -> Code {
+        let mut branches = children(&self.code, &node);
+        let (check, if_true, if_false) = match (branches.next(), branches.next(), branches.next()) {
+            (Some(check), Some(if_true), Some(if_false)) => (check, if_true, if_false),
+            _ => unreachable!("The if block must have check, if_true & if_false branches"),
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::code::CodePrinter;
-    use crate::diagnostic::{print_diagnostic, print_diagnostic_to_str};
-    use expect_test::expect;
+        let branch = match self.eval_bool(env, check, if_span) {
+            Ok(true) => if_true,
+            Ok(false) => if_false,
+            Err(halt) => return halt,
+        };
+        self.eval_scope(env, branch)
+    }
 
-    pub(crate) fn check(source: &str, expected_tree: expect_test::Expect) {
-        let parse = Program::from_src(source);
+    /// Evaluate the body of the first arm that match the `row`, with its variables & columns
+    fn eval_arms(&self, env: &mut Env, arms: &[Node<'_, Code>], row: &Row, span: Span) -> Code {
+        for arm in arms {
+            let mut binds = Vec::new();
+            match arm.data {
+                Code::Arm { pattern, .. } if match_row(pattern, row, &mut binds) => {}
+                _ => continue,
+            }
+            let body = match children(&self.code, arm).next() {
+                Some(body) => body,
+                None => unreachable!("The arm must have a body"),
+            };
 
-        let result = parse.eval();
+            env.push_scope();
+            for (name, value) in binds {
+                env.define(&name, value, false);
+            }
+            let old = env.set_row(Some(row.clone()));
+            let result = self.eval_node(env, body);
+            env.set_row(old);
+            env.pop_scope();
+            return result;
+        }
 
-        let printer = CodePrinter {
-            parsed: &Tree::new(result),
+        let value = match &row.data[..] {
+            [value] => value.to_string(),
+            _ => row.to_string(),
         };
-        println!("{}", &printer);
-        expected_tree.assert_eq(&printer.to_string());
+        let msg = format!("No case match the value `{value}`");
+        let error = match_error(ErrorKind::NotFound, &msg);
+        Code::Halt {
+            error: ErrorCode::Core { error, span },
+            span,
+        }
     }
 
-    pub(crate) fn check_err(source: &str, expected_tree: expect_test::Expect) {
-        let parse = Program::from_src(source);
-
-        let result = parse.eval();
+    /// `match value do case pattern do body end`. A relation is matched row by row, and the
+    /// values of the bodies are collected in a [Vector]
+    fn eval_match(&self, env: &mut Env, node: Node<'_, Code>, span: Span) -> Code {
+        let mut children = children(&self.code, &node);
+        let value = match children.next() {
+            Some(value) => self.eval_node(env, value),
+            None => unreachable!("The match must have a value"),
+        };
+        let arms: Vec<_> = children.collect();
 
-        let err = match result {
-            Code::Halt { error, .. } => print_diagnostic_to_str(&parse.files, &error).unwrap(),
-            x => panic!("Expected err, got: {x:?}"),
+        let rel = match &value {
+            Code::Halt { .. } => return value,
+            Code::Scalar { val, .. } => {
+                let row = Row::new(Schema::new_scalar(val.kind()), vec![val.clone()]);
+                if let Err(halt) = check_row_fields(&arms, &row.schema) {
+                    return halt;
+                }
+                return self.eval_arms(env, &arms, &row, span);
+            }
+            value => match value.as_rel() {
+                Some(rel) => rel,
+                None => {
+                    let error = ErrorCore::Unsupported {
+                        op: "match",
+                        kind: DataType::Unit,
+                    };
+                    return Code::Halt {
+                        error: ErrorCode::Core { error, span },
+                        span,
+                    };
+                }
+            },
         };
 
-        expected_tree.assert_eq(&err);
+        let schema = rel.schema();
+        if let Err(halt) = check_row_fields(&arms, &schema) {
+            return halt;
+        }
+        let mut values = Vec::with_capacity(rel.len());
+        for data in rel.rows() {
+            let row = Row::new(schema.clone(), data);
+            match self.eval_arms(env, &arms, &row, span) {
+                Code::Scalar { val, .. } => values.push(val),
+                Code::Halt { error, span } => return Code::Halt { error, span },
+                other => {
+                    let span = other.span().unwrap_or(span);
+                    let msg = "Each case must return a single value, when match a relation";
+                    let error = match_error(ErrorKind::Invalid, msg);
+                    return Code::Halt {
+                        error: ErrorCode::Core { error, span },
+                        span,
+                    };
+                }
+            }
+        }
+
+        let kind = values.first().map(Scalar::kind).unwrap_or(DataType::Any);
+        let rows = values.len();
+        let data = Array::from_scalars(&kind, values);
+        match Vector::new(Schema::new_scalar(kind), rows, data) {
+            Ok(val) => Code::Vector {
+                val: Box::new(val),
+                span,
+            },
+            Err(error) => Code::Halt {
+                error: ErrorCode::Core { error, span },
+                span,
+            },
+        }
     }
 
-    #[test]
-    fn eval_nothing() {
-        check(
-            "",
-            expect![[r##"
-Root
-"##]],
-        );
+    /// Evaluate the node in a new scope, so the variables declared inside are not visible after
+    fn eval_scope(&self, env: &mut Env, node: Node<'_, Code>) -> Code {
+        env.push_scope();
+        let result = self.eval_node(env, node);
+        env.pop_scope();
+        result
     }
 
-    #[test]
-    fn eval_scalar() {
-        check(
-            "1",
-            expect![[r##"
-1
-"##]],
-        );
+    /// A operand of `and` & `or`: a [Vector] of booleans or a [Scalar::Bool]
+    fn to_logic_operand(&self, value: Code, default: Span) -> Result<Operand, Code> {
+        match value {
+            Code::Vector { val, .. } => Ok(Operand::Vector(*val)),
+            Code::Scalar {
+                val: val @ Scalar::Bit(_),
+                ..
+            } => Ok(Operand::Scalar(val)),
+            value => self
+                .to_bool(value, default)
+                .map(|x| Operand::Scalar(x.into())),
+        }
+    }
 
-        check(
-            "true",
-            expect![[r##"
-true
-"##]],
-        );
+    /// Short-circuit `and` & `or`: The `rhs` is only evaluated if the `lhs` not decide the result.
+    ///
+    /// If any of the operands is a [Vector], is applied to each value
+    fn eval_logic(&self, env: &mut Env, node: Node<'_, Code>, op: LogicOp, span: Span) -> Code {
+        let mut args = children(&self.code, &node);
+        let (lhs, rhs) = match (args.next(), args.next()) {
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+            _ => unreachable!("The logic operator must have lhs & rhs"),
+        };
 
-        check(
-            "123_456",
-            expect![[r##"
-123456
-"##]],
-        );
+        let lhs = match self.to_logic_operand(self.eval_node(env, lhs), span) {
+            Ok(x) => x,
+            Err(halt) => return halt,
+        };
+        match (op, &lhs) {
+            (LogicOp::And, Operand::Scalar(Scalar::Bool([false])))
+            | (LogicOp::Or, Operand::Scalar(Scalar::Bool([true]))) => {
+                return to_code(Ok(lhs), span)
+            }
+            _ => {}
+        }
+        let rhs = match self.to_logic_operand(self.eval_node(env, rhs), span) {
+            Ok(x) => x,
+            Err(halt) => return halt,
+        };
 
-        check(
-            "1.1",
-            expect![[r##"
-1.1d
-"##]],
-        );
+        let result = match op {
+            LogicOp::And => kernels::and(&lhs, &rhs),
+            LogicOp::Or => kernels::or(&lhs, &rhs),
+        };
+        to_code(result, span)
+    }
 
-        check(
-            "1.1f",
-            expect![[r##"
-1.1f
-"##]],
-        );
+    /// Apply the operator `f` to the evaluated operands, so any error is reported at `span`
+    fn eval_op(
+        &self,
+        env: &mut Env,
+        node: Node<'_, Code>,
+        op: &'static str,
+        span: Span,
+        f: impl FnOnce(&[Operand]) -> ResultT<Operand>,
+    ) -> Code {
+        match self.eval_args(env, node, op, span) {
+            Ok(args) => to_code(f(&args), span),
+            Err(halt) => halt,
+        }
+    }
 
-        check(
-            "'hello\nworld'",
-            expect![[r##"
-"hello
-world"
-"##]],
-        );
+    /// Evaluate the arguments of a element-wise operator, or return the [Code::Halt]
+    fn eval_args(
+        &self,
+        env: &mut Env,
+        node: Node<'_, Code>,
+        op: &'static str,
+        span: Span,
+    ) -> Result<Vec<Operand>, Code> {
+        let mut args = Vec::with_capacity(2);
+        for child in children(&self.code, &node) {
+            match self.eval_node(env, child) {
+                Code::Scalar { val, .. } => args.push(Operand::Scalar(val)),
+                Code::Vector { val, .. } => args.push(Operand::Vector(*val)),
+                Code::Halt { error, span } => return Err(Code::Halt { error, span }),
+                value @ (Code::Table { .. } | Code::Tree { .. })
+                    if value.as_rel().map(|x| x.schema().len()) == Some(1) =>
+                {
+                    let rel = value.as_rel().expect("Is a relation");
+                    args.push(Operand::Vector(to_vector(rel)));
+                }
+                value => {
+                    let error = unsupported(op, &value);
+                    return Err(Code::Halt {
+                        error: ErrorCode::Core { error, span },
+                        span,
+                    });
+                }
+            }
+        }
+        Ok(args)
+    }
+
+    /// Call a function that turn each value in many rows, like `x.split(',')`, returning a
+    /// [Table] of the rows
+    fn eval_explode(
+        &self,
+        env: &mut Env,
+        node: Node<'_, Code>,
+        op: &str,
+        msg: &str,
+        span: Span,
+    ) -> Code {
+        let args = match self.eval_args(env, node, "call", span) {
+            Ok(args) => args,
+            Err(halt) => return halt,
+        };
+        let result =
+            call_explode(op, &args).unwrap_or_else(|| Err(call_error(ErrorKind::Invalid, msg, op)));
+        match result {
+            Ok(val) => Code::Table {
+                val: Box::new(val),
+                span,
+            },
+            Err(error) => Code::Halt {
+                error: ErrorCode::Core { error, span },
+                span,
+            },
+        }
+    }
+
+    /// Any values can be compared, following the total order of [Scalar], except the values of
+    /// a sum type with its parameters bound to other types, like `Some(1) < Some("x")`. The
+    /// relations are only compared for (in)equality, as sets with the fields aligned by name, so
+    /// `[a:Int, b:Str; 1, "x"] = [b:Str, a:Int; "x", 1]`
+    fn eval_cmp(&self, env: &mut Env, node: Node<'_, Code>, op: CmpOp, span: Span) -> Code {
+        let mut args = Vec::with_capacity(2);
+        for child in children(&self.code, &node) {
+            match self.eval_node(env, child) {
+                Code::Halt { error, span } => return Code::Halt { error, span },
+                value => args.push(value),
+            }
+        }
+
+        let x = match (&args[0], &args[1]) {
+            (Code::Scalar { val: lhs, .. }, Code::Scalar { val: rhs, .. }) => {
+                if let Err(error) = algebraic::check_params(lhs, rhs) {
+                    return Code::Halt {
+                        error: ErrorCode::Core { error, span },
+                        span,
+                    };
+                }
+                match op {
+                    CmpOp::Equals => lhs == rhs,
+                    CmpOp::NotEquals => lhs != rhs,
+                    CmpOp::Less => lhs < rhs,
+                    CmpOp::LessEquals => lhs <= rhs,
+                    CmpOp::Greater => lhs > rhs,
+                    CmpOp::GreaterEquals => lhs >= rhs,
+                }
+            }
+            (lhs, rhs) => match (lhs.as_rel(), rhs.as_rel(), op) {
+                (Some(lhs), Some(rhs), CmpOp::Equals) => set::equals(lhs, rhs),
+                (Some(lhs), Some(rhs), CmpOp::NotEquals) => !set::equals(lhs, rhs),
+                _ => {
+                    // Report the value that is not a scalar
+                    let value = match lhs {
+                        Code::Scalar { .. } => rhs,
+                        _ => lhs,
+                    };
+                    let error = unsupported(op.symbol(), value);
+                    return Code::Halt {
+                        error: ErrorCode::Core { error, span },
+                        span,
+                    };
+                }
+            },
+        };
+        Code::Scalar {
+            val: x.into(),
+            span,
+        }
+    }
+
+    /// `op(a, b, #col)`: a function of [REL_FNS]. The relations are named like the variables
+    /// they are read from, so the fields that collide are prefixed, like `products.price`
+    fn eval_rel_call(
+        &self,
+        env: &mut Env,
+        node: Node<'_, Code>,
+        op: &str,
+        usage: &str,
+        span: Span,
+    ) -> Code {
+        let mut rels = Vec::with_capacity(2);
+        let mut on = Vec::new();
+        for child in children(&self.code, &node) {
+            let name = match child.data {
+                Code::Column { col, .. } => {
+                    on.push(col.clone());
+                    continue;
+                }
+                Code::Ident { name, .. } => name.clone(),
+                _ if rels.is_empty() => "lhs".into(),
+                _ => "rhs".into(),
+            };
+            match self.eval_node(env, child) {
+                Code::Halt { error, span } => return Code::Halt { error, span },
+                value => rels.push((name, value)),
+            }
+        }
+
+        let result = match &rels[..] {
+            [(lhs_name, lhs), (rhs_name, rhs)] => match (lhs.as_rel(), rhs.as_rel()) {
+                (Some(lhs), Some(rhs)) => call_rel(
+                    op,
+                    Named::new(lhs_name, lhs),
+                    Named::new(rhs_name, rhs),
+                    &on,
+                ),
+                _ => None,
+            },
+            _ => None,
+        };
+        let result = result.unwrap_or_else(|| {
+            let msg = format!("Expected two relations & the columns to match, like `{usage}`");
+            Err(call_error(ErrorKind::Invalid, &msg, op))
+        });
+        match result {
+            Ok(val) => Code::Table {
+                val: Box::new(val),
+                span,
+            },
+            Err(error) => Code::Halt {
+                error: ErrorCode::Core { error, span },
+                span,
+            },
+        }
+    }
+
+    /// Evaluate the cells of a relation literal, checking each one match the type of its column.
+    ///
+    /// Return the schema & the values in *row-major* order, or the [Code::Halt]
+    fn eval_cells(
+        &self,
+        env: &mut Env,
+        node: Node<'_, Code>,
+        schema: Option<Schema>,
+        span: Span,
+    ) -> Result<(Schema, Vec<Scalar>), Code> {
+        let mut schema = schema;
+        let mut data = Vec::new();
+        // The types bound to the parameters of the sum types, by column
+        let mut params = HashMap::new();
+        for (pos, child) in children(&self.code, &node).enumerate() {
+            let expected = schema
+                .as_ref()
+                .map(|x| x.fields[pos % x.len()].kind.clone());
+            let (val, cell) = match self.eval_node(env, child) {
+                Code::Scalar { val, span } => (val, span),
+                Code::Halt { error, span } => return Err(Code::Halt { error, span }),
+                other => {
+                    let span = other.span().unwrap_or(span);
+                    let error = ErrorCore::TypeMismatch {
+                        expected: expected.unwrap_or(DataType::Any),
+                        get: DataType::Unit,
+                    };
+                    return Err(Code::Halt {
+                        error: ErrorCode::Core { error, span },
+                        span,
+                    });
+                }
+            };
+
+            match expected {
+                Some(expected) if expected != DataType::Any && expected != val.kind() => {
+                    let error = ErrorCore::TypeMismatch {
+                        expected,
+                        get: val.kind(),
+                    };
+                    return Err(Code::Halt {
+                        error: ErrorCode::Core { error, span: cell },
+                        span: cell,
+                    });
+                }
+                Some(_) => {}
+                // Without a header, the first cell define the type of the column
+                None => schema = Some(Schema::new_scalar(val.kind())),
+            }
+            if let Scalar::Sum([x]) = &val {
+                let col = pos % schema.as_ref().map_or(1, |x| x.len());
+                let bound = params.entry(col).or_insert_with(|| x.params().to_vec());
+                match algebraic::unify(bound, x.params()) {
+                    Ok(x) => *bound = x,
+                    Err(error) => {
+                        return Err(Code::Halt {
+                            error: ErrorCode::Core { error, span: cell },
+                            span: cell,
+                        })
+                    }
+                }
+            }
+            data.push(val);
+        }
+
+        let schema = schema.expect("A vector without header must have cells");
+        Ok((schema, data))
+    }
+
+    fn eval_vector(
+        &self,
+        env: &mut Env,
+        node: Node<'_, Code>,
+        schema: &Option<Schema>,
+        span: Span,
+    ) -> Code {
+        let (schema, data) = match self.eval_cells(env, node, schema.clone(), span) {
+            Ok(x) => x,
+            Err(halt) => return halt,
+        };
+        if schema.len() > 1 {
+            return match Table::from_rows(schema, data) {
+                Ok(val) => Code::Table {
+                    val: Box::new(val),
+                    span,
+                },
+                Err(error) => Code::Halt {
+                    error: ErrorCode::Core { error, span },
+                    span,
+                },
+            };
+        }
+
+        let kind = schema.fields[0].kind.clone();
+        match Vector::new(schema, data.len(), Array::from_scalars(&kind, data)) {
+            Ok(val) => Code::Vector {
+                val: Box::new(val),
+                span,
+            },
+            Err(error) => Code::Halt {
+                error: ErrorCode::Core { error, span },
+                span,
+            },
+        }
+    }
+
+    fn eval_tree(&self, env: &mut Env, node: Node<'_, Code>, schema: &Schema, span: Span) -> Code {
+        match self.eval_cells(env, node, Some(schema.clone()), span) {
+            // The duplicated keys are replaced with the last row
+            Ok((schema, data)) => match tree::Tree::from_rows(schema, data) {
+                Ok(val) => Code::Tree {
+                    val: Box::new(val),
+                    span,
+                },
+                Err(error) => Code::Halt {
+                    error: ErrorCode::Core { error, span },
+                    span,
+                },
+            },
+            Err(halt) => halt,
+        }
+    }
+
+    /// Check all the columns used in the `args` are in the `schema`, so a wrong one is
+    /// reported at its span even if the relation is empty
+    fn resolve_columns(&self, args: &[Node<'_, Code>], schema: &Schema) -> Result<(), Code> {
+        for arg in args {
+            let nodes = std::iter::once(arg.data)
+                .chain(ChildrenIter::new(arg.id, &self.code).map(|x| x.data));
+            for node in nodes {
+                if let Code::Column { col, span } | Code::Order { col, span, .. } = node {
+                    if schema.resolve(col).is_none() {
+                        let error = ErrorCore::FieldNotFound {
+                            column: col.clone(),
+                        };
+                        return Err(Code::Halt {
+                            error: ErrorCode::Core { error, span: *span },
+                            span: *span,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The number of rows for `?limit` & `?skip`, that must be a positive [Scalar::I64]
+    fn eval_total(
+        &self,
+        env: &mut Env,
+        node: Node<'_, Code>,
+        op: QueryOp,
+        default: Span,
+    ) -> Result<usize, Code> {
+        let (val, span) = match self.eval_node(env, node) {
+            Code::Scalar { val, span } => (val, span),
+            Code::Halt { error, span } => return Err(Code::Halt { error, span }),
+            other => (Scalar::Unit([()]), other.span().unwrap_or(default)),
+        };
+        let error = match val {
+            Scalar::I64([x]) if x >= 0 => return Ok(x as usize),
+            Scalar::I64([x]) => ErrorCore::Lang(Box::new(
+                ErrorLang::new(
+                    ErrorKind::Invalid,
+                    Some("The number of rows can't be negative"),
+                )
+                .with_ctx(ErrorCtx::new(op.symbol(), &x.to_string())),
+            )),
+            val => ErrorCore::TypeMismatch {
+                expected: DataType::I64,
+                get: val.kind(),
+            },
+        };
+        Err(Code::Halt {
+            error: ErrorCode::Core { error, span },
+            span,
+        })
+    }
+
+    /// Only the rows of `rel` where the `check` is true, evaluated with the columns of each row
+    fn eval_where(
+        &self,
+        env: &mut Env,
+        rel: &dyn Rel,
+        check: Node<'_, Code>,
+        span: Span,
+    ) -> Result<Table, Code> {
+        let mut halt = None;
+        let result = query::filter(rel, |row| {
+            // After a error, the rest of the rows are skipped
+            if halt.is_some() {
+                return Ok(false);
+            }
+            let old = env.set_row(Some(row.to_row()));
+            let keep = self.eval_bool(env, check.clone(), span);
+            env.set_row(old);
+            Ok(keep.unwrap_or_else(|err| {
+                halt = Some(err);
+                false
+            }))
+        });
+        match halt {
+            Some(halt) => Err(halt),
+            None => result.map_err(|error| Code::Halt {
+                error: ErrorCode::Core { error, span },
+                span,
+            }),
+        }
+    }
+
+    /// `rel ?op args`: Evaluate the relation, then apply the operator
+    fn eval_query(&self, env: &mut Env, node: Node<'_, Code>, op: QueryOp, span: Span) -> Code {
+        let mut children = children(&self.code, &node);
+        let rel = match children.next() {
+            Some(rel) => self.eval_node(env, rel),
+            None => unreachable!("The query must have a relation"),
+        };
+        let args: Vec<_> = children.collect();
+
+        if let Code::Halt { .. } = rel {
+            return rel;
+        }
+        let rel = match rel.as_rel() {
+            Some(rel) => rel,
+            None => {
+                let error = ErrorCore::Unsupported {
+                    op: op.symbol(),
+                    kind: DataType::Unit,
+                };
+                return Code::Halt {
+                    error: ErrorCode::Core { error, span },
+                    span,
+                };
+            }
+        };
+        if let Err(halt) = self.resolve_columns(&args, &rel.schema()) {
+            return halt;
+        }
+
+        let cols = || -> Vec<Column> {
+            args.iter()
+                .filter_map(|x| match x.data {
+                    Code::Column { col, .. } => Some(col.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+        let result = match op {
+            QueryOp::Where => match self.eval_where(env, rel, args[0].clone(), span) {
+                Ok(val) => Ok(val),
+                Err(halt) => return halt,
+            },
+            QueryOp::Select => query::select(rel, &cols()),
+            QueryOp::Deselect => query::deselect(rel, &cols()),
+            QueryOp::Limit | QueryOp::Skip => match self.eval_total(env, args[0].clone(), op, span)
+            {
+                Ok(total) if op == QueryOp::Limit => query::limit(rel, total),
+                Ok(total) => query::skip(rel, total),
+                Err(halt) => return halt,
+            },
+            QueryOp::Distinct => query::distinct(rel),
+            QueryOp::Sort => {
+                let by: Vec<_> = args
+                    .iter()
+                    .filter_map(|x| match x.data {
+                        Code::Column { col, .. } => Some((col.clone(), Order::Asc)),
+                        Code::Order { col, order, .. } => Some((col.clone(), *order)),
+                        _ => None,
+                    })
+                    .collect();
+                query::sort(rel, &by)
+            }
+            QueryOp::Group => {
+                // The parser only accept columns & aggregates of a column
+                let aggs: Vec<_> = args
+                    .iter()
+                    .filter_map(|x| match x.data {
+                        Code::Call { name, .. } => {
+                            let col = match ChildrenIter::new(x.id, &self.code).next()?.data {
+                                Code::Column { col, .. } => col.clone(),
+                                _ => return None,
+                            };
+                            Some((Aggregate::from_name(name)?, col))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                query::group(rel, &cols(), &aggs)
+            }
+        };
+
+        match result {
+            Ok(val) => Code::Table {
+                val: Box::new(val),
+                span,
+            },
+            Err(error) => Code::Halt {
+                error: ErrorCode::Core { error, span },
+                span,
+            },
+        }
+    }
+
+    /// Evaluate the children in order, return the last value or the first [Code::Halt]
+    fn eval_lines(&self, env: &mut Env, node: Node<'_, Code>, empty: Code) -> Code {
+        let mut result = empty;
+        for child in children(&self.code, &node) {
+            result = self.eval_node(env, child);
+            if let Code::Halt { .. } = result {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Evaluate the value to store in the variable `name`
+    fn eval_var(
+        &self,
+        env: &mut Env,
+        node: Node<'_, Code>,
+        span: Span,
+        f: impl FnOnce(&mut Env, Code) -> ResultT<()>,
+    ) -> Code {
+        let value = match children(&self.code, &node).next() {
+            Some(value) => self.eval_node(env, value),
+            None => unreachable!("The variable must have a value"),
+        };
+        if let Code::Halt { .. } = value {
+            return value;
+        }
+
+        match f(env, value) {
+            Ok(()) => Code::Pass,
+            Err(error) => Code::Halt {
+                error: ErrorCode::Core { error, span },
+                span,
+            },
+        }
+    }
+
+    fn eval_node(&self, env: &mut Env, node: Node<'_, Code>) -> Code {
+        match node.data {
+            Code::Root => self.eval_lines(env, node, Code::Root),
+            Code::Block { .. } => {
+                env.push_scope();
+                let result = self.eval_lines(env, node, Code::Pass);
+                env.pop_scope();
+                result
+            }
+            Code::Column { col, span } | Code::Order { col, span, .. } => match env.column(col) {
+                Ok(val) => Code::Scalar { val, span: *span },
+                Err(error) => Code::Halt {
+                    error: ErrorCode::Core { error, span: *span },
+                    span: *span,
+                },
+            },
+            Code::Query { op, span } => self.eval_query(env, node, *op, *span),
+            Code::Call { name, span } => match env.find_case(name).cloned() {
+                Some(of) => {
+                    self.eval_op(env, node, "call", *span, |args| call_case(&of, name, args))
+                }
+                None => {
+                    let rel = REL_FNS.iter().find(|(x, _)| x == name);
+                    let explode = EXPLODE_FNS.iter().find(|(x, _)| x == name);
+                    match (rel, explode) {
+                        (Some((op, usage)), _) => self.eval_rel_call(env, node, op, usage, *span),
+                        (_, Some((op, msg))) => self.eval_explode(env, node, op, msg, *span),
+                        _ => self.eval_op(env, node, "call", *span, |args| {
+                            call_builtin(name, args, &self.offset)
+                        }),
+                    }
+                }
+            },
+            // A case without fields, like `None`, is used by name
+            Code::Ident { name, span } => match (env.get(name), env.find_case(name)) {
+                (Ok(value), _) => value.clone().with_span(*span),
+                (Err(_), Some(of)) => to_code(call_case(of, name, &[]), *span),
+                (Err(error), None) => Code::Halt {
+                    error: ErrorCode::Core { error, span: *span },
+                    span: *span,
+                },
+            },
+            Code::DefineEnum {
+                name,
+                params,
+                parent,
+                cases,
+                span,
+            } => match define_enum(env, name, params, parent, cases) {
+                Ok(of) => {
+                    env.define_enum(of);
+                    Code::Pass
+                }
+                Err(error) => Code::Halt {
+                    error: ErrorCode::Core { error, span: *span },
+                    span: *span,
+                },
+            },
+            Code::Define {
+                name,
+                mutable,
+                span,
+            } => self.eval_var(env, node, *span, |env, value| {
+                env.define(name, value, *mutable);
+                Ok(())
+            }),
+            Code::Assign { name, span } => {
+                self.eval_var(env, node, *span, |env, value| env.assign(name, value))
+            }
+            Code::Pass => Code::Pass,
+            Code::Scalar { .. } | Code::Vector { .. } | Code::Table { .. } | Code::Tree { .. } => {
+                node.data.clone()
+            }
+            Code::NewVector { schema, span } => self.eval_vector(env, node, schema, *span),
+            Code::NewTree { schema, span } => self.eval_tree(env, node, schema, *span),
+            Code::If { span } => self.eval_if(env, node, *span),
+            Code::Match { span } => self.eval_match(env, node, *span),
+            Code::Arm { .. } => unreachable!("The arms are evaluated by the match"),
+            Code::BinOp { op, span } => self.eval_op(env, node, op.symbol(), *span, |args| {
+                let (lhs, rhs) = (&args[0], &args[1]);
+                match op {
+                    BinaryOp::Add => kernels::add(lhs, rhs),
+                    BinaryOp::Sub => kernels::sub(lhs, rhs),
+                    BinaryOp::Mul => kernels::mul(lhs, rhs),
+                    BinaryOp::Div => kernels::div(lhs, rhs),
+                }
+            }),
+            Code::UnaryOp { op, span } => {
+                self.eval_op(env, node, op.symbol(), *span, |args| match op {
+                    UnaryOp::Neg => kernels::neg(&args[0]),
+                    UnaryOp::Pos => kernels::pos(&args[0]),
+                    UnaryOp::Not => kernels::not(&args[0]),
+                })
+            }
+            Code::Cmp { op, span } => self.eval_cmp(env, node, *op, *span),
+            Code::Logic { op, span } => self.eval_logic(env, node, *op, *span),
+            Code::Halt { .. } => node.data.clone(),
+            Code::Eof => Code::Eof,
+        }
+    }
+
+    pub fn eval(&mut self) -> Code {
+        // The env is moved out, so it can be changed while the code is borrowed
+        let mut env = std::mem::take(&mut self.env);
+        let result = self.eval_node(&mut env, self.code.root());
+        self.env = env;
+        result
+    }
+}
+
+impl Default for Program {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn read_file_to_string(f: &mut fs::File) -> Result<String, io::Error> {
+    let mut x = String::new();
+    f.read_to_string(&mut x)?;
+    Ok(x)
+}
+
+pub fn create_file(path: PathBuf, source: &str) -> File {
+    File::from_path(path, source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code::CodePrinter;
+    use crate::diagnostic::print_diagnostic_to_str;
+    use expect_test::expect;
+
+    pub(crate) fn check(source: &str, expected_tree: expect_test::Expect) {
+        let mut parse = Program::from_src(source);
+
+        let result = parse.eval();
+
+        let printer = CodePrinter {
+            parsed: &Tree::new(result),
+        };
+        println!("{}", &printer);
+        expected_tree.assert_eq(&printer.to_string());
+    }
+
+    pub(crate) fn check_err(source: &str, expected_tree: expect_test::Expect) {
+        let mut parse = Program::from_src(source);
+
+        let result = parse.eval();
+
+        let err = match result {
+            Code::Halt { error, .. } => print_diagnostic_to_str(&parse.files, &error).unwrap(),
+            x => panic!("Expected err, got: {x:?}"),
+        };
+
+        expected_tree.assert_eq(&err);
+    }
+
+    #[test]
+    fn eval_nothing() {
+        check(
+            "",
+            expect![[r##"
+Root
+"##]],
+        );
+    }
+
+    #[test]
+    fn eval_scalar() {
+        check(
+            "1",
+            expect![[r##"
+1
+"##]],
+        );
+
+        check(
+            "true",
+            expect![[r##"
+true
+"##]],
+        );
+
+        check(
+            "123_456",
+            expect![[r##"
+123456
+"##]],
+        );
+
+        check(
+            "1.1",
+            expect![[r##"
+1.1d
+"##]],
+        );
+
+        check(
+            "1.1f",
+            expect![[r##"
+1.1f
+"##]],
+        );
+
+        check(
+            "'hello\nworld'",
+            expect![[r##"
+"hello
+world"
+"##]],
+        );
     }
 
     #[test]
@@ -274,4 +1744,2226 @@ Bits[0, 1, 0, 0, 1]
 "##]],
         );
     }
+
+    #[test]
+    fn eval_if() {
+        check(
+            "if true do 1 else 2 end",
+            expect![[r##"
+1
+"##]],
+        );
+
+        check(
+            "if false do 1 else 2 end",
+            expect![[r##"
+2
+"##]],
+        );
+
+        check(
+            "if true do\n 'yes'\nelse\n 'no'\nend",
+            expect![[r##"
+"yes"
+"##]],
+        );
+
+        check(
+            "if false do 1 else if true do 2 else 3 end end",
+            expect![[r##"
+2
+"##]],
+        );
+    }
+
+    #[test]
+    fn eval_math() {
+        check(
+            "1 + 2 * 3",
+            expect![[r#"
+                7
+            "#]],
+        );
+        check(
+            "(1 + 2) * 3",
+            expect![[r#"
+                9
+            "#]],
+        );
+        check(
+            "10 / 2",
+            expect![[r#"
+                5d
+            "#]],
+        );
+        check(
+            "2.0 - 1.0",
+            expect![[r#"
+                1.0d
+            "#]],
+        );
+        check(
+            "2.0f * 10.5f",
+            expect![[r#"
+                21f
+            "#]],
+        );
+        check(
+            "-1 - -2",
+            expect![[r#"
+                1
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_math_err() {
+        check_err(
+            "1 + 2.0",
+            expect![[r#"
+                [100] Error: Type mismatch: expected Int, found Dec
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 1 + 2.0
+                   · ───┬───  
+                   ·    ╰───── Type mismatch: expected Int, found Dec
+                ───╯
+            "#]],
+        );
+        check_err(
+            "1 / 0",
+            expect![[r#"
+                [102] Error: Division by zero
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 1 / 0
+                   · ──┬──  
+                   ·   ╰──── Division by zero
+                ───╯
+            "#]],
+        );
+        check_err(
+            "1 +",
+            expect![[r#"
+                [03] Error: Expected an expression
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 1 +
+                   ·    │ 
+                   ·    ╰─ Expected an expression
+                ───╯
+            "#]],
+        );
+        check_err(
+            "-true",
+            expect![[r#"
+                [103] Error: The operator `-` is not supported for Bool
+                   ╭─[repl:1:1]
+                   │
+                 1 │ -true
+                   · ──┬──  
+                   ·   ╰──── The operator `-` is not supported for Bool
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_cmp() {
+        check(
+            "1 + 1 = 2",
+            expect![[r#"
+                true
+            "#]],
+        );
+        check(
+            "1 != 1",
+            expect![[r#"
+                false
+            "#]],
+        );
+        check(
+            "2.5 >= 2.5 and 1 <= 0",
+            expect![[r#"
+                false
+            "#]],
+        );
+        check(
+            "'a' < 'b' or false",
+            expect![[r#"
+                true
+            "#]],
+        );
+        check(
+            "not 1 > 2",
+            expect![[r#"
+                true
+            "#]],
+        );
+        // The total order rank the types, so a Int is always smaller than a Dec
+        check(
+            "10 < 1.0",
+            expect![[r#"
+                true
+            "#]],
+        );
+        check(
+            "if 1 < 2 and 2 < 3 do 'yes' else 'no' end",
+            expect![[r#"
+                "yes"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_short_circuit() {
+        // The `rhs` would fail, so it must not run
+        check(
+            "false and 1 / 0 = 1",
+            expect![[r#"
+                false
+            "#]],
+        );
+        check(
+            "true or 1 / 0 = 1",
+            expect![[r#"
+                true
+            "#]],
+        );
+        check_err(
+            "true and 1 / 0 = 1",
+            expect![[r#"
+                [102] Error: Division by zero
+                   ╭─[repl:1:1]
+                   │
+                 1 │ true and 1 / 0 = 1
+                   ·          ──┬──  
+                   ·            ╰──── Division by zero
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_vars() {
+        check(
+            "let x := 1\nx + 1",
+            expect![[r#"
+                2
+            "#]],
+        );
+        check(
+            "var x := 1\nx := x * 10\nx",
+            expect![[r#"
+                10
+            "#]],
+        );
+        check(
+            "let ok := 1 < 2\nif ok do 'yes' else 'no' end",
+            expect![[r#"
+                "yes"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_scopes() {
+        // The inner `x` shadow the outer until the end of the block
+        check(
+            "let x := 1\nlet y := do\n let x := 10\n x + 1\nend\nx + y",
+            expect![[r#"
+                12
+            "#]],
+        );
+        // A `var` of a outer scope can be changed in a inner one
+        check(
+            "var x := 1\nif true do\n x := 2\n x\nelse\n 0\nend\nx",
+            expect![[r#"
+                2
+            "#]],
+        );
+        check(
+            "let x := 1\nlet x := x + 1\nx",
+            expect![[r#"
+                2
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_vars_err() {
+        check_err(
+            "let x := 1\nx := 2",
+            expect![[r#"
+                [105] Error: The variable `x` is immutable, declare it with `var` to change it
+                   ╭─[repl:1:1]
+                   │
+                 2 │ x := 2
+                   · ───┬──  
+                   ·    ╰──── The variable `x` is immutable, declare it with `var` to change it
+                ───╯
+            "#]],
+        );
+        check_err(
+            "do\n let x := 1\nend\nx",
+            expect![[r#"
+                [104] Error: The variable `x` is not defined
+                   ╭─[repl:1:1]
+                   │
+                 4 │ x
+                   · ┬  
+                   · ╰── The variable `x` is not defined
+                ───╯
+            "#]],
+        );
+        check_err(
+            "var x := 1\nx := 'a'",
+            expect![[r#"
+                [100] Error: Type mismatch: expected Int, found Str
+                   ╭─[repl:1:1]
+                   │
+                 2 │ x := 'a'
+                   · ────┬───  
+                   ·     ╰───── Type mismatch: expected Int, found Str
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_vector() {
+        check(
+            "[1; 2; 3]",
+            expect![[r#"
+                [it:Int; 1; 2; 3]
+            "#]],
+        );
+        check(
+            "[Dec; 1.0; 2.5]",
+            expect![[r#"
+                [it:Dec; 1.0d; 2.5d]
+            "#]],
+        );
+        check(
+            "let x := 'Tea'\n[name:Str, price:Dec; 'Soda', 3.0; x, 1.0 + 0.5]",
+            expect![[r#"
+                [name:Str, price:Dec; "Soda", 3.0d; "Tea", 1.5d]
+            "#]],
+        );
+        check(
+            "[Int]",
+            expect![[r#"
+                [it:Int]
+            "#]],
+        );
+        check(
+            "let sales := [\n  name:Str, price:Dec, qty:Int;\n  'Hamburger', 10.2, 2;\n  'Soda', 3.0, 4;\n]\nsales",
+            expect![[r#"
+                [name:Str, price:Dec, qty:Int; "Hamburger", 10.2d, 2; "Soda", 3.0d, 4]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_vector_err() {
+        check_err(
+            "[1; 2.0]",
+            expect![[r#"
+                [100] Error: Type mismatch: expected Int, found Dec
+                   ╭─[repl:1:1]
+                   │
+                 1 │ [1; 2.0]
+                   ·     ─┬─  
+                   ·      ╰─── Type mismatch: expected Int, found Dec
+                ───╯
+            "#]],
+        );
+        check_err(
+            "[name:Str, qty:Int; 'a', 1; 'b', 'c']",
+            expect![[r#"
+                [100] Error: Type mismatch: expected Int, found Str
+                   ╭─[repl:1:1]
+                   │
+                 1 │ [name:Str, qty:Int; 'a', 1; 'b', 'c']
+                   ·                                  ─┬─  
+                   ·                                   ╰─── Type mismatch: expected Int, found Str
+                ───╯
+            "#]],
+        );
+        check_err(
+            "[a:Int, b:Int;\n 1, 2;\n 3]",
+            expect![[r#"
+                [05] Error: The row 2 has 1 values, expected 2
+                   ╭─[repl:1:1]
+                   │
+                 3 │  3]
+                   ·  ┬  
+                   ·  ╰── The row 2 has 1 values, expected 2
+                ───╯
+            "#]],
+        );
+        check_err(
+            "[1, 2]",
+            expect![[r#"
+                [06] Error: Can't infer the names of many columns, add a header like `[a:Int, b:Int]`
+                   ╭─[repl:1:1]
+                   │
+                 1 │ [1, 2]
+                   ·     ┬  
+                   ·     ╰── Can't infer the names of many columns, add a header like `[a:Int, b:Int]`
+                ───╯
+            "#]],
+        );
+        check_err(
+            "[1] + [3; 4]",
+            expect![[r#"
+                [107] Error: The vectors have a different rank: lhs = 1, rhs = 2
+                   ╭─[repl:1:1]
+                   │
+                 1 │ [1] + [3; 4]
+                   · ──────┬─────  
+                   ·       ╰─────── The vectors have a different rank: lhs = 1, rhs = 2
+                ───╯
+            "#]],
+        );
+        check_err(
+            "[true; false] and [1; 2]",
+            expect![[r#"
+                [100] Error: Type mismatch: expected Bool, found Int
+                   ╭─[repl:1:1]
+                   │
+                 1 │ [true; false] and [1; 2]
+                   · ────────────┬───────────  
+                   ·             ╰───────────── Type mismatch: expected Bool, found Int
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_broadcast() {
+        check(
+            "1 + [1; 2; 3]",
+            expect![[r#"
+                [it:Int; 2; 3; 4]
+            "#]],
+        );
+        check(
+            "[1; 2] + [3; 4]",
+            expect![[r#"
+                [it:Int; 4; 6]
+            "#]],
+        );
+        check(
+            "-[1.5; 2.0] * 2.0",
+            expect![[r#"
+                [it:Dec; -3.00d; -4.00d]
+            "#]],
+        );
+        check(
+            "not [true; false] or [false; false]",
+            expect![[r#"
+                [it:Bool; false; true]
+            "#]],
+        );
+        check(
+            "false and [true; false]",
+            expect![[r#"
+                false
+            "#]],
+        );
+        check(
+            "[Int;] / [Int;]",
+            expect![[r#"
+                [it:Dec]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_cast() {
+        check(
+            "Int('42') + 1",
+            expect![[r#"
+                43
+            "#]],
+        );
+        check(
+            "2.5.to_int()",
+            expect![[r#"
+                2
+            "#]],
+        );
+        check(
+            "2.5.to_int('half_up')",
+            expect![[r#"
+                3
+            "#]],
+        );
+        check(
+            "Int(-2.1, 'floor')",
+            expect![[r#"
+                -3
+            "#]],
+        );
+        check(
+            "d'2000-01-02'.to_str().to_date() = d'2000-01-02'",
+            expect![[r#"
+                true
+            "#]],
+        );
+        check(
+            "Dec(10) / 4.0",
+            expect![[r#"
+                2.5d
+            "#]],
+        );
+        check(
+            "[1; 2].to_float()",
+            expect![[r#"
+                [it:Float; 1f; 2f]
+            "#]],
+        );
+        check(
+            "Str([Int;])",
+            expect![[r#"
+                [it:Str]
+            "#]],
+        );
+        check(
+            "[Int;].to_dec()",
+            expect![[r#"
+                [it:Dec]
+            "#]],
+        );
+        check(
+            "let x := d'2000-01-02'\nx.to_str()",
+            expect![[r#"
+                "2000-01-02"
+            "#]],
+        );
+        check(
+            "Date('2000-01-02') = d'2000-01-02'",
+            expect![[r#"
+                true
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_cast_err() {
+        check_err(
+            "Int('1.5')",
+            expect![[r#"
+                [107] Error: The text is not a valid Int: value = 1.5
+                   ╭─[repl:1:1]
+                   │
+                 1 │ Int('1.5')
+                   · ────┬────  
+                   ·     ╰────── The text is not a valid Int: value = 1.5
+                ───╯
+            "#]],
+        );
+        check_err(
+            "1.to_date()",
+            expect![[r#"
+                [107] Error: Can't convert Int to Date: value = 1
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 1.to_date()
+                   · ─────┬────  
+                   ·      ╰────── Can't convert Int to Date: value = 1
+                ───╯
+            "#]],
+        );
+        check_err(
+            "2.5.to_int('nearest')",
+            expect![[r#"
+                [107] Error: Unknown rounding mode, expected one of `half_even`, `half_up`, `half_down`, `up`, `down`, `floor` or `ceiling`: function = to_int
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 2.5.to_int('nearest')
+                   · ──────────┬─────────  
+                   ·           ╰─────────── Unknown rounding mode, expected one of `half_even`, `half_up`, `half_down`, `up`, `down`, `floor` or `ceiling`: function = to_int
+                ───╯
+            "#]],
+        );
+        check_err(
+            "median(1)",
+            expect![[r#"
+                [107] Error: The function `median` is not defined: function = median
+                   ╭─[repl:1:1]
+                   │
+                 1 │ median(1)
+                   · ────┬───  
+                   ·     ╰───── The function `median` is not defined: function = median
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_bit_ops() {
+        check(
+            "0101b and 0011b",
+            expect![[r#"
+                Bits[0, 0, 0, 1]
+            "#]],
+        );
+        check(
+            "0101b or 0011b",
+            expect![[r#"
+                Bits[0, 1, 1, 1]
+            "#]],
+        );
+        check(
+            "not 01b",
+            expect![[r#"
+                Bits[1, 0]
+            "#]],
+        );
+        check(
+            "0101b.xor(0011b)",
+            expect![[r#"
+                Bits[0, 1, 1, 0]
+            "#]],
+        );
+        check(
+            "1001b.shift_right(1)",
+            expect![[r#"
+                Bits[1, 1, 0, 0]
+            "#]],
+        );
+        check(
+            "1001b.shift_right_zero(1)",
+            expect![[r#"
+                Bits[0, 1, 0, 0]
+            "#]],
+        );
+        check(
+            "1001b.shift_left(2)",
+            expect![[r#"
+                Bits[0, 1, 0, 0]
+            "#]],
+        );
+        check(
+            "[1b; 0b; 1b] and 1b",
+            expect![[r#"
+                [it:Bit; Bits[1]; Bits[0]; Bits[1]]
+            "#]],
+        );
+        check(
+            "not [1b; 0b]",
+            expect![[r#"
+                [it:Bit; Bits[0]; Bits[1]]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_bit_ops_err() {
+        check_err(
+            "0101b and 01b",
+            expect![[r#"
+                [107] Error: The bits have a different length: lhs = 4, rhs = 2
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 0101b and 01b
+                   · ──────┬──────  
+                   ·       ╰──────── The bits have a different length: lhs = 4, rhs = 2
+                ───╯
+            "#]],
+        );
+        check_err(
+            "01b.shift_left(-1)",
+            expect![[r#"
+                [107] Error: The shift can't be negative: shift_left = -1
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 01b.shift_left(-1)
+                   · ────────┬────────  
+                   ·         ╰────────── The shift can't be negative: shift_left = -1
+                ───╯
+            "#]],
+        );
+        check_err(
+            "01b.xor(1)",
+            expect![[r#"
+                [103] Error: The operator `xor` is not supported for Int
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 01b.xor(1)
+                   · ────┬────  
+                   ·     ╰────── The operator `xor` is not supported for Int
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_dates() {
+        check(
+            "d'2000-01-31' + i'1 month'",
+            expect![[r#"
+                2000-02-29
+            "#]],
+        );
+        check(
+            "i'1 month' + d'2000-01-31'",
+            expect![[r#"
+                2000-02-29
+            "#]],
+        );
+        check(
+            "d'2000-03-01' - i'1 day'",
+            expect![[r#"
+                2000-02-29
+            "#]],
+        );
+        check(
+            "d'2000-03-01' - d'2000-01-31'",
+            expect![[r#"
+                30 days
+            "#]],
+        );
+        check(
+            "dt'2000-01-01 10:00:00 +0000' - dt'1999-12-31 08:30:00 +0000'",
+            expect![[r#"
+                1 day 1 hour 30 minutes
+            "#]],
+        );
+        check(
+            "t'23:30:00' + i'45 minutes'",
+            expect![[r#"
+                00:15:00
+            "#]],
+        );
+        check(
+            "i'1 week' * 2 - i'1 day'",
+            expect![[r#"
+                13 days
+            "#]],
+        );
+        check(
+            "-i'2 hours'",
+            expect![[r#"
+                -2 hours
+            "#]],
+        );
+        check(
+            "Interval('1 year 2 months')",
+            expect![[r#"
+                1 year 2 months
+            "#]],
+        );
+        check(
+            "d'2000-02-16'.year()",
+            expect![[r#"
+                2000
+            "#]],
+        );
+        check(
+            "d'2000-02-16'.weekday()",
+            expect![[r#"
+                3
+            "#]],
+        );
+        check(
+            "dt'2000-02-16 10:11:12 +0000'.hour()",
+            expect![[r#"
+                10
+            "#]],
+        );
+        check(
+            "d'2000-02-16'.trunc('month')",
+            expect![[r#"
+                2000-02-01
+            "#]],
+        );
+        check(
+            "dt'2000-02-16 10:11:12 +0000'.trunc('hour')",
+            expect![[r#"
+                2000-02-16 10:00:00 +0000
+            "#]],
+        );
+        check(
+            "[d'2000-01-31'; d'2000-02-16'] + i'1 month'",
+            expect![[r#"
+                [it:Date; 2000-02-29; 2000-03-16]
+            "#]],
+        );
+        check(
+            "[d'2000-01-31'; d'2000-02-16'].month()",
+            expect![[r#"
+                [it:Int; 1; 2]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_dates_err() {
+        check_err(
+            "d'2000-01-31' + i'1 hour'",
+            expect![[r#"
+                [107] Error: Can't add hours, minutes or seconds to a Date: value = 2000-01-31
+                   ╭─[repl:1:1]
+                   │
+                 1 │ d'2000-01-31' + i'1 hour'
+                   · ────────────┬────────────  
+                   ·             ╰────────────── Can't add hours, minutes or seconds to a Date: value = 2000-01-31
+                ───╯
+            "#]],
+        );
+        check_err(
+            "t'10:00:00' + i'1 day'",
+            expect![[r#"
+                [107] Error: Can't add years, months or days to a Time: value = 10:00:00
+                   ╭─[repl:1:1]
+                   │
+                 1 │ t'10:00:00' + i'1 day'
+                   · ───────────┬──────────  
+                   ·            ╰──────────── Can't add years, months or days to a Time: value = 10:00:00
+                ───╯
+            "#]],
+        );
+        check_err(
+            "d'2000-01-31' - dt'2000-01-31 10:00:00 +0000'",
+            expect![[r#"
+                [100] Error: Type mismatch: expected Date, found DateTime
+                   ╭─[repl:1:1]
+                   │
+                 1 │ d'2000-01-31' - dt'2000-01-31 10:00:00 +0000'
+                   · ──────────────────────┬──────────────────────  
+                   ·                       ╰──────────────────────── Type mismatch: expected Date, found DateTime
+                ───╯
+            "#]],
+        );
+        check_err(
+            "d'2000-01-31'.hour()",
+            expect![[r#"
+                [107] Error: A Date has no hour: value = 2000-01-31
+                   ╭─[repl:1:1]
+                   │
+                 1 │ d'2000-01-31'.hour()
+                   · ─────────┬─────────  
+                   ·          ╰─────────── A Date has no hour: value = 2000-01-31
+                ───╯
+            "#]],
+        );
+        check_err(
+            "d'2000-01-31'.trunc('decade')",
+            expect![[r#"
+                [107] Error: Expected a date & the part to truncate, one of `year`, `month`, `week`, `day`, `hour`, `minute` or `second`, like `x.trunc('month')`: function = trunc
+                   ╭─[repl:1:1]
+                   │
+                 1 │ d'2000-01-31'.trunc('decade')
+                   · ──────────────┬─────────────  
+                   ·               ╰─────────────── Expected a date & the part to truncate, one of `year`, `month`, `week`, `day`, `hour`, `minute` or `second`, like `x.trunc('month')`: function = trunc
+                ───╯
+            "#]],
+        );
+        check_err(
+            "i'1 fortnight'",
+            expect![[r#"
+                [01] Error: Unknown unit `fortnight`, expected one of year, month, week, day, hour, minute or second
+                   ╭─[repl:1:1]
+                   │
+                 1 │ i'1 fortnight'
+                   · ───────┬──────  
+                   ·        ╰──────── Unknown unit `fortnight`, expected one of year, month, week, day, hour, minute or second
+                   · 
+                   · Note: Parsing value of type: Interval
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_offsets() {
+        check(
+            "dt'2020-02-02T08:00:00'",
+            expect![[r#"
+                2020-02-02 08:00:00 +0000
+            "#]],
+        );
+        check(
+            "dt'2020-02-02T08:00:00.5Z'",
+            expect![[r#"
+                2020-02-02 08:00:00.500 +0000
+            "#]],
+        );
+        check(
+            "dt'2020-02-02 08:00 PST'",
+            expect![[r#"
+                2020-02-02 08:00:00 -0800
+            "#]],
+        );
+        check(
+            "dt'2020-02-02T08:00:00+05:30'.to_utc()",
+            expect![[r#"
+                2020-02-02 02:30:00 +0000
+            "#]],
+        );
+        check(
+            "dt'2020-02-02T08:00:00Z'.to_offset('-03:00')",
+            expect![[r#"
+                2020-02-02 05:00:00 -0300
+            "#]],
+        );
+        check(
+            "dt'2020-02-02T08:00:00Z'.to_offset('-03:00') = dt'2020-02-02T08:00:00Z'",
+            expect![[r#"
+                true
+            "#]],
+        );
+        check(
+            "DateTime('2020-02-02T08:00:00 CET')",
+            expect![[r#"
+                2020-02-02 08:00:00 +0100
+            "#]],
+        );
+
+        // The session offset is used by the dates without one
+        let mut program = Program::new();
+        program.set_offset(types::parse_offset("+02:00").unwrap());
+        program
+            .compile_from_src(
+                "[dt'2020-02-02 08:00'.to_local(); DateTime('2020-02-02 08:00Z').to_local()]",
+            )
+            .unwrap();
+        let printer = CodePrinter {
+            parsed: &Tree::new(program.eval()),
+        };
+        expect![[r#"
+            [it:DateTime; 2020-02-02 08:00:00 +0200; 2020-02-02 10:00:00 +0200]
+        "#]]
+        .assert_eq(&printer.to_string());
+    }
+
+    #[test]
+    fn eval_offsets_err() {
+        check_err(
+            "dt'2020-02-02T08:00:00 Mars'",
+            expect![[r#"
+                [01] Error: Invalid offset `Mars`, expected one like `+05:00`, `Z` or `UTC`
+                   ╭─[repl:1:1]
+                   │
+                 1 │ dt'2020-02-02T08:00:00 Mars'
+                   · ──────────────┬─────────────  
+                   ·               ╰─────────────── Invalid offset `Mars`, expected one like `+05:00`, `Z` or `UTC`
+                   · 
+                   · Note: Parsing value of type: Date(DateTime)
+                ───╯
+            "#]],
+        );
+        check_err(
+            "d'2020-02-02'.to_utc()",
+            expect![[r#"
+                [107] Error: A Date has no offset: value = 2020-02-02
+                   ╭─[repl:1:1]
+                   │
+                 1 │ d'2020-02-02'.to_utc()
+                   · ──────────┬──────────  
+                   ·           ╰──────────── A Date has no offset: value = 2020-02-02
+                ───╯
+            "#]],
+        );
+        check_err(
+            "dt'2020-02-02T08:00:00Z'.to_offset('+99')",
+            expect![[r#"
+                [107] Error: Expected a date time & a offset like `+05:00`, `Z` or `UTC`, like `x.to_offset('+05:00')`: function = to_offset
+                   ╭─[repl:1:1]
+                   │
+                 1 │ dt'2020-02-02T08:00:00Z'.to_offset('+99')
+                   · ────────────────────┬───────────────────  
+                   ·                     ╰───────────────────── Expected a date time & a offset like `+05:00`, `Z` or `UTC`, like `x.to_offset('+05:00')`: function = to_offset
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_text() {
+        check(
+            "c'ñ'",
+            expect![[r#"
+                'ñ'
+            "#]],
+        );
+        check(
+            "c'a' < c'b'",
+            expect![[r#"
+                true
+            "#]],
+        );
+        check(
+            "Char(97)",
+            expect![[r#"
+                'a'
+            "#]],
+        );
+        check(
+            "Int(c'a')",
+            expect![[r#"
+                97
+            "#]],
+        );
+        check(
+            "'🇨🇴 Colombia'.len()",
+            expect![[r#"
+                10
+            "#]],
+        );
+        check(
+            "'🇨🇴 Colombia'.slice(2)",
+            expect![[r#"
+                "Colombia"
+            "#]],
+        );
+        check(
+            "'🇨🇴 Colombia'.slice(0, 1)",
+            expect![[r#"
+                "🇨🇴"
+            "#]],
+        );
+        check(
+            "'Straße'.upper()",
+            expect![[r#"
+                "STRASSE"
+            "#]],
+        );
+        check(
+            "'  hola  '.trim()",
+            expect![[r#"
+                "hola"
+            "#]],
+        );
+        check(
+            "'abc'.find('c')",
+            expect![[r#"
+                2
+            "#]],
+        );
+        check(
+            "'a-b-c'.replace('-', c'+')",
+            expect![[r#"
+                "a+b+c"
+            "#]],
+        );
+        check(
+            "'7'.pad_start(3, '0')",
+            expect![[r#"
+                "007"
+            "#]],
+        );
+        check(
+            "'ab'.pad_end(4)",
+            expect![[r#"
+                "ab  "
+            "#]],
+        );
+        check(
+            "'a,b,c'.split(',')",
+            expect![[r#"
+                [it:Str; "a"; "b"; "c"]
+            "#]],
+        );
+        check(
+            "['ana'; 'Luis Díaz'].upper()",
+            expect![[r#"
+                [it:Str; "ANA"; "LUIS DÍAZ"]
+            "#]],
+        );
+        check(
+            "['ana'; 'Luis Díaz'].len()",
+            expect![[r#"
+                [it:Int; 3; 9]
+            "#]],
+        );
+        check(
+            "['ana'; 'Luis Díaz'].split(' ')",
+            expect![[r#"
+                [row:Int, it:Str; 0, "ana"; 1, "Luis"; 1, "Díaz"]
+            "#]],
+        );
+        check(
+            "let parts := ['ana'; 'Luis Díaz'].split(' ')\nparts ?where #row = 1 ?select #it",
+            expect![[r#"
+                [it:Str; "Luis"; "Díaz"]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_text_err() {
+        check_err(
+            "c'ab'",
+            expect![[r#"
+                [01] Error: A Char must be a single character, like c'a'
+                   ╭─[repl:1:1]
+                   │
+                 1 │ c'ab'
+                   · ──┬──  
+                   ·   ╰──── A Char must be a single character, like c'a'
+                   · 
+                   · Note: Parsing value of type: Char
+                ───╯
+            "#]],
+        );
+        check_err(
+            "'abc'.slice(-1)",
+            expect![[r#"
+                [107] Error: The position can't be negative: start = -1
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 'abc'.slice(-1)
+                   · ───────┬──────  
+                   ·        ╰──────── The position can't be negative: start = -1
+                ───╯
+            "#]],
+        );
+        check_err(
+            "'abc'.pad_start(3, 'xy')",
+            expect![[r#"
+                [107] Error: The fill must be a single character: fill = xy
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 'abc'.pad_start(3, 'xy')
+                   · ───────────┬───────────  
+                   ·            ╰───────────── The fill must be a single character: fill = xy
+                ───╯
+            "#]],
+        );
+        check_err(
+            "1.upper()",
+            expect![[r#"
+                [103] Error: The operator `upper` is not supported for Int
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 1.upper()
+                   · ────┬───  
+                   ·     ╰───── The operator `upper` is not supported for Int
+                ───╯
+            "#]],
+        );
+        check_err(
+            "'abc'.replace('a')",
+            expect![[r#"
+                [107] Error: Expected a text & the arguments, like `x.replace('a', 'b')`: function = replace
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 'abc'.replace('a')
+                   · ────────┬────────  
+                   ·         ╰────────── Expected a text & the arguments, like `x.replace('a', 'b')`: function = replace
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_strings() {
+        check(
+            "\"tab\\there\"",
+            expect![[r#"
+                "tab	here"
+            "#]],
+        );
+        check(
+            "\"it\\\"s \\u{1F600}\"",
+            expect![[r#"
+                "it"s 😀"
+            "#]],
+        );
+        check(
+            "\"ab\" + 'cd'",
+            expect![[r#"
+                "abcd"
+            "#]],
+        );
+        check(
+            "[\"a\"; \"b\"] + \"!\"",
+            expect![[r#"
+                [it:Str; "a!"; "b!"]
+            "#]],
+        );
+        check(
+            "let price := 3\nlet qty := 4\n\"total: {price * qty}\"",
+            expect![[r#"
+                "total: 12"
+            "#]],
+        );
+        check(
+            "\"{1 + 1} is {2.to_str()}\"",
+            expect![[r#"
+                "2 is 2"
+            "#]],
+        );
+        check(
+            "\"ids: {[1; 2]}\"",
+            expect![[r#"
+                [it:Str; "ids: 1"; "ids: 2"]
+            "#]],
+        );
+        check(
+            "\"\"\"\nline 1\n  \"line\" 2\n\"\"\"",
+            expect![[r#"
+                "line 1
+                  "line" 2
+                "
+            "#]],
+        );
+        check(
+            "\"\\{not interpolated\\}\"",
+            expect![[r#"
+                "{not interpolated}"
+            "#]],
+        );
+        check(
+            "c'\\'' = c'\\u{27}'",
+            expect![[r#"
+                true
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_strings_err() {
+        check_err(
+            "\"{missing}\"",
+            expect![[r#"
+                [104] Error: The variable `missing` is not defined
+                   ╭─[repl:1:1]
+                   │
+                 1 │ "{missing}"
+                   ·   ───┬───  
+                   ·      ╰───── The variable `missing` is not defined
+                ───╯
+            "#]],
+        );
+        check_err(
+            "\"a\" - \"b\"",
+            expect![[r#"
+                [103] Error: The operator `-` is not supported for Str
+                   ╭─[repl:1:1]
+                   │
+                 1 │ "a" - "b"
+                   · ────┬────  
+                   ·     ╰────── The operator `-` is not supported for Str
+                ───╯
+            "#]],
+        );
+        check_err(
+            "\"bad \\q\"",
+            expect![[r#"
+                [01] Error: Invalid escape, expected one of `\n \t \r \0 \\ \' \" \{ \} \u{..}`
+                   ╭─[repl:1:1]
+                   │
+                 1 │ "bad \q"
+                   ·      ─┬  
+                   ·       ╰── Invalid escape, expected one of `\n \t \r \0 \\ \' \" \{ \} \u{..}`
+                   · 
+                   · Note: Parsing value of type: Utf8
+                ───╯
+            "#]],
+        );
+        check_err(
+            "\"{1 +}\"",
+            expect![[r#"
+                [03] Error: Expected an expression
+                   ╭─[repl:1:1]
+                   │
+                 1 │ "{1 +}"
+                   ·      │ 
+                   ·      ╰─ Expected an expression
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_money() {
+        check(
+            "12.5 USD",
+            expect![[r#"
+                12.50 USD
+            "#]],
+        );
+        check(
+            "0.1 USD + 0.2 USD",
+            expect![[r#"
+                0.30 USD
+            "#]],
+        );
+        check(
+            "10 USD / 3",
+            expect![[r#"
+                3.33 USD
+            "#]],
+        );
+        check(
+            "0.25 USD / 2",
+            expect![[r#"
+                0.12 USD
+            "#]],
+        );
+        check(
+            "1.5 * 10 USD",
+            expect![[r#"
+                15.00 USD
+            "#]],
+        );
+        check(
+            "10 USD / 4 USD",
+            expect![[r#"
+                2.50d
+            "#]],
+        );
+        check(
+            "(10 USD).allocate(3)",
+            expect![[r#"
+                [it:Money; 3.34 USD; 3.33 USD; 3.33 USD]
+            "#]],
+        );
+        check(
+            "-(5 JPY)",
+            expect![[r#"
+                -5 JPY
+            "#]],
+        );
+        check(
+            "2.345.to_money('USD')",
+            expect![[r#"
+                2.34 USD
+            "#]],
+        );
+        check(
+            "2.345.to_money('USD', 'half_up')",
+            expect![[r#"
+                2.35 USD
+            "#]],
+        );
+        check(
+            "'3 EUR'.to_money()",
+            expect![[r#"
+                3.00 EUR
+            "#]],
+        );
+        check(
+            "(1 USD).currency()",
+            expect![[r#"
+                "USD"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_money_err() {
+        check_err(
+            "1 USD + 1 EUR",
+            expect![[r#"
+                [107] Error: Can't mix currencies: lhs = USD, rhs = EUR
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 1 USD + 1 EUR
+                   · ──────┬──────  
+                   ·       ╰──────── Can't mix currencies: lhs = USD, rhs = EUR
+                ───╯
+            "#]],
+        );
+        check_err(
+            "12.505 USD",
+            expect![[r#"
+                [01] Error: USD has only 2 decimals: amount = 12.505
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 12.505 USD
+                   · ─────┬────  
+                   ·      ╰────── USD has only 2 decimals: amount = 12.505
+                   · 
+                   · Note: Parsing value of type: Money
+                ───╯
+            "#]],
+        );
+        check_err(
+            "1 XYZ",
+            expect![[r#"
+                [01] Error: Unknown currency `XYZ`
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 1 XYZ
+                   · ──┬──  
+                   ·   ╰──── Unknown currency `XYZ`
+                   · 
+                   · Note: Parsing value of type: Money
+                ───╯
+            "#]],
+        );
+        check_err(
+            "1 USD * 1.5f",
+            expect![[r#"
+                [103] Error: The operator `*` is not supported for Money
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 1 USD * 1.5f
+                   · ──────┬─────  
+                   ·       ╰─────── The operator `*` is not supported for Money
+                ───╯
+            "#]],
+        );
+        check_err(
+            "1.to_money('XYZ')",
+            expect![[r#"
+                [107] Error: Unknown currency `XYZ`: function = to_money
+                   ╭─[repl:1:1]
+                   │
+                 1 │ 1.to_money('XYZ')
+                   · ────────┬───────  
+                   ·         ╰───────── Unknown currency `XYZ`: function = to_money
+                ───╯
+            "#]],
+        );
+        check_err(
+            "(1 USD).allocate(0)",
+            expect![[r#"
+                [107] Error: The parts must be more than zero: parts = 0
+                   ╭─[repl:1:1]
+                   │
+                 1 │ (1 USD).allocate(0)
+                   ·  ────────┬────────  
+                   ·          ╰────────── The parts must be more than zero: parts = 0
+                ───╯
+            "#]],
+        );
+        check_err(
+            "(1 USD).allocate(100000000000)",
+            expect![[r#"
+                [107] Error: The parts can't be more than 1000000: parts = 100000000000
+                   ╭─[repl:1:1]
+                   │
+                 1 │ (1 USD).allocate(100000000000)
+                   ·  ──────────────┬─────────────  
+                   ·                ╰─────────────── The parts can't be more than 1000000: parts = 100000000000
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_enum() {
+        check(
+            "enum Status do\n case Active\n case Inactive\nend\nActive",
+            expect![[r#"
+                Active
+            "#]],
+        );
+        check(
+            "enum Option[T] do\n case None\n case Some(T)\nend\nSome(1)",
+            expect![[r#"
+                Some(1)
+            "#]],
+        );
+        check(
+            "enum Option[T] do case None case Some(T) end\n[Some(2); None; Some(1)]",
+            expect![[r#"
+                [it:Option; Some(2); None; Some(1)]
+            "#]],
+        );
+        check(
+            "enum Option[T] do case None case Some(T) end\nNone < Some(1) and Some(1) < Some(2)",
+            expect![[r#"
+                true
+            "#]],
+        );
+        check(
+            "enum Value do\n case Num(Int)\n case Dec(Dec)\nend\nenum Value2: Value do\n case Txt(Str)\nend\n[Num(1); Txt('a')]",
+            expect![[r#"
+                [it:Value2; Num(1); Txt("a")]
+            "#]],
+        );
+        check(
+            "enum List do case Nil case Cons(Int, List) end\nCons(1, Cons(2, Nil))",
+            expect![[r#"
+                Cons(1, Cons(2, Nil))
+            "#]],
+        );
+        check(
+            "enum Status do case Active case Inactive end\nlet x := Active\nx = Inactive",
+            expect![[r#"
+                false
+            "#]],
+        );
+        check(
+            "let None := 1\nenum Option[T] do case None case Some(T) end\nNone",
+            expect![[r#"
+                1
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_enum_err() {
+        check_err(
+            "enum Option[T] do case None case Some(T) end\nSome()",
+            expect![[r#"
+                [107] Error: The case `Some` take 1 values, but get 0: case = Some(T)
+                   ╭─[repl:1:1]
+                   │
+                 2 │ Some()
+                   · ──┬──  
+                   ·   ╰──── The case `Some` take 1 values, but get 0: case = Some(T)
+                ───╯
+            "#]],
+        );
+        check_err(
+            "enum Value do case Num(Int) end\nNum('1')",
+            expect![[r#"
+                [100] Error: Type mismatch: expected Int, found Str
+                   ╭─[repl:1:1]
+                   │
+                 2 │ Num('1')
+                   · ───┬───  
+                   ·    ╰───── Type mismatch: expected Int, found Str
+                ───╯
+            "#]],
+        );
+        check_err(
+            "enum Pair[T] do case Pair(T, T) end\nPair(1, 'a')",
+            expect![[r#"
+                [100] Error: Type mismatch: expected Int, found Str
+                   ╭─[repl:1:1]
+                   │
+                 2 │ Pair(1, 'a')
+                   · ─────┬─────  
+                   ·      ╰─────── Type mismatch: expected Int, found Str
+                ───╯
+            "#]],
+        );
+        check_err(
+            "enum Value2: Value do case Txt(Str) end",
+            expect![[r#"
+                [107] Error: The type `Value` is not defined
+                   ╭─[repl:1:1]
+                   │
+                 1 │ enum Value2: Value do case Txt(Str) end
+                   · ───────────────────┬───────────────────  
+                   ·                    ╰───────────────────── The type `Value` is not defined
+                ───╯
+            "#]],
+        );
+        check_err(
+            "enum Tree do case Leaf(Node) end",
+            expect![[r#"
+                [107] Error: The type `Node` is not defined
+                   ╭─[repl:1:1]
+                   │
+                 1 │ enum Tree do case Leaf(Node) end
+                   · ────────────────┬───────────────  
+                   ·                 ╰───────────────── The type `Node` is not defined
+                ───╯
+            "#]],
+        );
+        check_err(
+            "enum Status do case Active case Active end",
+            expect![[r#"
+                [107] Error: The case `Active` is duplicated: enum = Status
+                   ╭─[repl:1:1]
+                   │
+                 1 │ enum Status do case Active case Active end
+                   · ─────────────────────┬────────────────────  
+                   ·                      ╰────────────────────── The case `Active` is duplicated: enum = Status
+                ───╯
+            "#]],
+        );
+        check_err(
+            "enum Option[T] do case None case Some(T) end\nSome([1; 2])",
+            expect![[r#"
+                [107] Error: The values of a case must be scalars: function = Some
+                   ╭─[repl:1:1]
+                   │
+                 2 │ Some([1; 2])
+                   · ─────┬─────  
+                   ·      ╰─────── The values of a case must be scalars: function = Some
+                ───╯
+            "#]],
+        );
+        check_err(
+            "enum Status do case Active end\nenum Status do case Done end",
+            expect![[r#"
+                [09] Error: The type `Status` is already defined: enum = Status
+                   ╭─[repl:1:1]
+                   │
+                 2 │ enum Status do case Done end
+                   · ──────────────┬─────────────  
+                   ·               ╰─────────────── The type `Status` is already defined: enum = Status
+                ───╯
+            "#]],
+        );
+        check_err(
+            "enum A do case X case Y end\nenum B do case X end",
+            expect![[r#"
+                [09] Error: The case `X` is already defined in `A`: enum = B
+                   ╭─[repl:1:1]
+                   │
+                 2 │ enum B do case X end
+                   · ──────────┬─────────  
+                   ·           ╰─────────── The case `X` is already defined in `A`: enum = B
+                ───╯
+            "#]],
+        );
+        let opt = "enum Option[T] do case None case Some(T) end\n";
+        check_err(
+            &format!("{opt}Some(1) < Some('x')"),
+            expect![[r#"
+                [100] Error: Type mismatch: expected Int, found Str
+                   ╭─[repl:1:1]
+                   │
+                 2 │ Some(1) < Some('x')
+                   · ─────────┬────────  
+                   ·          ╰────────── Type mismatch: expected Int, found Str
+                ───╯
+            "#]],
+        );
+        check_err(
+            &format!("{opt}[Some(1); None; Some('x')]"),
+            expect![[r#"
+                [100] Error: Type mismatch: expected Int, found Str
+                   ╭─[repl:1:1]
+                   │
+                 2 │ [Some(1); None; Some('x')]
+                   ·                 ────┬───  
+                   ·                     ╰───── Type mismatch: expected Int, found Str
+                ───╯
+            "#]],
+        );
+        check_err(
+            &format!("{opt}var x := Some(1)\nx := Some('x')"),
+            expect![[r#"
+                [100] Error: Type mismatch: expected Int, found Str
+                   ╭─[repl:1:1]
+                   │
+                 3 │ x := Some('x')
+                   · ──────┬──────  
+                   ·       ╰──────── Type mismatch: expected Int, found Str
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_match() {
+        check(
+            "let x := 5\nmatch x do case 0 do 'zero' case 1..10 do 'small' else 'big' end",
+            expect![[r#"
+                "small"
+            "#]],
+        );
+        check(
+            "match -1 do case -1 do 'minus one' else 'other' end",
+            expect![[r#"
+                "minus one"
+            "#]],
+        );
+        check(
+            "enum Option[T] do case None case Some(T) end\nmatch Some(2) do\n case Some(x) do x * 10\n case None do 0\nend",
+            expect![[r#"
+                20
+            "#]],
+        );
+        check(
+            "enum Option[T] do case None case Some(T) end\nmatch [Some(1); None; Some(3)] do case Some(x) do x case None do 0 end",
+            expect![[r#"
+                [it:Int; 1; 0; 3]
+            "#]],
+        );
+        check(
+            "let t := Tree[| pk id:Int, qty:Int; 1, 10; 2, 0 |]\nmatch t do case [| qty: 0 |] do 0 case [| id: x |] do #qty * x end",
+            expect![[r#"
+                [it:Int; 10; 0]
+            "#]],
+        );
+        check(
+            "enum Option[T] do case None case Some(T) end\nlet x := Some(1)\nmatch x do case Some(Some(y)) do y else 0 end",
+            expect![[r#"
+                0
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_match_err() {
+        check_err(
+            "match 1 do case 1 do 1 end",
+            expect![[r#"
+                [10] Error: The `match` not cover all the values, add a `else`
+                   ╭─[repl:1:1]
+                   │
+                 1 │ match 1 do case 1 do 1 end
+                   · ───┬───  
+                   ·    ╰───── The `match` not cover all the values, add a `else`
+                ───╯
+            "#]],
+        );
+        check_err(
+            "enum Option[T] do case None case Some(T) end\nmatch None do case Some(x) do x end",
+            expect![[r#"
+                [10] Error: The `match` not cover the cases `None`, add them or a `else`
+                   ╭─[repl:1:1]
+                   │
+                 2 │ match None do case Some(x) do x end
+                   · ─────┬────  
+                   ·      ╰────── The `match` not cover the cases `None`, add them or a `else`
+                ───╯
+            "#]],
+        );
+        check_err(
+            "match 1 do case x do x case 1 do 2 end",
+            expect![[r#"
+                [10] Error: The case is unreachable, the cases before it match all its values
+                   ╭─[repl:1:1]
+                   │
+                 1 │ match 1 do case x do x case 1 do 2 end
+                   ·                             ┬  
+                   ·                             ╰── The case is unreachable, the cases before it match all its values
+                ───╯
+            "#]],
+        );
+        check_err(
+            "match Tree[| pk id:Int; 1 |] do case [| qty: x |] do x end",
+            expect![[r#"
+                [107] Error: The field `qty` is not in the row `[id:Int]`
+                   ╭─[repl:1:1]
+                   │
+                 1 │ match Tree[| pk id:Int; 1 |] do case [| qty: x |] do x end
+                   ·                                      ──────┬─────  
+                   ·                                            ╰─────── The field `qty` is not in the row `[id:Int]`
+                ───╯
+            "#]],
+        );
+        check_err(
+            "let lines := Tree[| pk id:Int, qty:Int; 1, 10 |]\nmatch lines do case [| price: x |] do 0 else 1 end",
+            expect![[r#"
+                [107] Error: The field `price` is not in the row `[id:Int, qty:Int]`
+                   ╭─[repl:1:1]
+                   │
+                 2 │ match lines do case [| price: x |] do 0 else 1 end
+                   ·                     ───────┬──────  
+                   ·                            ╰──────── The field `price` is not in the row `[id:Int, qty:Int]`
+                ───╯
+            "#]],
+        );
+        check_err(
+            "let x := [a:Int, b:Int; 1, 2]\nmatch x do case [| c: 1 |] do 1 else 2 end",
+            expect![[r#"
+                [107] Error: The field `c` is not in the row `[a:Int, b:Int]`
+                   ╭─[repl:1:1]
+                   │
+                 2 │ match x do case [| c: 1 |] do 1 else 2 end
+                   ·                 ─────┬────  
+                   ·                      ╰────── The field `c` is not in the row `[a:Int, b:Int]`
+                ───╯
+            "#]],
+        );
+        let value = "enum Value do case Txt(Str) case Num(Int) end\nenum Value2: Value do case Bool(Bool) end\n";
+        check_err(
+            &format!("{value}match Bool(true) do case Txt(x) do 1 case Num(x) do 2 end"),
+            expect![[r#"
+                [10] Error: The `match` not cover the cases `Bool(_)`, add them or a `else`
+                   ╭─[repl:1:1]
+                   │
+                 3 │ match Bool(true) do case Txt(x) do 1 case Num(x) do 2 end
+                   · ───────┬───────  
+                   ·        ╰───────── The `match` not cover the cases `Bool(_)`, add them or a `else`
+                ───╯
+            "#]],
+        );
+        check_err(
+            &format!("{value}enum Status do case Active end\nmatch Active do case Active do 1 case Num(x) do 2 end"),
+            expect![[r#"
+                [10] Error: The case `Num` is not of the same enum of `Active`
+                   ╭─[repl:1:1]
+                   │
+                 4 │ match Active do case Active do 1 case Num(x) do 2 end
+                   ·                                       ──┬──  
+                   ·                                         ╰──── The case `Num` is not of the same enum of `Active`
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_tree() {
+        check(
+            "Tree[|\n pk id:Int, name:Str;\n 2, 'world';\n 1, 'hello';\n 2, 'again'\n|]",
+            expect![[r#"
+                Tree[|pk id:Int, name:Str; 1, "hello"; 2, "again"|]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_tree_composite() {
+        check(
+            "Tree[| pk invoice:Int, pk line:Int, qty:Int;\n 2, 1, 3;\n 1, 2, 5;\n 1, 1, 10 |]",
+            expect![[r#"
+                Tree[|pk invoice:Int, pk line:Int, qty:Int; 1, 1, 10; 1, 2, 5; 2, 1, 3|]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_tree_err() {
+        check_err(
+            "Tree[| pk id:Int, name:Str; 1, 2 |]",
+            expect![[r#"
+                [100] Error: Type mismatch: expected Str, found Int
+                   ╭─[repl:1:1]
+                   │
+                 1 │ Tree[| pk id:Int, name:Str; 1, 2 |]
+                   ·                                ┬  
+                   ·                                ╰── Type mismatch: expected Str, found Int
+                ───╯
+            "#]],
+        );
+        check_err(
+            "Tree[| id:Int; 1 |]",
+            expect![[r#"
+                [06] Error: A `Tree` need a key, add `pk` to a column like `Tree[| pk id:Int |]`
+                   ╭─[repl:1:1]
+                   │
+                 1 │ Tree[| id:Int; 1 |]
+                   · ─────────┬─────────  
+                   ·          ╰─────────── A `Tree` need a key, add `pk` to a column like `Tree[| pk id:Int |]`
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_query() {
+        let sales = "let sales := Tree[| pk id:Int, name:Str, qty:Int; 1, 'Soda', 4; 2, 'Tea', 2; 3, 'Soda', 4 |]\n";
+        check(
+            &format!("{sales}sales ?where #qty > 2 ?select #name, #0"),
+            expect![[r#"
+                [name:Str, id:Int; "Soda", 1; "Soda", 3]
+            "#]],
+        );
+        check(
+            &format!("{sales}sales ?deselect #id ?distinct"),
+            expect![[r#"
+                [name:Str, qty:Int; "Soda", 4; "Tea", 2]
+            "#]],
+        );
+        check(
+            &format!("{sales}sales ?skip 1 ?limit 1"),
+            expect![[r#"
+                [id:Int, name:Str, qty:Int; 2, "Tea", 2]
+            "#]],
+        );
+        check(
+            &format!("{sales}sales ?sort #qty desc, #name ?select #id"),
+            expect![[r#"
+                [id:Int; 1; 3; 2]
+            "#]],
+        );
+        check(
+            "[3; 1; 2] ?sort",
+            expect![[r#"
+                [it:Int; 1; 2; 3]
+            "#]],
+        );
+        check(
+            "[1; 2; 3] ?where #it != 2",
+            expect![[r#"
+                [it:Int; 1; 3]
+            "#]],
+        );
+        check(
+            &format!("{sales}sales ?group #name, sum(#qty), count(#id)"),
+            expect![[r#"
+                [name:Str, sum_qty:Int, count_id:Int; "Soda", 8, 2; "Tea", 2, 1]
+            "#]],
+        );
+        check(
+            &format!("{sales}sales ?group avg(#qty), max(#name)"),
+            expect![[r#"
+                [avg_qty:Dec, max_name:Str; 3.3333333333333333333333333333d, "Tea"]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_aggregate() {
+        check(
+            "sum([1; 2; 3])",
+            expect![[r#"
+            6
+        "#]],
+        );
+        check(
+            "[1.5d; 2.25d].sum()",
+            expect![[r#"
+            3.75d
+        "#]],
+        );
+        check(
+            "avg([1; 2; 3])",
+            expect![[r#"
+            2d
+        "#]],
+        );
+        check(
+            "min([3; 1; 2])",
+            expect![[r#"
+            1
+        "#]],
+        );
+        check(
+            "max(['a'; 'c'; 'b'])",
+            expect![[r#"
+            "c"
+        "#]],
+        );
+        check(
+            "count([1; 2] ?where #it > 5)",
+            expect![[r#"
+            0
+        "#]],
+        );
+        check(
+            "sum(1.0f)",
+            expect![[r#"
+            1f
+        "#]],
+        );
+        check(
+            "let sales := [name:Str, qty:Int; 'Soda', 4; 'Tea', 2]\nsum(sales ?select #qty)",
+            expect![[r#"
+                6
+            "#]],
+        );
+        check(
+            "sum([1 USD; 2 USD] ?where #it > 5 USD)",
+            expect![[r#"
+            ()
+        "#]],
+        );
+        check(
+            "avg([1 USD; 2 USD] ?where #it > 5 USD)",
+            expect![[r#"
+            ()
+        "#]],
+        );
+        check(
+            "[m:Money; 1 USD] ?where #m > 5 USD ?group sum(#m), avg(#m)",
+            expect![[r#"
+                [sum_m:Money, avg_m:Money; (), ()]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_query_err() {
+        check_err(
+            "[a:Int, b:Int; 1, 2] ?select #a, #price",
+            expect![[r#"
+                [106] Error: The column `#price` is not in the schema
+                   ╭─[repl:1:1]
+                   │
+                 1 │ [a:Int, b:Int; 1, 2] ?select #a, #price
+                   ·                                  ───┬──  
+                   ·                                     ╰──── The column `#price` is not in the schema
+                ───╯
+            "#]],
+        );
+        check_err(
+            "[1; 2] ?where #3 = 1",
+            expect![[r#"
+                [106] Error: The column `#3` is not in the schema
+                   ╭─[repl:1:1]
+                   │
+                 1 │ [1; 2] ?where #3 = 1
+                   ·               ─┬  
+                   ·                ╰── The column `#3` is not in the schema
+                ───╯
+            "#]],
+        );
+        check_err(
+            "[1; 2] ?sort #price desc",
+            expect![[r#"
+                [106] Error: The column `#price` is not in the schema
+                   ╭─[repl:1:1]
+                   │
+                 1 │ [1; 2] ?sort #price desc
+                   ·              ─────┬─────  
+                   ·                   ╰─────── The column `#price` is not in the schema
+                ───╯
+            "#]],
+        );
+        check_err(
+            "[a:Int; 1] ?group #a, sum(#b)",
+            expect![[r#"
+                [106] Error: The column `#b` is not in the schema
+                   ╭─[repl:1:1]
+                   │
+                 1 │ [a:Int; 1] ?group #a, sum(#b)
+                   ·                           ─┬  
+                   ·                            ╰── The column `#b` is not in the schema
+                ───╯
+            "#]],
+        );
+        check_err(
+            "sum([a:Int, b:Int; 1, 2])",
+            expect![[r#"
+                [103] Error: The operator `call` is not supported for a Table
+                   ╭─[repl:1:1]
+                   │
+                 1 │ sum([a:Int, b:Int; 1, 2])
+                   · ────────────┬───────────  
+                   ·             ╰───────────── The operator `call` is not supported for a Table
+                ───╯
+            "#]],
+        );
+        check_err(
+            "[1; 2] ?limit -1",
+            expect![[r#"
+                [107] Error: The number of rows can't be negative: ?limit = -1
+                   ╭─[repl:1:1]
+                   │
+                 1 │ [1; 2] ?limit -1
+                   ·               ─┬  
+                   ·                ╰── The number of rows can't be negative: ?limit = -1
+                ───╯
+            "#]],
+        );
+        check_err(
+            "[1; 2] ?where #it = 1 and x",
+            expect![[r#"
+                [104] Error: The variable `x` is not defined
+                   ╭─[repl:1:1]
+                   │
+                 1 │ [1; 2] ?where #it = 1 and x
+                   ·                           ┬  
+                   ·                           ╰── The variable `x` is not defined
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_sets() {
+        let rels = "let a := [name:Str, qty:Int; 'Soda', 1; 'Tea', 2]\nlet b := [qty:Int, name:Str; 2, 'Tea'; 3, 'Cake']\n";
+        check(
+            &format!("{rels}union(a, b)"),
+            expect![[r#"
+                [name:Str, qty:Int; "Soda", 1; "Tea", 2; "Cake", 3]
+            "#]],
+        );
+        check(
+            &format!("{rels}a.intersect(b)"),
+            expect![[r#"
+                [name:Str, qty:Int; "Tea", 2]
+            "#]],
+        );
+        check(
+            &format!("{rels}difference(a, b)"),
+            expect![[r#"
+                [name:Str, qty:Int; "Soda", 1]
+            "#]],
+        );
+        check(
+            &format!("{rels}[a:Int, b:Str; 1, \"x\"] = [b:Str, a:Int; \"x\", 1]"),
+            expect![[r#"
+                true
+            "#]],
+        );
+        check(
+            &format!("{rels}[1; 2] = [2; 1; 1]"),
+            expect![[r#"
+                true
+            "#]],
+        );
+        check(
+            &format!("{rels}a != b"),
+            expect![[r#"
+                true
+            "#]],
+        );
+        check(
+            &format!("{rels}Tree[| pk id:Int; 1 |] = [id:Int; 1]"),
+            expect![[r#"
+                true
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_sets_err() {
+        let rels = "let a := [name:Str, qty:Int; 'Soda', 1; 'Tea', 2]\nlet b := [qty:Int, name:Str; 2, 'Tea'; 3, 'Cake']\n";
+        check_err(
+            &format!("{rels}union(a, [1; 2])"),
+            expect![[r#"
+                [107] Error: The fields of the relations not match: lhs = name:Str, lhs = qty:Int, rhs = it:Int
+                   ╭─[repl:1:1]
+                   │
+                 3 │ union(a, [1; 2])
+                   · ───────┬───────  
+                   ·        ╰───────── The fields of the relations not match: lhs = name:Str, lhs = qty:Int, rhs = it:Int
+                ───╯
+            "#]],
+        );
+        check_err(
+            &format!("{rels}a < b"),
+            expect![[r#"
+                [103] Error: The operator `<` is not supported for a Table
+                   ╭─[repl:1:1]
+                   │
+                 3 │ a < b
+                   · ──┬──  
+                   ·   ╰──── The operator `<` is not supported for a Table
+                ───╯
+            "#]],
+        );
+        check_err(
+            &format!("{rels}a + 1"),
+            expect![[r#"
+                [103] Error: The operator `+` is not supported for a Table
+                   ╭─[repl:1:1]
+                   │
+                 3 │ a + 1
+                   · ──┬──  
+                   ·   ╰──── The operator `+` is not supported for a Table
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_join() {
+        let rels = "let products := Tree[| pk id:Int, name:Str, price:Int; 1, 'Soda', 2; 2, 'Tea', 3; 3, 'Cake', 5 |]\nlet sales := [id:Int, qty:Int; 1, 4; 2, 1; 1, 2]\n";
+        check(
+            &format!("{rels}let names := products ?select #name\nlet qty := 10\ncross(names, qty)"),
+            expect![[r#"
+                [name:Str, it:Int; "Soda", 10; "Tea", 10; "Cake", 10]
+            "#]],
+        );
+        check(
+            &format!("{rels}inner(products, sales, #id)"),
+            expect![[r#"
+                [products.id:Int, name:Str, price:Int, sales.id:Int, qty:Int; 1, "Soda", 2, 1, 4; 1, "Soda", 2, 1, 2; 2, "Tea", 3, 2, 1]
+            "#]],
+        );
+        check(
+            &format!("{rels}left(products, sales, #id)"),
+            expect![[r#"
+                [products.id:Int, name:Str, price:Int, sales.id:Int?, qty:Int?; 1, "Soda", 2, 1, 4; 1, "Soda", 2, 1, 2; 2, "Tea", 3, 2, 1; 3, "Cake", 5, (), ()]
+            "#]],
+        );
+        check(
+            &format!("{rels}natural(products, sales)"),
+            expect![[r#"
+                [id:Int, name:Str, price:Int, qty:Int; 1, "Soda", 2, 4; 1, "Soda", 2, 2; 2, "Tea", 3, 1]
+            "#]],
+        );
+        check(
+            &format!("{rels}products.semi(sales, #id)"),
+            expect![[r#"
+                [id:Int, name:Str, price:Int; 1, "Soda", 2; 2, "Tea", 3]
+            "#]],
+        );
+        check(
+            &format!("{rels}anti(products, sales, #id)"),
+            expect![[r#"
+                [id:Int, name:Str, price:Int; 3, "Cake", 5]
+            "#]],
+        );
+        check(
+            &format!(
+                "{rels}left(products, sales, #id) ?where #sales.id = 1 ?select #products.id, #qty"
+            ),
+            expect![[r#"
+                [products.id:Int, qty:Int?; 1, 4; 1, 2]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_join_err() {
+        let rels = "let products := Tree[| pk id:Int, name:Str, price:Int; 1, 'Soda', 2; 2, 'Tea', 3; 3, 'Cake', 5 |]\nlet sales := [id:Int, qty:Int; 1, 4; 2, 1; 1, 2]\n";
+        check_err(
+            &format!("{rels}inner(products, sales)"),
+            expect![[r#"
+                [107] Error: Expected two relations & the columns to match, like `inner(a, b, #id)`: function = inner
+                   ╭─[repl:1:1]
+                   │
+                 3 │ inner(products, sales)
+                   · ──────────┬──────────  
+                   ·           ╰──────────── Expected two relations & the columns to match, like `inner(a, b, #id)`: function = inner
+                ───╯
+            "#]],
+        );
+        check_err(
+            &format!("{rels}inner(products, sales, #qty)"),
+            expect![[r#"
+                [106] Error: The column `#qty` is not in the schema
+                   ╭─[repl:1:1]
+                   │
+                 3 │ inner(products, sales, #qty)
+                   · ─────────────┬─────────────  
+                   ·              ╰─────────────── The column `#qty` is not in the schema
+                ───╯
+            "#]],
+        );
+        check_err(
+            &format!("{rels}cross(products)"),
+            expect![[r#"
+                [107] Error: Expected two relations & the columns to match, like `cross(a, b)`: function = cross
+                   ╭─[repl:1:1]
+                   │
+                 3 │ cross(products)
+                   · ───────┬──────  
+                   ·        ╰──────── Expected two relations & the columns to match, like `cross(a, b)`: function = cross
+                ───╯
+            "#]],
+        );
+    }
+
+    #[test]
+    fn eval_if_not_bool() {
+        // The parser only accept boolean checks, so build the AST directly
+        let span = Parser::from_src("").parse().ast.root().data.span();
+        let mut ast = Tree::new(Ast::Root(span));
+        ast.tree_root_mut().push(Ast::IfBlock {
+            if_span: span,
+            do_span: span,
+            else_span: span,
+            end_span: span,
+            check: Box::new(ExprBool::Scalar {
+                val: Scalar::I64([1]),
+                span,
+            }),
+            if_true: Box::new(Ast::Scalar {
+                val: Scalar::I64([1]),
+                span,
+            }),
+            if_false: Box::new(Ast::Scalar {
+                val: Scalar::I64([2]),
+                span,
+            }),
+        });
+        let parsed = Parsed {
+            ast,
+            errors: vec![],
+        };
+
+        let mut p = Program::new();
+        p.compile(&parsed).unwrap();
+
+        match p.eval() {
+            Code::Halt {
+                error: ErrorCode::Core { error, .. },
+                ..
+            } => assert_eq!(error.to_string(), "Type mismatch: expected Bool, found Int"),
+            x => panic!("Expected err, got: {x:?}"),
+        }
+    }
 }
@@ -1,9 +1,15 @@
-use crate::token::{CmpOp, Token};
+use crate::token::Token;
+use corelib::algebraic::Case;
 use corelib::errors::Span;
 use corelib::prelude::DataType;
 use corelib::scalar::Scalar;
+use corelib::schema::{Column, Schema};
+use corelib::utils::format_list;
 use std::fmt;
 
+pub use crate::token::{BinaryOp, CmpOp, LogicOp, QueryOp, UnaryOp};
+pub use corelib::query::Order;
+
 pub type Return = std::result::Result<Ast, ()>;
 
 impl From<&Token> for Span {
@@ -39,15 +45,43 @@ impl fmt::Display for Ty {
     }
 }
 
+/// A expression that must return a [Scalar::Bool]
 #[derive(Debug, Clone)]
 pub enum ExprBool {
-    Scalar { val: Scalar, span: Span },
+    Scalar {
+        val: Scalar,
+        span: Span,
+    },
+    /// Compare any values, following the total order of [Scalar]
+    Cmp {
+        op: CmpOp,
+        lhs: Box<Ast>,
+        rhs: Box<Ast>,
+        span: Span,
+    },
+    /// `and` & `or`, the `rhs` is only evaluated if the `lhs` not decide the result
+    Logic {
+        op: LogicOp,
+        lhs: Box<ExprBool>,
+        rhs: Box<ExprBool>,
+        span: Span,
+    },
+    Not {
+        expr: Box<ExprBool>,
+        span: Span,
+    },
+    /// A expression that is only know to be a boolean when evaluated, like a variable
+    Expr(Box<Ast>),
 }
 
 impl ExprBool {
-    pub(crate) fn span(&self) -> Span {
+    pub fn span(&self) -> Span {
         match self {
             ExprBool::Scalar { span, .. } => *span,
+            ExprBool::Cmp { span, .. } => *span,
+            ExprBool::Logic { span, .. } => *span,
+            ExprBool::Not { span, .. } => *span,
+            ExprBool::Expr(x) => x.span(),
         }
     }
 
@@ -59,6 +93,77 @@ impl ExprBool {
     }
 }
 
+/// The pattern of a `case` of a `match`
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// Match any value, binding it to `name`. The `else` of the `match` not bind it
+    Any { name: Option<String>, span: Span },
+    /// Match only the value, like `1` or `'a'`
+    Scalar { val: Scalar, span: Span },
+    /// `start..end`, the values of the same type from `start` until before `end`
+    Range {
+        start: Scalar,
+        end: Scalar,
+        span: Span,
+    },
+    /// `name(pattern, pattern)`, a case of a `enum` with the patterns of its fields
+    Case {
+        name: String,
+        fields: Vec<Pattern>,
+        span: Span,
+    },
+    /// `[| name: pattern |]`, a row of a relation with the patterns of some of its fields
+    Row {
+        fields: Vec<(String, Pattern)>,
+        span: Span,
+    },
+}
+
+impl Pattern {
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Any { span, .. } => *span,
+            Pattern::Scalar { span, .. } => *span,
+            Pattern::Range { span, .. } => *span,
+            Pattern::Case { span, .. } => *span,
+            Pattern::Row { span, .. } => *span,
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Any { name: Some(x), .. } => write!(f, "{}", x),
+            Pattern::Any { name: None, .. } => write!(f, "else"),
+            Pattern::Scalar { val, .. } => write!(f, "{}", val),
+            Pattern::Range { start, end, .. } => write!(f, "{}..{}", start, end),
+            Pattern::Case { name, fields, .. } => {
+                write!(f, "{}", name)?;
+                if !fields.is_empty() {
+                    format_list(fields, fields.len(), "(", ")", f)?;
+                }
+                Ok(())
+            }
+            Pattern::Row { fields, .. } => {
+                write!(f, "[|")?;
+                for (pos, (name, x)) in fields.iter().enumerate() {
+                    let sep = if pos == 0 { " " } else { ", " };
+                    write!(f, "{}{}: {}", sep, name, x)?;
+                }
+                write!(f, " |]")
+            }
+        }
+    }
+}
+
+/// A `case` of a `match`, the `body` is evaluated if the value match the `pattern`
+#[derive(Debug, Clone)]
+pub struct Arm {
+    pub pattern: Pattern,
+    pub body: Ast,
+}
+
 /// Encode the parse-tolerant AST
 #[derive(Debug, Clone)]
 pub enum Ast {
@@ -78,9 +183,99 @@ pub enum Ast {
         if_true: Box<Ast>,
         if_false: Box<Ast>,
     },
-
-    Cmp {
-        op: CmpOp,
+    /// The `span` cover from the `lhs` to the `rhs`
+    BinOp {
+        op: BinaryOp,
+        lhs: Box<Ast>,
+        rhs: Box<Ast>,
+        span: Span,
+    },
+    UnaryOp {
+        op: UnaryOp,
+        expr: Box<Ast>,
+        span: Span,
+    },
+    Bool(Box<ExprBool>),
+    /// Lookup the value of a variable
+    Ident {
+        name: String,
+        span: Span,
+    },
+    /// `let name := value`, a immutable binding
+    Let {
+        name: String,
+        value: Box<Ast>,
+        span: Span,
+    },
+    /// `var name := value`, a mutable binding
+    Var {
+        name: String,
+        value: Box<Ast>,
+        span: Span,
+    },
+    /// `name := value`, change the value of a `var`
+    Assign {
+        name: String,
+        value: Box<Ast>,
+        span: Span,
+    },
+    /// A sequence of expressions that open a new scope, return the last value
+    Block {
+        body: Vec<Ast>,
+        span: Span,
+    },
+    /// `[header; cell, cell; ...]`, the `rows` are checked to match the columns of the header.
+    ///
+    /// Without a header the `schema` is inferred when evaluated, as a single column `it`
+    Vector {
+        schema: Option<Schema>,
+        rows: Vec<Vec<Ast>>,
+        span: Span,
+    },
+    /// `Tree[| pk header; cell, cell; ... |]`, like a vector but the `schema` is required & has a key
+    Tree {
+        schema: Schema,
+        rows: Vec<Vec<Ast>>,
+        span: Span,
+    },
+    /// `#name` or `#0`, a column of the relation of the enclosing query
+    Column {
+        col: Column,
+        span: Span,
+    },
+    /// `#name desc`, a column of `?sort` with its order
+    Order {
+        col: Column,
+        order: Order,
+        span: Span,
+    },
+    /// `rel ?op arg, arg`, the columns in the `args` are resolved against the schema of `rel`
+    Query {
+        op: QueryOp,
+        rel: Box<Ast>,
+        args: Vec<Ast>,
+        span: Span,
+    },
+    /// `name(arg, arg)` or `arg.name(arg)`, the function is resolved when evaluated
+    Call {
+        name: String,
+        args: Vec<Ast>,
+        span: Span,
+    },
+    /// `enum name[params]: parent do case name(fields) end`, declare a sum type. The `parent`
+    /// is resolved when evaluated
+    Enum {
+        name: String,
+        params: Vec<String>,
+        parent: Option<String>,
+        cases: Vec<Case>,
+        span: Span,
+    },
+    /// `match value do case pattern do body ... end`, evaluate the body of the first case
+    /// that match the value. The cases are checked to cover all the values
+    Match {
+        value: Box<Ast>,
+        arms: Vec<Arm>,
         span: Span,
     },
     Pass(Span),
@@ -94,8 +289,42 @@ impl Ast {
             Ast::Scalar { val, span: _ } => Ty::Kind(val.kind()),
             Ast::Pass(_) => Ty::Unknown,
             Ast::Eof(_) => Ty::Ignore,
-            Ast::Cmp { .. } => Ty::Unknown,
+            Ast::Bool(_) => Ty::Kind(DataType::Bool),
+            Ast::Ident { .. } => Ty::Unknown,
+            Ast::Let { .. } | Ast::Var { .. } | Ast::Assign { .. } | Ast::Enum { .. } => Ty::Ignore,
+            Ast::Block { .. } => Ty::Unknown,
             Ast::IfBlock { .. } => Ty::Unknown,
+            Ast::BinOp { .. } => Ty::Unknown,
+            Ast::UnaryOp { .. } => Ty::Unknown,
+            Ast::Vector { .. } | Ast::Tree { .. } => Ty::Unknown,
+            Ast::Column { .. } | Ast::Order { .. } | Ast::Query { .. } => Ty::Unknown,
+            Ast::Call { .. } | Ast::Match { .. } => Ty::Unknown,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Ast::Root(span) => *span,
+            Ast::Scalar { span, .. } => *span,
+            Ast::IfBlock { if_span, .. } => *if_span,
+            Ast::BinOp { span, .. } => *span,
+            Ast::UnaryOp { span, .. } => *span,
+            Ast::Bool(x) => x.span(),
+            Ast::Ident { span, .. } => *span,
+            Ast::Let { span, .. } => *span,
+            Ast::Var { span, .. } => *span,
+            Ast::Assign { span, .. } => *span,
+            Ast::Block { span, .. } => *span,
+            Ast::Vector { span, .. } => *span,
+            Ast::Tree { span, .. } => *span,
+            Ast::Column { span, .. } => *span,
+            Ast::Order { span, .. } => *span,
+            Ast::Query { span, .. } => *span,
+            Ast::Call { span, .. } => *span,
+            Ast::Enum { span, .. } => *span,
+            Ast::Match { span, .. } => *span,
+            Ast::Pass(span) => *span,
+            Ast::Eof(span) => *span,
         }
     }
 
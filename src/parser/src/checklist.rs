@@ -1,6 +1,7 @@
 use crate::cst::CstNode;
-use crate::token::{BinaryOp, CmpOp, UnaryOp};
+use crate::token::{BinaryOp, CmpOp, LogicOp, UnaryOp};
 use corelib::prelude::Span;
+use corelib::query::Order;
 use corelib::types::DataType;
 use std::cmp::min;
 
@@ -18,6 +19,9 @@ pub enum Kw {
     Do,
     Else,
     End,
+    Enum,
+    Case,
+    Match,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -36,6 +40,14 @@ pub enum Step {
     UnaryOp(UnaryOp),
     BinOP(BinaryOp),
     CmpOp(CmpOp),
+    LogicOp(LogicOp),
+    Vector,
+    Sep,
+    Close,
+    Query,
+    Column,
+    Order(Order),
+    Call,
 }
 
 impl Step {
@@ -47,13 +59,24 @@ impl Step {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Task {
     Start,
+    #[allow(dead_code)]
     Scalar(DataType),
     Expr,
     IfExpr,
-    CmpExpr,
+    CmpExpr(CmpOp),
+    LogicOp(LogicOp),
     UnaryOp(UnaryOp),
     BinOp(BinaryOp),
+    DefLet,
     DefVar,
+    SetVar,
+    Block,
+    Vector,
+    Query,
+    Order(Order),
+    Call,
+    DefEnum,
+    Match,
 }
 
 impl Task {
@@ -62,8 +85,27 @@ impl Task {
             Task::Start => vec![Step::ExprIncomplete],
             Task::Scalar(_) => vec![Step::Expr],
             Task::Expr => vec![Step::Expr],
+            Task::DefLet => vec![Step::Kw(Kw::Let), Step::Ident, Step::Assign, Step::Expr],
             Task::DefVar => vec![Step::Kw(Kw::Var), Step::Ident, Step::Assign, Step::Expr],
-            Task::UnaryOp(op) => vec![Step::UnaryOp(*op), Step::Expr, Step::Expr],
+            Task::SetVar => vec![Step::Assign, Step::Ident, Step::Expr],
+            Task::Block => vec![Step::Kw(Kw::Do), Step::Expr, Step::Kw(Kw::End)],
+            Task::Vector => vec![Step::Vector, Step::Close],
+            Task::Query => vec![Step::Query, Step::Expr],
+            Task::Order(order) => vec![Step::Order(*order), Step::Expr],
+            Task::Call => vec![Step::Call, Step::Ident],
+            Task::DefEnum => vec![
+                Step::Kw(Kw::Enum),
+                Step::Ident,
+                Step::Kw(Kw::Do),
+                Step::Kw(Kw::End),
+            ],
+            Task::Match => vec![
+                Step::Kw(Kw::Match),
+                Step::Expr,
+                Step::Kw(Kw::Do),
+                Step::Kw(Kw::End),
+            ],
+            Task::UnaryOp(op) => vec![Step::UnaryOp(*op), Step::Expr],
             Task::BinOp(op) => vec![Step::BinOP(*op), Step::Expr, Step::Expr],
             Task::IfExpr => vec![
                 Step::Kw(Kw::If),
@@ -74,9 +116,8 @@ impl Task {
                 Step::Expr,
                 Step::Kw(Kw::End),
             ],
-            Task::CmpExpr => {
-                vec![Step::CmpOp(CmpOp::Equals), Step::Expr, Step::Expr]
-            }
+            Task::CmpExpr(op) => vec![Step::CmpOp(*op), Step::Expr, Step::Expr],
+            Task::LogicOp(op) => vec![Step::LogicOp(*op), Step::Expr, Step::Expr],
         }
     }
 }
@@ -112,6 +153,7 @@ impl CheckList {
         self.pos >= self.steps.len()
     }
 
+    #[allow(dead_code)]
     pub fn done(&self) -> &[Step] {
         let total = self.steps.len();
         &self.steps[0..min(self.pos, total)]
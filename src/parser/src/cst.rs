@@ -1,13 +1,14 @@
 //! The CST store a full-fidelity view of the code (even if wrong)
 use corelib::errors::Span;
 use std::fmt;
+use std::ops::Range;
 use std::rc::Rc;
 
 use crate::lexer::Scanner;
 use corelib::tree_flat::prelude::{Tree, TreeMut};
 
 use crate::pratt::S;
-use crate::pratt::{expr, Pratt};
+use crate::pratt::{expr, expr_at, Pratt};
 use crate::token::{Syntax, Token, TokenId};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -19,6 +20,36 @@ pub(crate) enum CstNode {
     Else(TokenId),
     Do(TokenId),
     End(TokenId),
+    Let(TokenId),
+    Var(TokenId),
+    Assign(TokenId),
+    /// `[`, the cells of the vector follow as children
+    Vector(TokenId),
+    /// `[|`, the type of the relation & the cells follow as children
+    Rel(TokenId),
+    /// `pk`, the field of the key follow as child
+    Pk(TokenId),
+    /// `name:Type`, the name & type follow as children
+    Field(TokenId),
+    /// `?op`, the relation & the arguments follow as children
+    Query(TokenId),
+    /// `asc` or `desc`, the column to sort follow as child
+    Order(TokenId),
+    /// `(` of a call, the name of the function & the arguments follow as children
+    Call(TokenId),
+    /// `enum`, the name, the parameters, the parent & the cases follow as children
+    Enum(TokenId),
+    /// `case` of a `enum`, the name & the types of the fields follow as children. In a `match`,
+    /// the pattern & the body
+    Case(TokenId),
+    /// `match`, the value & the cases follow as children
+    Match(TokenId),
+    /// `..` of a range pattern, the start & end follow as children
+    Range(TokenId),
+    /// `,` or `;` between the cells of a vector
+    Sep(TokenId),
+    /// `]` or `|]`
+    Close(TokenId),
     Err(TokenId),
     Eof(TokenId),
 }
@@ -34,6 +65,22 @@ impl CstNode {
             CstNode::Else(x) => x,
             CstNode::Do(x) => x,
             CstNode::End(x) => x,
+            CstNode::Let(x) => x,
+            CstNode::Var(x) => x,
+            CstNode::Assign(x) => x,
+            CstNode::Vector(x) => x,
+            CstNode::Rel(x) => x,
+            CstNode::Pk(x) => x,
+            CstNode::Field(x) => x,
+            CstNode::Query(x) => x,
+            CstNode::Order(x) => x,
+            CstNode::Call(x) => x,
+            CstNode::Enum(x) => x,
+            CstNode::Case(x) => x,
+            CstNode::Match(x) => x,
+            CstNode::Range(x) => x,
+            CstNode::Sep(x) => x,
+            CstNode::Close(x) => x,
             CstNode::Eof(x) => x,
         };
         *x
@@ -67,8 +114,12 @@ fn fmt_op(f: &mut fmt::Formatter<'_>, level: usize, code: &str, t: &Token) -> fm
         format!("BinOp {:?}", op)
     } else if let Some(op) = t.kind.to_unary_op() {
         format!("UnaryOp {:?}", op)
+    } else if let Some(op) = t.kind.to_cmp_op() {
+        format!("CmpOp {:?}", op)
+    } else if let Some(op) = t.kind.to_logic_op() {
+        format!("LogicOp {:?}", op)
     } else {
-        unreachable!()
+        t.kind.to_string()
     };
 
     write!(
@@ -113,7 +164,23 @@ impl fmt::Display for Cst<'_> {
                     let t = self.tokens.get(*t);
                     fmt_t(f, level, self.code, t)?
                 }
-                CstNode::End(t) => {
+                CstNode::End(t)
+                | CstNode::Let(t)
+                | CstNode::Var(t)
+                | CstNode::Assign(t)
+                | CstNode::Vector(t)
+                | CstNode::Rel(t)
+                | CstNode::Pk(t)
+                | CstNode::Field(t)
+                | CstNode::Query(t)
+                | CstNode::Order(t)
+                | CstNode::Call(t)
+                | CstNode::Enum(t)
+                | CstNode::Case(t)
+                | CstNode::Match(t)
+                | CstNode::Range(t)
+                | CstNode::Sep(t)
+                | CstNode::Close(t) => {
                     let t = self.tokens.get(*t);
                     fmt_t(f, level, self.code, t)?
                 }
@@ -129,18 +196,38 @@ fn push(tree: &mut TreeMut<CstNode>, t: CstNode) {
     tree.push(t);
 }
 
+fn keyword_node(kind: Syntax, t: TokenId) -> Option<CstNode> {
+    let node = match kind {
+        Syntax::IfKw => CstNode::If(t),
+        Syntax::ElseKw => CstNode::Else(t),
+        Syntax::DoKw => CstNode::Do(t),
+        Syntax::EndKw => CstNode::End(t),
+        Syntax::LetKw => CstNode::Let(t),
+        Syntax::VarKw => CstNode::Var(t),
+        Syntax::Assign => CstNode::Assign(t),
+        Syntax::LSquare => CstNode::Vector(t),
+        Syntax::Colon => CstNode::Field(t),
+        Syntax::Comma | Syntax::Semicolon => CstNode::Sep(t),
+        Syntax::LRel => CstNode::Rel(t),
+        Syntax::PkKw => CstNode::Pk(t),
+        Syntax::Query => CstNode::Query(t),
+        Syntax::AscKw | Syntax::DescKw => CstNode::Order(t),
+        Syntax::LParen => CstNode::Call(t),
+        Syntax::EnumKw => CstNode::Enum(t),
+        Syntax::CaseKw => CstNode::Case(t),
+        Syntax::MatchKw => CstNode::Match(t),
+        Syntax::DotDot => CstNode::Range(t),
+        Syntax::RSquare | Syntax::RRel => CstNode::Close(t),
+        _ => return None,
+    };
+    Some(node)
+}
+
 fn to_cst(tree: &mut TreeMut<CstNode>, tokens: &Scanner, ast: S) {
     match ast {
         S::Atom(t) => push(tree, CstNode::Atom(t)),
         S::Keyword(keyword, t) => {
-            let node = match keyword {
-                Syntax::IfKw => CstNode::If(t),
-                Syntax::ElseKw => CstNode::Else(t),
-                Syntax::DoKw => CstNode::Do(t),
-                Syntax::EndKw => CstNode::End(t),
-                _ => unreachable!("Invalid keyword"),
-            };
-
+            let node = keyword_node(keyword, t).expect("Invalid keyword");
             push(tree, node)
         }
         S::Block(rest) => {
@@ -151,13 +238,7 @@ fn to_cst(tree: &mut TreeMut<CstNode>, tokens: &Scanner, ast: S) {
 
         S::Cons(op, rest) => {
             let op = tokens.get(op);
-            let node = match op.kind {
-                Syntax::IfKw => CstNode::If(op.id),
-                Syntax::ElseKw => CstNode::Else(op.id),
-                Syntax::DoKw => CstNode::Do(op.id),
-                Syntax::EndKw => CstNode::End(op.id),
-                _ => CstNode::Op(op.id),
-            };
+            let node = keyword_node(op.kind, op.id).unwrap_or(CstNode::Op(op.id));
 
             let op = &mut tree.push(node);
             for s in rest {
@@ -190,6 +271,11 @@ pub(crate) fn src_to_cst(code: &str) -> Cst<'_> {
     parse(s)
 }
 
+/// The CST of the `range` of the `code`, with the positions of the tokens in the `code`
+pub(crate) fn src_range_to_cst(code: &str, range: Range<usize>) -> Cst<'_> {
+    parse(expr_at(code, range))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,6 +310,40 @@ Root
         );
     }
 
+    #[test]
+    fn define() {
+        check(
+            "let x := 1\nx := 2",
+            expect![[r#"
+                Root
+                  let @ 0..3 "let"
+                   Ident @ 4..5 "x"
+                   := @ 6..8 ":="
+                   Integer @ 9..10 "1"
+                  := @ 13..15 ":="
+                   Ident @ 11..12 "x"
+                   Integer @ 16..17 "2"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn vector() {
+        check(
+            "[x:Int; 1]",
+            expect![[r#"
+                Root
+                  [ @ 0..1 "["
+                   : @ 2..3 ":"
+                    Ident @ 1..2 "x"
+                    Ident @ 3..6 "Int"
+                   ; @ 6..7 ";"
+                   Integer @ 8..9 "1"
+                   ] @ 9..10 "]"
+            "#]],
+        );
+    }
+
     #[test]
     fn ifs() {
         check(
@@ -9,6 +9,12 @@ pub enum ErrorCode {
     ParseBoolExpr,
     ParseNoExpr,
     ParseIncomplete,
+    ParseVectorShape,
+    ParseVectorHeader,
+    ParseQuery,
+    ParseCall,
+    ParseEnum,
+    ParseMatch,
 }
 
 /// Define the main Error type for the parser
@@ -31,6 +37,38 @@ pub enum ErrorParser {
         err: CheckError,
         missing: Vec<Step>,
     },
+    /// A row of a vector with a different number of cells than the header, the `span`
+    /// point to the first extra cell or to the last one if is missing cells
+    VectorShape {
+        span: Span,
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    VectorHeader {
+        span: Span,
+        msg: String,
+    },
+    /// A relational operator that is unknown or with the wrong arguments
+    Query {
+        span: Span,
+        msg: String,
+    },
+    /// A call with a missing `)` or a method without the `(`, like `x.to_str`
+    Call {
+        span: Span,
+        msg: String,
+    },
+    /// A `enum` without cases or with a invalid name
+    Enum {
+        span: Span,
+        msg: String,
+    },
+    /// A invalid pattern, a case that is unreachable or a `match` that not cover all the values
+    Match {
+        span: Span,
+        msg: String,
+    },
 }
 
 impl ErrorParser {
@@ -40,6 +78,12 @@ impl ErrorParser {
             ErrorParser::BoolExpr { .. } => ErrorCode::ParseBoolExpr,
             ErrorParser::NoExpr { .. } => ErrorCode::ParseNoExpr,
             ErrorParser::Incomplete { .. } => ErrorCode::ParseIncomplete,
+            ErrorParser::VectorShape { .. } => ErrorCode::ParseVectorShape,
+            ErrorParser::VectorHeader { .. } => ErrorCode::ParseVectorHeader,
+            ErrorParser::Query { .. } => ErrorCode::ParseQuery,
+            ErrorParser::Call { .. } => ErrorCode::ParseCall,
+            ErrorParser::Enum { .. } => ErrorCode::ParseEnum,
+            ErrorParser::Match { .. } => ErrorCode::ParseMatch,
         }
     }
 
@@ -49,6 +93,12 @@ impl ErrorParser {
             ErrorParser::NoExpr { span, .. } => span,
             ErrorParser::ScalarParse { span, .. } => span,
             ErrorParser::Incomplete { err, .. } => &err.span,
+            ErrorParser::VectorShape { span, .. } => span,
+            ErrorParser::VectorHeader { span, .. } => span,
+            ErrorParser::Query { span, .. } => span,
+            ErrorParser::Call { span, .. } => span,
+            ErrorParser::Enum { span, .. } => span,
+            ErrorParser::Match { span, .. } => span,
         }
     }
 }
@@ -87,7 +137,7 @@ pub(crate) fn bool_expr(t: &Token, code: &str) -> ErrorParser {
 
 pub(crate) fn incomplete(t: &CheckList, found: CstNode) -> ErrorParser {
     let span: Span = t.span();
-    let missing = t.expect.into_iter().collect();
+    let missing = t.pending().to_vec();
     let err = CheckError {
         span,
         found,
@@ -95,3 +145,38 @@ pub(crate) fn incomplete(t: &CheckList, found: CstNode) -> ErrorParser {
     };
     ErrorParser::Incomplete { err, missing }
 }
+
+pub(crate) fn vector_header(span: Span, msg: &str) -> ErrorParser {
+    ErrorParser::VectorHeader {
+        span,
+        msg: msg.into(),
+    }
+}
+
+pub(crate) fn query(span: Span, msg: &str) -> ErrorParser {
+    ErrorParser::Query {
+        span,
+        msg: msg.into(),
+    }
+}
+
+pub(crate) fn call(span: Span, msg: &str) -> ErrorParser {
+    ErrorParser::Call {
+        span,
+        msg: msg.into(),
+    }
+}
+
+pub(crate) fn enums(span: Span, msg: &str) -> ErrorParser {
+    ErrorParser::Enum {
+        span,
+        msg: msg.into(),
+    }
+}
+
+pub(crate) fn pattern(span: Span, msg: &str) -> ErrorParser {
+    ErrorParser::Match {
+        span,
+        msg: msg.into(),
+    }
+}
@@ -0,0 +1,550 @@
+//! Check the cases of a `match`: all the values must be covered, and each case must match some
+//! value that the cases before it not.
+//!
+//! Follow the *usefulness* algorithm of "Warnings for pattern matching" (Maranget, 2007): a
+//! pattern is useful if match a value that none of the patterns before it match. A case that is
+//! not useful is unreachable, and the `match` cover all the values if a `else` after the
+//! last case is not useful.
+//!
+//! The patterns are checked as a matrix, one row by case. The fields of a case of a `enum` (or
+//! of a row) are expanded as new columns when the first column is *specialized* by it.
+use corelib::algebraic::{Case, SumType};
+use corelib::prelude::{Scalar, Span};
+
+use crate::ast::Pattern;
+
+/// The sum type that has all the cases `names`, the one with less cases if many have them, like
+/// `Value` & the `Value2: Value` that extend it
+pub(crate) fn find_enum<'a>(enums: &'a [SumType], names: &[&str]) -> Option<&'a SumType> {
+    enums
+        .iter()
+        .rev()
+        .filter(|x| names.iter().all(|name| x.case(name).is_some()))
+        .min_by_key(|x| x.cases.len())
+}
+
+/// The type at the start of the extensions of `of`, like the `Value` of `Value2: Value`
+fn root<'a>(enums: &'a [SumType], of: &'a SumType) -> &'a str {
+    let parent = of
+        .parent
+        .as_ref()
+        .and_then(|name| enums.iter().rev().find(|x| &x.name == name));
+    match parent {
+        Some(parent) => root(enums, parent),
+        None => &of.name,
+    }
+}
+
+/// The cases of the values that a `match` of the cases `names` can get: the ones of all the
+/// types that have them, so the cases of `Value` must also cover the ones added by the
+/// `Value2: Value` that extend it.
+///
+/// Fail with the position of the name if they are not of a single type
+fn find_cases<'a>(enums: &'a [SumType], names: &[&str]) -> Result<Vec<&'a Case>, (usize, String)> {
+    let has_all = |names: &[&str]| {
+        enums
+            .iter()
+            .filter(|x| names.iter().all(|name| x.case(name).is_some()))
+            .collect::<Vec<_>>()
+    };
+    let of = has_all(names);
+    let first = match of.first() {
+        Some(first) => first,
+        None => {
+            let pos = (0..names.len())
+                .find(|pos| has_all(&names[..=*pos]).is_empty())
+                .unwrap_or_default();
+            let name = names[pos];
+            let msg = if has_all(&[name]).is_empty() {
+                format!("The case `{name}` is not defined")
+            } else {
+                format!(
+                    "The case `{name}` is not of the same enum of `{}`",
+                    names[0]
+                )
+            };
+            return Err((pos, msg));
+        }
+    };
+    if let Some(other) = of.iter().find(|x| root(enums, x) != root(enums, first)) {
+        let msg = format!(
+            "The case `{}` is ambiguous, is in `{}` & `{}`",
+            names[0], first.name, other.name
+        );
+        return Err((0, msg));
+    }
+
+    let mut cases: Vec<&Case> = Vec::new();
+    for x in of.iter().flat_map(|x| &x.cases) {
+        if !cases.iter().any(|old| old.name == x.name) {
+            cases.push(x);
+        }
+    }
+    Ok(cases)
+}
+
+/// Check the cases in the same position of the `patterns` are of a single type, like the `Some`
+/// & `None` of `case Some(x)` & `case None`. Fail with the span of the first wrong case
+pub(crate) fn check_cases(enums: &[SumType], patterns: &[&Pattern]) -> Result<(), (Span, String)> {
+    let cases: Vec<_> = patterns
+        .iter()
+        .filter_map(|x| match x {
+            Pattern::Case { name, fields, span } => Some((name.as_str(), fields, *span)),
+            _ => None,
+        })
+        .collect();
+    if !cases.is_empty() {
+        let names: Vec<_> = cases.iter().map(|(name, ..)| *name).collect();
+        find_cases(enums, &names).map_err(|(pos, msg)| (cases[pos].2, msg))?;
+    }
+
+    // The fields of the same case, or with the same name in a row, are in the same position
+    let mut columns: Vec<(Field<'_>, Vec<&Pattern>)> = Vec::new();
+    for x in patterns {
+        let fields: Vec<_> = match x {
+            Pattern::Case { name, fields, .. } => fields
+                .iter()
+                .enumerate()
+                .map(|(pos, x)| (Field::Case(name, pos), x))
+                .collect(),
+            Pattern::Row { fields, .. } => fields
+                .iter()
+                .map(|(name, x)| (Field::Row(name), x))
+                .collect(),
+            _ => continue,
+        };
+        for (field, x) in fields {
+            match columns.iter_mut().find(|(of, _)| *of == field) {
+                Some((_, column)) => column.push(x),
+                None => columns.push((field, vec![x])),
+            }
+        }
+    }
+    columns
+        .iter()
+        .try_for_each(|(_, column)| check_cases(enums, column))
+}
+
+/// A field of the patterns, by the position in a case or the name in a row
+#[derive(Debug, PartialEq, Eq)]
+enum Field<'a> {
+    Case(&'a str, usize),
+    Row(&'a str),
+}
+
+/// A pattern in a column of the matrix
+#[derive(Debug, Clone, Copy)]
+enum Pat<'a> {
+    Any,
+    Of(&'a Pattern),
+}
+
+impl Pat<'_> {
+    fn is_any(&self) -> bool {
+        matches!(self, Pat::Any | Pat::Of(Pattern::Any { .. }))
+    }
+}
+
+/// The values a pattern match, without its fields
+#[derive(Debug, Clone)]
+enum Ctor<'a> {
+    /// The case `name` with its number of fields
+    Case(&'a str, usize),
+    Bool(bool),
+    Value(&'a Scalar),
+    Range(&'a Scalar, &'a Scalar),
+    /// A row, with the names of the fields used in all the patterns of the column
+    Row(Vec<&'a str>),
+}
+
+impl Ctor<'_> {
+    fn arity(&self) -> usize {
+        match self {
+            Ctor::Case(_, fields) => *fields,
+            Ctor::Row(names) => names.len(),
+            Ctor::Bool(_) | Ctor::Value(_) | Ctor::Range(..) => 0,
+        }
+    }
+}
+
+fn in_range(start: &Scalar, end: &Scalar, x: &Scalar) -> bool {
+    x.kind() == start.kind() && start <= x && x < end
+}
+
+struct Cover<'a> {
+    enums: &'a [SumType],
+    /// If the row patterns cover the rows that have its fields. The fields are only checked
+    /// when running, so for the [unreachable] cases a row pattern not cover the ones after it
+    rows: bool,
+}
+
+impl<'a> Cover<'a> {
+    /// The patterns of the first column, without the ones that match any value
+    fn column(matrix: &[Vec<Pat<'a>>]) -> Vec<&'a Pattern> {
+        matrix
+            .iter()
+            .filter_map(|row| match row[0] {
+                Pat::Of(Pattern::Any { .. }) | Pat::Any => None,
+                Pat::Of(x) => Some(x),
+            })
+            .collect()
+    }
+
+    fn row_names(column: &[&'a Pattern]) -> Vec<&'a str> {
+        let mut names: Vec<&str> = Vec::new();
+        for x in column {
+            if let Pattern::Row { fields, .. } = x {
+                for (name, _) in fields {
+                    if !names.contains(&name.as_str()) {
+                        names.push(name);
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    fn ctor(pattern: &'a Pattern, column: &[&'a Pattern]) -> Option<Ctor<'a>> {
+        let ctor = match pattern {
+            Pattern::Any { .. } => return None,
+            Pattern::Scalar {
+                val: Scalar::Bool([x]),
+                ..
+            } => Ctor::Bool(*x),
+            Pattern::Scalar { val, .. } => Ctor::Value(val),
+            Pattern::Range { start, end, .. } => Ctor::Range(start, end),
+            Pattern::Case { name, fields, .. } => Ctor::Case(name, fields.len()),
+            Pattern::Row { .. } => {
+                let mut column = column.to_vec();
+                column.push(pattern);
+                Ctor::Row(Self::row_names(&column))
+            }
+        };
+        Some(ctor)
+    }
+
+    /// All the constructors of the type, if the `column` use all of them
+    fn signature(&self, column: &[&'a Pattern]) -> Option<Vec<Ctor<'a>>> {
+        let names: Vec<&str> = column
+            .iter()
+            .filter_map(|x| match x {
+                Pattern::Case { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        if !names.is_empty() {
+            let cases = find_cases(self.enums, &names).ok()?;
+            let all = cases.iter().all(|x| names.contains(&x.name.as_str()));
+            return all.then(|| {
+                cases
+                    .iter()
+                    .map(|x| Ctor::Case(&x.name, x.fields.len()))
+                    .collect()
+            });
+        }
+        if column.iter().any(|x| matches!(x, Pattern::Row { .. })) {
+            return Some(vec![Ctor::Row(Self::row_names(column))]);
+        }
+        let has = |b: bool| {
+            column
+                .iter()
+                .any(|x| matches!(x, Pattern::Scalar { val: Scalar::Bool([x]), .. } if *x == b))
+        };
+        if has(true) && has(false) {
+            return Some(vec![Ctor::Bool(true), Ctor::Bool(false)]);
+        }
+        None
+    }
+
+    /// The `row` with the first pattern replaced by its fields, if it match the values of `ctor`.
+    /// Without `rows` a row pattern not match any value
+    fn specialize(row: &[Pat<'a>], ctor: &Ctor<'a>, rows: bool) -> Option<Vec<Pat<'a>>> {
+        let fields = match (row[0], ctor) {
+            (head, _) if head.is_any() => vec![Pat::Any; ctor.arity()],
+            (Pat::Of(Pattern::Row { .. }), _) if !rows => return None,
+            (Pat::Of(Pattern::Case { name, fields, .. }), Ctor::Case(x, arity)) if name == x => {
+                let extra = arity.saturating_sub(fields.len());
+                fields
+                    .iter()
+                    .map(Pat::Of)
+                    .chain(std::iter::repeat_n(Pat::Any, extra))
+                    .collect()
+            }
+            (
+                Pat::Of(Pattern::Scalar {
+                    val: Scalar::Bool([x]),
+                    ..
+                }),
+                Ctor::Bool(b),
+            ) if x == b => vec![],
+            (Pat::Of(Pattern::Scalar { val, .. }), Ctor::Value(x)) if val == *x => vec![],
+            (Pat::Of(Pattern::Range { start, end, .. }), Ctor::Value(x))
+                if in_range(start, end, x) =>
+            {
+                vec![]
+            }
+            // A range cover other if it include all its values
+            (Pat::Of(Pattern::Range { start, end, .. }), Ctor::Range(from, to))
+                if from.kind() == start.kind() && start <= *from && *to <= end =>
+            {
+                vec![]
+            }
+            (Pat::Of(Pattern::Row { fields, .. }), Ctor::Row(names)) => names
+                .iter()
+                .map(|name| {
+                    fields
+                        .iter()
+                        .find(|(x, _)| x == name)
+                        .map(|(_, x)| Pat::Of(x))
+                        .unwrap_or(Pat::Any)
+                })
+                .collect(),
+            _ => return None,
+        };
+        Some(fields.into_iter().chain(row[1..].iter().copied()).collect())
+    }
+
+    fn useful_ctor(&self, matrix: &[Vec<Pat<'a>>], row: &[Pat<'a>], ctor: &Ctor<'a>) -> bool {
+        let matrix: Vec<_> = matrix
+            .iter()
+            .filter_map(|x| Self::specialize(x, ctor, self.rows))
+            .collect();
+        match Self::specialize(row, ctor, true) {
+            Some(row) => self.useful(&matrix, &row),
+            None => false,
+        }
+    }
+
+    /// If the `row` match some values that none of the rows of the `matrix` match
+    fn useful(&self, matrix: &[Vec<Pat<'a>>], row: &[Pat<'a>]) -> bool {
+        let head = match row.first() {
+            Some(head) => *head,
+            None => return matrix.is_empty(),
+        };
+        let column = Self::column(matrix);
+
+        let ctor = match head {
+            Pat::Of(x) => Self::ctor(x, &column),
+            Pat::Any => None,
+        };
+        if let Some(ctor) = ctor {
+            return self.useful_ctor(matrix, row, &ctor);
+        }
+
+        match self.signature(&column) {
+            Some(all) => all.iter().any(|ctor| self.useful_ctor(matrix, row, ctor)),
+            // Only the rows that match any value can cover the values not in the column
+            None => {
+                let matrix: Vec<_> = matrix
+                    .iter()
+                    .filter(|x| x[0].is_any())
+                    .map(|x| x[1..].to_vec())
+                    .collect();
+                self.useful(&matrix, &row[1..])
+            }
+        }
+    }
+}
+
+/// The positions of the `patterns` that can't match a value, because the patterns before
+/// already match all of their values
+pub(crate) fn unreachable(enums: &[SumType], patterns: &[Pattern]) -> Vec<usize> {
+    let cover = Cover { enums, rows: false };
+    let mut matrix = Vec::with_capacity(patterns.len());
+    let mut dead = Vec::new();
+    for (pos, x) in patterns.iter().enumerate() {
+        let row = vec![Pat::Of(x)];
+        if !cover.useful(&matrix, &row) {
+            dead.push(pos);
+        }
+        matrix.push(row);
+    }
+    dead
+}
+
+/// The values that the `patterns` not match, like `Some(_)`. Is `None` if match all of them,
+/// and empty if the missing values can't be listed, like the numbers
+pub(crate) fn missing(enums: &[SumType], patterns: &[Pattern]) -> Option<Vec<String>> {
+    // A row without the fields is a error, so it not need a `else`
+    let cover = Cover { enums, rows: true };
+    let matrix: Vec<_> = patterns.iter().map(|x| vec![Pat::Of(x)]).collect();
+    if !cover.useful(&matrix, &[Pat::Any]) {
+        return None;
+    }
+
+    let column = Cover::column(&matrix);
+    let names: Vec<&str> = column
+        .iter()
+        .filter_map(|x| match x {
+            Pattern::Case { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let cases = if names.is_empty() {
+        None
+    } else {
+        find_cases(enums, &names).ok()
+    };
+    let ctors = match cases {
+        Some(cases) => cases
+            .iter()
+            .map(|x| Ctor::Case(&x.name, x.fields.len()))
+            .collect(),
+        None => vec![Ctor::Bool(true), Ctor::Bool(false)],
+    };
+    let has_bool = column.iter().any(|x| {
+        matches!(
+            x,
+            Pattern::Scalar {
+                val: Scalar::Bool(_),
+                ..
+            }
+        )
+    });
+
+    let missing = ctors
+        .iter()
+        .filter(|ctor| matches!(ctor, Ctor::Case(..)) || has_bool)
+        .filter(|ctor| cover.useful_ctor(&matrix, &[Pat::Any], ctor))
+        .map(|ctor| match ctor {
+            Ctor::Case(name, 0) => name.to_string(),
+            Ctor::Case(name, fields) => format!("{}({})", name, vec!["_"; *fields].join(", ")),
+            Ctor::Bool(x) => x.to_string(),
+            _ => unreachable!("Only the cases & booleans are listed"),
+        })
+        .collect();
+    Some(missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token::token_eof;
+    use corelib::algebraic::{Case, FieldKind};
+    use corelib::prelude::{dsl, Span};
+
+    fn span() -> Span {
+        (&token_eof()).into()
+    }
+
+    fn option() -> Vec<SumType> {
+        let t = FieldKind::Param("T".into());
+        let cases = [Case::new("None", &[]), Case::new("Some", &[t])];
+        vec![SumType::new("Option", &["T".into()], &cases).unwrap()]
+    }
+
+    fn any(name: &str) -> Pattern {
+        Pattern::Any {
+            name: Some(name.into()),
+            span: span(),
+        }
+    }
+
+    fn val(x: Scalar) -> Pattern {
+        Pattern::Scalar {
+            val: x,
+            span: span(),
+        }
+    }
+
+    fn case(name: &str, fields: &[Pattern]) -> Pattern {
+        Pattern::Case {
+            name: name.into(),
+            fields: fields.to_vec(),
+            span: span(),
+        }
+    }
+
+    fn range(start: i64, end: i64) -> Pattern {
+        Pattern::Range {
+            start: dsl::int(start),
+            end: dsl::int(end),
+            span: span(),
+        }
+    }
+
+    #[test]
+    fn cases() {
+        let enums = option();
+        let all = [case("Some", &[any("x")]), case("None", &[])];
+        assert_eq!(missing(&enums, &all), None);
+        assert!(unreachable(&enums, &all).is_empty());
+
+        let some = [case("Some", &[val(dsl::int(1))]), case("None", &[])];
+        assert_eq!(missing(&enums, &some), Some(vec!["Some(_)".into()]));
+
+        let dup = [case("None", &[]), any("x"), case("Some", &[any("y")])];
+        assert_eq!(missing(&enums, &dup), None);
+        assert_eq!(unreachable(&enums, &dup), [2]);
+    }
+
+    #[test]
+    fn extended() {
+        let value = SumType::new("Value", &[], &[Case::new("Txt", &[])]).unwrap();
+        let value2 = value
+            .extend("Value2", &[], &[Case::new("Num", &[])])
+            .unwrap();
+        let enums = vec![value, value2];
+        // The values of `Value2` also have the case `Txt` of `Value`
+        assert_eq!(
+            missing(&enums, &[case("Txt", &[])]),
+            Some(vec!["Num".into()])
+        );
+        assert_eq!(missing(&enums, &[case("Num", &[]), case("Txt", &[])]), None);
+
+        let other = SumType::new("Other", &[], &[Case::new("Txt", &[])]).unwrap();
+        let enums = [enums, vec![other]].concat();
+        let txt = case("Txt", &[]);
+        assert_eq!(
+            check_cases(&enums, &[&txt]).unwrap_err().1,
+            "The case `Txt` is ambiguous, is in `Value` & `Other`"
+        );
+        let (none, num) = (case("None", &[]), case("Num", &[]));
+        let mixed = [&num, &none];
+        assert_eq!(
+            check_cases(&[enums, option()].concat(), &mixed)
+                .unwrap_err()
+                .1,
+            "The case `None` is not of the same enum of `Num`"
+        );
+    }
+
+    #[test]
+    fn values() {
+        let enums = option();
+        let ints = [val(dsl::int(1)), range(0, 10), val(dsl::int(5))];
+        assert_eq!(missing(&enums, &ints), Some(vec![]));
+        assert_eq!(unreachable(&enums, &ints), [2]);
+
+        let ranges = [range(0, 10), range(2, 4), range(5, 20)];
+        assert_eq!(unreachable(&enums, &ranges), [1]);
+
+        let bools = [val(true.into()), val(false.into())];
+        assert_eq!(missing(&enums, &bools), None);
+        let bools = [val(true.into())];
+        assert_eq!(missing(&enums, &bools), Some(vec!["false".into()]));
+    }
+
+    #[test]
+    fn rows() {
+        let enums = option();
+        let row = |fields: &[(&str, Pattern)]| Pattern::Row {
+            fields: fields
+                .iter()
+                .map(|(name, x)| (name.to_string(), x.clone()))
+                .collect(),
+            span: span(),
+        };
+        let rows = [
+            row(&[("a", val(dsl::int(1)))]),
+            row(&[("a", val(dsl::int(1))), ("b", any("x"))]),
+            row(&[("a", any("x"))]),
+        ];
+        // The fields are only checked when running
+        assert!(unreachable(&enums, &rows).is_empty());
+        assert_eq!(missing(&enums, &rows), None);
+
+        let rows = [row(&[("a", any("x"))]), any("_"), row(&[("a", any("x"))])];
+        assert_eq!(unreachable(&enums, &rows), [2]);
+    }
+}
@@ -1,15 +1,26 @@
-use crate::ast::{Ast, ExprBool};
-use crate::checklist::{Kw, Step, Task};
-use crate::cst::CstNode;
+use crate::ast::{Arm, Ast, ExprBool, Pattern};
+use crate::checklist::{CheckError, Kw, Step, Task};
+use crate::cst::{src_range_to_cst, CstNode};
 use crate::errors;
-use crate::errors::{not_a_expr, ErrorParser};
+use crate::errors::{call, enums, not_a_expr, pattern, query, vector_header, ErrorParser};
+use crate::exhaustive::{self, find_enum};
 use crate::parser::Checker;
-use crate::token::{Syntax, Token};
-use corelib::prelude::{Decimal, Scalar, F64};
+use crate::token::{BinaryOp, CmpOp, LogicOp, QueryOp, SepOp, Syntax, Token, UnaryOp};
+use corelib::aggregate::Aggregate;
+use corelib::algebraic::{Case, FieldKind, SumType};
+use corelib::chrono::FixedOffset;
+use corelib::kernels::{self, Operand};
+use corelib::prelude::{Decimal, Interval, Money, Scalar, Span, F64};
+use corelib::query::Order;
 use corelib::scalar::{BitVec, DateKind};
+use corelib::schema::{Column, Field, Schema, FIELD_NAME_SCALAR};
+use corelib::text_size::{TextRange, TextSize};
 use corelib::tree_flat::node::NodeId;
 use corelib::types;
 use corelib::types::DataType;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::CharIndices;
 
 pub(crate) fn root(p: &mut Checker) {
     let mut parent = p.cst.ast.root().id;
@@ -54,10 +65,19 @@ fn check(p: &mut Checker, parent: NodeId) -> Result<NodeId, NodeId> {
 
     match &p.check.task {
         Task::Start => {
-            if let CstNode::Atom(t) = next {
-                p.new_task(Task::Expr, t);
-            }
-            if let CstNode::Op(t) = next {
+            if let CstNode::Atom(t)
+            | CstNode::Op(t)
+            | CstNode::Do(t)
+            | CstNode::Let(t)
+            | CstNode::Var(t)
+            | CstNode::Assign(t)
+            | CstNode::Vector(t)
+            | CstNode::Rel(t)
+            | CstNode::Query(t)
+            | CstNode::Call(t)
+            | CstNode::Enum(t)
+            | CstNode::Match(t) = next
+            {
                 p.new_task(Task::Expr, t);
             }
             if let CstNode::If(t) = next {
@@ -67,16 +87,16 @@ fn check(p: &mut Checker, parent: NodeId) -> Result<NodeId, NodeId> {
             return if p.check.task == Task::Start {
                 //if p.peek()
                 let _ = p.check.check(&next, Step::Expr, next.span(&p.cst.tokens));
+                // Skip it, so the parsing always make progress
+                p.advance();
                 Err(parent)
             } else {
                 check(p, parent)
             };
         }
         Task::Expr => {
-            if let CstNode::Atom(_) = &next {
-                let of = parse_scalar(p, parent, &next);
-                return p.push_or_err(of, parent);
-            }
+            let of = line(p, parent, next);
+            return p.push_or_err(of, parent);
         }
         Task::IfExpr => {
             if let CstNode::If(_) = &next {
@@ -111,8 +131,8 @@ fn clean_num(code: &str) -> String {
 }
 
 fn clean_quotes(code: &str) -> &str {
-    code.trim_start_matches(|x| x == '\'' || x == '"')
-        .trim_end_matches(|x| x == '\'' || x == '"')
+    code.trim_start_matches(['\'', '"'])
+        .trim_end_matches(['\'', '"'])
 }
 
 fn clean_prefix<'a>(code: &'a str, prefix: &str) -> &'a str {
@@ -123,10 +143,6 @@ fn clean_dates<'a>(code: &'a str, prefix: &str) -> &'a str {
     clean_quotes(clean_prefix(code, prefix))
 }
 
-fn clean_str(code: &str) -> String {
-    format!("\"{}\"", clean_quotes(code))
-}
-
 fn clean_floats(code: &str) -> String {
     if code.ends_with('d') || code.ends_with('f') {
         clean_num(&code[..code.len() - 1])
@@ -171,14 +187,209 @@ fn parse_f64(code: &str, t: &Token) -> Result<(Ast, Step), ErrorParser> {
     Ok((Ast::scalar(x.into(), t), Step::Dec))
 }
 
-fn parse_str(code: &str, t: &Token) -> Result<(Ast, Step), ErrorParser> {
-    let x = _parse_scalar::<String>(&clean_str(code), DataType::Utf8, t)?;
+/// The text between the quotes of a string, and where it start in the token.
+///
+/// A triple quoted string skip the line break after the opening quotes
+fn str_body(code: &str) -> (usize, &str) {
+    let quotes = if code.len() >= 6 && (code.starts_with("\"\"\"") || code.starts_with("'''")) {
+        3
+    } else {
+        1
+    };
+    let body = &code[quotes..code.len() - quotes];
+    let rest = body
+        .strip_prefix('\n')
+        .or_else(|| body.strip_prefix("\r\n"));
+    match rest {
+        Some(rest) if quotes == 3 => (code.len() - quotes - rest.len(), rest),
+        _ => (quotes, body),
+    }
+}
+
+/// The token for the part of the string at `pos`, so the errors point to it
+fn token_at(t: &Token, pos: usize, len: usize) -> Token {
+    let start = t.range.start() + TextSize::from(pos as u32);
+    Token {
+        range: TextRange::at(start, TextSize::from(len as u32)),
+        ..*t
+    }
+}
+
+/// Decode the `{1F600}` of a unicode escape, or tell the position where it stop being valid
+fn parse_unicode(chars: &mut Peekable<CharIndices<'_>>, start: usize) -> Result<char, usize> {
+    let mut end = start;
+    let mut hex = String::new();
+    if let Some((at, _)) = chars.next_if(|(_, x)| *x == '{') {
+        end = at;
+        while let Some((at, x)) = chars.next_if(|(_, x)| x.is_ascii_hexdigit()) {
+            end = at;
+            hex.push(x);
+        }
+        if let Some((at, _)) = chars.next_if(|(_, x)| *x == '}') {
+            end = at;
+            let code = u32::from_str_radix(&hex, 16).ok();
+            if let Some(x) = code.and_then(char::from_u32) {
+                return Ok(x);
+            }
+        }
+    }
+    Err(end)
+}
+
+/// Decode the escape after the `\` at `pos`, like `\n` or `\u{1F600}`
+fn parse_escape(
+    chars: &mut Peekable<CharIndices<'_>>,
+    pos: usize,
+    t: &Token,
+    kind: DataType,
+) -> Result<char, ErrorParser> {
+    let x = match chars.next() {
+        Some((_, 'n')) => '\n',
+        Some((_, 't')) => '\t',
+        Some((_, 'r')) => '\r',
+        Some((_, '0')) => '\0',
+        Some((_, x @ ('\\' | '\'' | '"' | '{' | '}'))) => x,
+        Some((start, 'u')) => parse_unicode(chars, start).map_err(|end| {
+            let msg = "Invalid unicode escape, expected one like `\\u{1F600}`";
+            errors::parse(&token_at(t, pos, end - start + 2), kind, msg)
+        })?,
+        found => {
+            let len = 1 + found.map(|(_, x)| x.len_utf8()).unwrap_or_default();
+            let msg =
+                "Invalid escape, expected one of `\\n \\t \\r \\0 \\\\ \\' \\\" \\{ \\} \\u{..}`";
+            return Err(errors::parse(&token_at(t, pos, len), kind, msg));
+        }
+    };
+    Ok(x)
+}
+
+/// The position of the `}` that close the interpolation, skipping the nested strings
+fn closing_brace(of: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (pos, x) in of.char_indices() {
+        match (quote, x) {
+            (Some(q), x) if x == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(x),
+            (None, '{') => depth += 1,
+            (None, '}') if depth == 0 => return Some(pos),
+            (None, '}') => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse the expression of `{expr}` inside a string, as `expr.to_str()`
+fn parse_interpolation(p: &Checker, range: Range<usize>, t: &Token) -> Result<Ast, ErrorParser> {
+    let braces = token_at(t, range.start - 1, range.len() + 2);
+    let start = usize::from(t.range.start());
+    let range = range.start + start..range.end + start;
+
+    let mut sub = Checker::new(src_range_to_cst(p.cst.code, range), p.offset);
+    sub.enums = p.enums.clone();
+    root(&mut sub);
+    if let Some(err) = sub.errors.into_iter().next() {
+        return Err(err);
+    }
+    // Skip the root
+    let mut lines = sub.ast.iter().skip(1).map(|x| x.data.clone());
+    match (lines.next(), lines.next()) {
+        (Some(expr), None) => Ok(Ast::Call {
+            name: "to_str".into(),
+            span: expr.span(),
+            args: vec![expr],
+        }),
+        _ => Err(errors::parse(
+            &braces,
+            DataType::Utf8,
+            "Expected a single expression inside `{}`",
+        )),
+    }
+}
+
+fn parse_money(code: &str, t: &Token) -> Result<(Ast, Step), ErrorParser> {
+    let x = _parse_scalar::<Money>(code, DataType::Money, t)?;
+    Ok((Ast::scalar(x.into(), t), Step::Dec))
+}
+
+/// Parse a string with its escapes, and the `{expr}` in it turned in the concatenation of
+/// the text with `expr.to_str()`
+fn parse_str(p: &Checker, t: &Token) -> Result<(Ast, Step), ErrorParser> {
+    let (start, body) = str_body(p.code(t));
+    let mut parts = Vec::new();
+    let mut text = String::with_capacity(body.len());
+
+    let mut chars = body.char_indices().peekable();
+    while let Some((pos, x)) = chars.next() {
+        match x {
+            '\\' => text.push(parse_escape(&mut chars, start + pos, t, DataType::Utf8)?),
+            '{' => {
+                let end = match closing_brace(&body[pos + 1..]) {
+                    Some(len) => pos + 1 + len,
+                    None => {
+                        let msg = "The `{` is not closed, write `\\{` for a literal brace";
+                        let t = token_at(t, start + pos, 1);
+                        return Err(errors::parse(&t, DataType::Utf8, msg));
+                    }
+                };
+                if !text.is_empty() {
+                    parts.push(Ast::scalar(std::mem::take(&mut text).into(), t));
+                }
+                parts.push(parse_interpolation(p, start + pos + 1..start + end, t)?);
+                while chars.next_if(|(at, _)| *at <= end).is_some() {}
+            }
+            '}' => {
+                let msg = "The `}` is not opened, write `\\}` for a literal brace";
+                let t = token_at(t, start + pos, 1);
+                return Err(errors::parse(&t, DataType::Utf8, msg));
+            }
+            x => text.push(x),
+        }
+    }
+    if !text.is_empty() || parts.is_empty() {
+        parts.push(Ast::scalar(text.into(), t));
+    }
+
+    let span: Span = t.into();
+    let mut parts = parts.into_iter();
+    let first = parts.next().expect("A string has at least one part");
+    let ast = parts.fold(first, |lhs, rhs| Ast::BinOp {
+        op: BinaryOp::Add,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+        span,
+    });
+    Ok((ast, Step::Str))
+}
+
+fn parse_char(code: &str, t: &Token) -> Result<(Ast, Step), ErrorParser> {
+    let mut text = String::new();
+    let mut chars = code[2..code.len() - 1].char_indices().peekable();
+    while let Some((pos, x)) = chars.next() {
+        match x {
+            '\\' => text.push(parse_escape(&mut chars, 2 + pos, t, DataType::Char)?),
+            x => text.push(x),
+        }
+    }
+    let mut chars = text.chars();
+    let x = match (chars.next(), chars.next()) {
+        (Some(x), None) => x,
+        _ => {
+            return Err(errors::parse(
+                t,
+                DataType::Char,
+                "A Char must be a single character, like c'a'",
+            ))
+        }
+    };
     Ok((Ast::scalar(x.into(), t), Step::Str))
 }
 
-fn parse_date(code: &str, t: &Token) -> Result<(Ast, Step), ErrorParser> {
+fn parse_date(code: &str, t: &Token, offset: &FixedOffset) -> Result<(Ast, Step), ErrorParser> {
     let x = clean_dates(code, "d");
-    let d = types::parse_date_t(x).map_err(|x| ErrorParser::ScalarParse {
+    let d = types::parse_date_at(x, offset).map_err(|x| ErrorParser::ScalarParse {
         kind: DataType::Date(DateKind::Date),
         span: t.into(),
         msg: x.to_string(),
@@ -196,9 +407,9 @@ fn parse_time(code: &str, t: &Token) -> Result<(Ast, Step), ErrorParser> {
     Ok((Ast::scalar(d.into(), t), Step::Date))
 }
 
-fn parse_datetime(code: &str, t: &Token) -> Result<(Ast, Step), ErrorParser> {
+fn parse_datetime(code: &str, t: &Token, offset: &FixedOffset) -> Result<(Ast, Step), ErrorParser> {
     let x = clean_dates(code, "dt");
-    let d = types::parse_date_time_t(x).map_err(|x| ErrorParser::ScalarParse {
+    let d = types::parse_date_time_at(x, offset).map_err(|x| ErrorParser::ScalarParse {
         kind: DataType::Date(DateKind::DateTime),
         span: t.into(),
         msg: x.to_string(),
@@ -206,6 +417,38 @@ fn parse_datetime(code: &str, t: &Token) -> Result<(Ast, Step), ErrorParser> {
     Ok((Ast::scalar(d.into(), t), Step::Date))
 }
 
+fn parse_interval(code: &str, t: &Token) -> Result<(Ast, Step), ErrorParser> {
+    let x = clean_dates(code, "i");
+    let d = x
+        .parse::<Interval>()
+        .map_err(|msg| ErrorParser::ScalarParse {
+            kind: DataType::Interval,
+            span: t.into(),
+            msg,
+        })?;
+    Ok((Ast::scalar(d.into(), t), Step::Date))
+}
+
+/// `#name` or `#0`, a column by name or position
+fn parse_column(code: &str, t: &Token) -> Result<(Ast, Step), ErrorParser> {
+    let name = clean_prefix(code, "#");
+    let col = if name.starts_with(|x: char| x.is_ascii_digit()) {
+        let pos = name
+            .parse::<usize>()
+            .map_err(|x| errors::parse(t, DataType::I64, &x.to_string()))?;
+        Column::Pos(pos)
+    } else {
+        Column::Name(name.into())
+    };
+    Ok((
+        Ast::Column {
+            col,
+            span: t.into(),
+        },
+        Step::Column,
+    ))
+}
+
 pub(crate) fn parse_scalar(
     p: &mut Checker,
     _parent: NodeId,
@@ -219,12 +462,23 @@ pub(crate) fn parse_scalar(
         Syntax::Bool => parse_bool(code, t)?,
         Syntax::Integer => parse_i64(code, t)?,
         Syntax::Decimal => parse_d64(code, t)?,
+        Syntax::Money => parse_money(code, t)?,
         Syntax::Float => parse_f64(code, t)?,
         Syntax::Bit => parse_bit(code, t)?,
-        Syntax::String => parse_str(code, t)?,
-        Syntax::Date => parse_date(code, t)?,
+        Syntax::String => parse_str(p, t)?,
+        Syntax::Char => parse_char(code, t)?,
+        Syntax::Date => parse_date(code, t, &p.offset)?,
         Syntax::Time => parse_time(code, t)?,
-        Syntax::DateTime => parse_datetime(code, t)?,
+        Syntax::DateTime => parse_datetime(code, t, &p.offset)?,
+        Syntax::Interval => parse_interval(code, t)?,
+        Syntax::Ident => (
+            Ast::Ident {
+                name: code.into(),
+                span,
+            },
+            Step::Ident,
+        ),
+        Syntax::Column => parse_column(code, t)?,
         x => unimplemented!("{:?}", x),
     };
     p.check.check(node, step, span)?;
@@ -233,25 +487,226 @@ pub(crate) fn parse_scalar(
 
 /// A valid boolean expression is one of:
 /// true | false
+/// A variable
 /// A [CmpOp] between expressions
+/// `and`, `or` & `not` of boolean expressions
+/// A vector of booleans, so the operators are applied to each value
 /// A function that return a boolean expression
-pub(crate) fn parse_bool_expr(p: &mut Checker, node: &CstNode) -> Result<ExprBool, ErrorParser> {
-    let t = p.token(node.token_id());
-    let code = p.code(t);
+pub(crate) fn parse_bool_expr(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+) -> Result<ExprBool, ErrorParser> {
+    let t = *p.token(node.token_id());
+    let code = p.code(&t);
 
-    if let CstNode::Atom(_) = node {
-        if t.kind == Syntax::Bool {
-            let x = _parse_scalar::<bool>(code, DataType::Bool, t)?;
-            return Ok(ExprBool::bool(x, t));
+    match node {
+        CstNode::Atom(_) if t.kind == Syntax::Bool => {
+            let x = _parse_scalar::<bool>(code, DataType::Bool, &t)?;
+            Ok(ExprBool::bool(x, &t))
+        }
+        // The logic operators are bitwise for bits
+        CstNode::Atom(_) if t.kind == Syntax::Bit => {
+            let (ast, _) = parse_bit(code, &t)?;
+            Ok(ExprBool::Expr(Box::new(ast)))
+        }
+        // The type of the variable is only know when evaluated
+        CstNode::Atom(_) if t.kind == Syntax::Ident => {
+            let ast = Ast::Ident {
+                name: code.into(),
+                span: (&t).into(),
+            };
+            Ok(ExprBool::Expr(Box::new(ast)))
+        }
+        // Like a variable, but the value is from the row of the query
+        CstNode::Atom(_) if t.kind == Syntax::Column => {
+            let (ast, _) = parse_column(code, &t)?;
+            Ok(ExprBool::Expr(Box::new(ast)))
+        }
+        CstNode::Vector(_) => {
+            let span = node.span(&p.cst.tokens);
+            let ast = p.sub_task(Task::Vector, span, |p| parse_vector(p, parent, node))?;
+            Ok(ExprBool::Expr(Box::new(ast)))
         }
+        CstNode::Call(_) => {
+            let span = node.span(&p.cst.tokens);
+            let ast = p.sub_task(Task::Call, span, |p| parse_call(p, parent, node))?;
+            Ok(ExprBool::Expr(Box::new(ast)))
+        }
+        CstNode::Op(_) => {
+            let span: Span = (&t).into();
+            if let Some(op) = t.kind.to_cmp_op() {
+                p.sub_task(Task::CmpExpr(op), span, |p| parse_cmp(p, parent, node, op))
+            } else if let Some(op) = t.kind.to_logic_op() {
+                p.sub_task(Task::LogicOp(op), span, |p| {
+                    parse_logic(p, parent, node, op)
+                })
+            } else if t.kind == Syntax::NotKw {
+                p.sub_task(Task::UnaryOp(UnaryOp::Not), span, |p| {
+                    parse_not(p, parent, node)
+                })
+            } else {
+                Err(errors::bool_expr(&t, code))
+            }
+        }
+        _ => Err(errors::bool_expr(&t, code)),
     }
+}
+
+/// Parse a operand of a boolean operator, that must be also a boolean expression
+fn bool_operand(p: &mut Checker, parent: NodeId) -> Result<ExprBool, ErrorParser> {
+    let next = p.advance_and_next();
+    let expr = parse_bool_expr(p, parent, &next)?;
+    p.check.check(&next, Step::Expr, expr.span())?;
+    Ok(expr)
+}
+
+fn parse_cmp(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+    op: CmpOp,
+) -> Result<ExprBool, ErrorParser> {
+    p.check
+        .check(node, Step::CmpOp(op), node.span(&p.cst.tokens))?;
+
+    let next = p.advance_and_next();
+    let lhs = expr(p, parent, next)?;
 
-    Err(errors::bool_expr(t, code))
+    let next = p.advance_and_next();
+    let rhs = expr(p, parent, next)?;
+
+    Ok(ExprBool::Cmp {
+        op,
+        span: lhs.span().cover(&rhs.span()),
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    })
+}
+
+fn parse_logic(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+    op: LogicOp,
+) -> Result<ExprBool, ErrorParser> {
+    p.check
+        .check(node, Step::LogicOp(op), node.span(&p.cst.tokens))?;
+
+    let lhs = bool_operand(p, parent)?;
+    let rhs = bool_operand(p, parent)?;
+
+    Ok(ExprBool::Logic {
+        op,
+        span: lhs.span().cover(&rhs.span()),
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    })
+}
+
+fn parse_not(p: &mut Checker, parent: NodeId, node: &CstNode) -> Result<ExprBool, ErrorParser> {
+    let span = node.span(&p.cst.tokens);
+    p.check.check(node, Step::UnaryOp(UnaryOp::Not), span)?;
+
+    let expr = bool_operand(p, parent)?;
+
+    Ok(ExprBool::Not {
+        span: span.cover(&expr.span()),
+        expr: Box::new(expr),
+    })
+}
+
+/// Parse a line of a block: a declaration or an expression
+pub(crate) fn line(p: &mut Checker, parent: NodeId, next: CstNode) -> Result<Ast, ErrorParser> {
+    let span = next.span(&p.cst.tokens);
+    let ast = match next {
+        CstNode::Let(_) => p.sub_task(Task::DefLet, span, |p| {
+            parse_define(p, parent, &next, Kw::Let)
+        })?,
+        CstNode::Var(_) => p.sub_task(Task::DefVar, span, |p| {
+            parse_define(p, parent, &next, Kw::Var)
+        })?,
+        CstNode::Assign(_) => p.sub_task(Task::SetVar, span, |p| parse_set(p, parent, &next))?,
+        CstNode::Enum(_) => p.sub_task(Task::DefEnum, span, |p| parse_enum(p, &next))?,
+        _ => return expr(p, parent, next),
+    };
+    p.check.check(&next, Step::Expr, ast.span())?;
+    Ok(ast)
 }
 
 pub(crate) fn expr(p: &mut Checker, parent: NodeId, next: CstNode) -> Result<Ast, ErrorParser> {
     match next {
         CstNode::Atom(_) => parse_scalar(p, parent, &next),
+        CstNode::Do(_) => {
+            let span = next.span(&p.cst.tokens);
+            let ast = p.sub_task(Task::Block, span, |p| parse_do(p, parent, &next))?;
+            p.check.check(&next, Step::Expr, ast.span())?;
+            Ok(ast)
+        }
+        CstNode::If(_) => {
+            let span = next.span(&p.cst.tokens);
+            let ast = p.sub_task(Task::IfExpr, span, |p| parse_if(p, parent, &next))?;
+            p.check.check(&next, Step::Expr, ast.span())?;
+            Ok(ast)
+        }
+        CstNode::Match(_) => {
+            let span = next.span(&p.cst.tokens);
+            let ast = p.sub_task(Task::Match, span, |p| parse_match(p, parent, &next))?;
+            p.check.check(&next, Step::Expr, ast.span())?;
+            Ok(ast)
+        }
+        CstNode::Vector(_) => {
+            let span = next.span(&p.cst.tokens);
+            let ast = p.sub_task(Task::Vector, span, |p| parse_vector(p, parent, &next))?;
+            p.check.check(&next, Step::Expr, ast.span())?;
+            Ok(ast)
+        }
+        CstNode::Rel(_) => {
+            let span = next.span(&p.cst.tokens);
+            let ast = p.sub_task(Task::Vector, span, |p| parse_tree(p, parent, &next))?;
+            p.check.check(&next, Step::Expr, ast.span())?;
+            Ok(ast)
+        }
+        CstNode::Query(_) => {
+            let span = next.span(&p.cst.tokens);
+            let ast = p.sub_task(Task::Query, span, |p| parse_query(p, parent, &next))?;
+            p.check.check(&next, Step::Expr, ast.span())?;
+            Ok(ast)
+        }
+        CstNode::Call(_) => {
+            let span = next.span(&p.cst.tokens);
+            let ast = p.sub_task(Task::Call, span, |p| parse_call(p, parent, &next))?;
+            p.check.check(&next, Step::Expr, ast.span())?;
+            Ok(ast)
+        }
+        CstNode::Op(t) => {
+            let t = *p.token(t);
+            if t.kind == Syntax::Point {
+                p.cursor = p.last_child();
+                return Err(call(
+                    (&t).into(),
+                    "Expected a call after the `.`, like `x.to_str()`",
+                ));
+            }
+            if t.kind.to_cmp_op().is_some()
+                || t.kind.to_logic_op().is_some()
+                || t.kind == Syntax::NotKw
+            {
+                let ast = Ast::Bool(Box::new(parse_bool_expr(p, parent, &next)?));
+                p.check.check(&next, Step::Expr, ast.span())?;
+                return Ok(ast);
+            }
+            let task = if let Some(op) = t.kind.to_unary_op() {
+                Task::UnaryOp(op)
+            } else if let Some(op) = t.kind.to_bin_op() {
+                Task::BinOp(op)
+            } else {
+                return Err(not_a_expr(&t, p.code(&t)));
+            };
+            let ast = p.sub_task(task, (&t).into(), |p| parse_op(p, parent, &next))?;
+            p.check.check(&next, Step::Expr, ast.span())?;
+            Ok(ast)
+        }
         _ => {
             let t = p.token(next.token_id());
             let code = p.code(t);
@@ -260,6 +715,731 @@ pub(crate) fn expr(p: &mut Checker, parent: NodeId, next: CstNode) -> Result<Ast
     }
 }
 
+/// Check the `node` is the one expected by `step`, like the `:=` in a declaration
+fn expect_node(
+    p: &mut Checker,
+    node: &CstNode,
+    step: Step,
+    is_expected: bool,
+) -> Result<Span, ErrorParser> {
+    let span = node.span(&p.cst.tokens);
+    if is_expected {
+        p.check.check(node, step, span)?;
+        Ok(span)
+    } else {
+        Err(CheckError {
+            span,
+            found: *node,
+            expect: Some(step),
+        }
+        .into())
+    }
+}
+
+/// The name of a variable
+fn parse_ident(p: &mut Checker, node: &CstNode) -> Result<String, ErrorParser> {
+    let t = *p.token(node.token_id());
+    expect_node(p, node, Step::Ident, t.kind == Syntax::Ident)?;
+    Ok(p.code(&t).into())
+}
+
+/// let|var name := value
+fn parse_define(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+    kw: Kw,
+) -> Result<Ast, ErrorParser> {
+    let span = node.span(&p.cst.tokens);
+    p.check.check(node, Step::Kw(kw), span)?;
+
+    let next = p.advance_and_next();
+    let name = parse_ident(p, &next)?;
+
+    let next = p.advance_and_next();
+    expect_node(p, &next, Step::Assign, matches!(next, CstNode::Assign(_)))?;
+
+    let next = p.advance_and_next();
+    let value = Box::new(expr(p, parent, next)?);
+    let span = span.cover(&value.span());
+
+    Ok(if kw == Kw::Let {
+        Ast::Let { name, value, span }
+    } else {
+        Ast::Var { name, value, span }
+    })
+}
+
+/// name := value
+fn parse_set(p: &mut Checker, parent: NodeId, node: &CstNode) -> Result<Ast, ErrorParser> {
+    p.check
+        .check(node, Step::Assign, node.span(&p.cst.tokens))?;
+
+    let next = p.advance_and_next();
+    let span = next.span(&p.cst.tokens);
+    let name = parse_ident(p, &next)?;
+
+    let next = p.advance_and_next();
+    let value = Box::new(expr(p, parent, next)?);
+    let span = span.cover(&value.span());
+
+    Ok(Ast::Assign { name, value, span })
+}
+
+/// Parse the lines of a block until found the `else`/`end` (or the next `case` of a `match`)
+/// that close it.
+///
+/// The cursor must be on the keyword that open the block
+fn parse_body(p: &mut Checker, parent: NodeId) -> Result<Vec<Ast>, ErrorParser> {
+    let mut body = Vec::new();
+    loop {
+        if let CstNode::Else(_) | CstNode::End(_) | CstNode::Case(_) | CstNode::Eof(_) = p.peek() {
+            break;
+        }
+        let next = p.advance_and_next();
+        let span = next.span(&p.cst.tokens);
+        body.push(p.sub_task(Task::Expr, span, |p| line(p, parent, next))?);
+    }
+
+    if body.is_empty() {
+        let t = *p.token(p.peek().token_id());
+        return Err(not_a_expr(&t, p.code(&t)));
+    }
+    Ok(body)
+}
+
+/// The body of a `if`, a single expression or a [Ast::Block] for many
+fn parse_branch(p: &mut Checker, parent: NodeId) -> Result<Ast, ErrorParser> {
+    let mut body = parse_body(p, parent)?;
+    if body.len() == 1 {
+        return Ok(body.pop().unwrap());
+    }
+
+    let span = body[0].span().cover(&body[body.len() - 1].span());
+    Ok(Ast::Block { body, span })
+}
+
+/// do block end
+pub(crate) fn parse_do(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+) -> Result<Ast, ErrorParser> {
+    let span = node.span(&p.cst.tokens);
+    p.check.check(node, Step::Kw(Kw::Do), span)?;
+
+    let body = parse_body(p, parent)?;
+    let body_span = body[0].span().cover(&body[body.len() - 1].span());
+    p.check.check(node, Step::Expr, body_span)?;
+
+    let next = p.advance_and_next();
+    let end_span = expect_node(p, &next, Step::Kw(Kw::End), matches!(next, CstNode::End(_)))?;
+
+    Ok(Ast::Block {
+        body,
+        span: span.cover(&end_span),
+    })
+}
+
+/// Parse an unary or binary operator, the operands follow it in the CST
+pub(crate) fn parse_op(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+) -> Result<Ast, ErrorParser> {
+    let t = *p.token(node.token_id());
+    let op_span: Span = (&t).into();
+
+    if let Some(op) = t.kind.to_unary_op() {
+        p.check.check(node, Step::UnaryOp(op), op_span)?;
+
+        let next = p.advance_and_next();
+        let expr = expr(p, parent, next)?;
+
+        return Ok(Ast::UnaryOp {
+            op,
+            span: op_span.cover(&expr.span()),
+            expr: Box::new(expr),
+        });
+    }
+
+    let op = t.kind.to_bin_op().expect("Is a binary operator");
+    p.check.check(node, Step::BinOP(op), op_span)?;
+
+    let next = p.advance_and_next();
+    let lhs = expr(p, parent, next)?;
+
+    let next = p.advance_and_next();
+    let rhs = expr(p, parent, next)?;
+
+    Ok(Ast::BinOp {
+        op,
+        span: lhs.span().cover(&rhs.span()),
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    })
+}
+
+/// A cell of a vector literal: a column of the header or a value
+enum Cell {
+    Header {
+        name: Option<String>,
+        kind: DataType,
+        /// Is marked as the key with `pk`
+        pk: bool,
+        span: Span,
+    },
+    Value(Ast),
+}
+
+impl Cell {
+    fn is_header(&self) -> bool {
+        matches!(self, Cell::Header { .. })
+    }
+}
+
+/// A column of the header, `Type`, `name:Type` or the key `pk name:Type`. Any other node is a value
+fn parse_header(p: &mut Checker, node: &CstNode) -> Result<Option<Cell>, ErrorParser> {
+    let t = *p.token(node.token_id());
+    match node {
+        CstNode::Atom(_) if t.kind == Syntax::Ident => {
+            let cell = DataType::from_name(p.code(&t)).map(|kind| Cell::Header {
+                name: None,
+                kind,
+                pk: false,
+                span: (&t).into(),
+            });
+            Ok(cell)
+        }
+        CstNode::Pk(_) => {
+            let next = p.advance_and_next();
+            match parse_header(p, &next)? {
+                Some(Cell::Header {
+                    name, kind, span, ..
+                }) => Ok(Some(Cell::Header {
+                    name,
+                    kind,
+                    pk: true,
+                    span: Span::from(&t).cover(&span),
+                })),
+                _ => Err(vector_header(
+                    next.span(&p.cst.tokens),
+                    "Expected a column after `pk`, like `pk id:Int`",
+                )),
+            }
+        }
+        CstNode::Field(_) => {
+            let next = p.advance_and_next();
+            let name = *p.token(next.token_id());
+            if !matches!(next, CstNode::Atom(_)) || name.kind != Syntax::Ident {
+                return Err(CheckError {
+                    span: next.span(&p.cst.tokens),
+                    found: next,
+                    expect: Some(Step::Ident),
+                }
+                .into());
+            }
+
+            let next = p.advance_and_next();
+            let of = *p.token(next.token_id());
+            let code = p.code(&of);
+            let kind = match next {
+                CstNode::Atom(_) => DataType::from_name(code),
+                _ => None,
+            };
+            let kind =
+                kind.ok_or_else(|| vector_header((&of).into(), &format!("Unknown type `{code}`")))?;
+
+            Ok(Some(Cell::Header {
+                name: Some(p.code(&name).into()),
+                kind,
+                pk: false,
+                span: Span::from(&name).cover(&(&of).into()),
+            }))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Collect the cells of the vector by row, until the `]` (or `|]`) at `last`.
+///
+/// Return the rows & the span of the close
+fn parse_rows(
+    p: &mut Checker,
+    parent: NodeId,
+    open: Span,
+    last: usize,
+) -> Result<(Vec<Vec<Cell>>, Span), ErrorParser> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    // A cell is expected at the start & after a separator, except at the end for a trailing `;`
+    let mut need_cell = true;
+    let mut after_comma = false;
+
+    while p.cursor < last {
+        let next = p.advance_and_next();
+        let span = next.span(&p.cst.tokens);
+        match next {
+            CstNode::Sep(t) => {
+                let t = *p.token(t);
+                if need_cell {
+                    return Err(not_a_expr(&t, p.code(&t)));
+                }
+                let sep = t.kind.to_separator();
+                if sep == SepOp::Semicolon {
+                    rows.push(std::mem::take(&mut row));
+                }
+                need_cell = true;
+                after_comma = sep == SepOp::Comma;
+            }
+            CstNode::Close(t) => {
+                if need_cell && after_comma {
+                    let t = *p.token(t);
+                    return Err(not_a_expr(&t, p.code(&t)));
+                }
+                if !row.is_empty() {
+                    rows.push(row);
+                }
+                p.check.check(&next, Step::Close, span)?;
+                return Ok((rows, span));
+            }
+            _ => {
+                if !need_cell {
+                    return Err(CheckError {
+                        span,
+                        found: next,
+                        expect: Some(Step::Sep),
+                    }
+                    .into());
+                }
+                let cell = match parse_header(p, &next)? {
+                    Some(cell) => cell,
+                    None => Cell::Value(p.sub_task(Task::Expr, span, |p| expr(p, parent, next))?),
+                };
+                row.push(cell);
+                need_cell = false;
+            }
+        }
+    }
+
+    Err(CheckError {
+        span: open,
+        found: p.next(),
+        expect: Some(Step::Close),
+    }
+    .into())
+}
+
+/// Build the [Schema] from the header. Without it, only a single column can be inferred.
+///
+/// When `keyed` the header must mark the key with `pk`, else `pk` is not allowed.
+fn vector_schema(
+    header: Option<Vec<Cell>>,
+    rows: &[Vec<Ast>],
+    span: Span,
+    keyed: bool,
+) -> Result<Option<Schema>, ErrorParser> {
+    let need_key = || {
+        vector_header(
+            span,
+            "A `Tree` need a key, add `pk` to a column like `Tree[| pk id:Int |]`",
+        )
+    };
+
+    let header = match header {
+        Some(header) => header,
+        None if keyed => return Err(need_key()),
+        None => {
+            return match rows.first() {
+                None => Err(vector_header(
+                    span,
+                    "Can't infer the type of a empty vector, add a header like `[Int]`",
+                )),
+                Some(row) if row.len() > 1 => Err(vector_header(
+                    row[1].span(),
+                    "Can't infer the names of many columns, add a header like `[a:Int, b:Int]`",
+                )),
+                Some(_) => Ok(None),
+            };
+        }
+    };
+
+    let total = header.len();
+    let mut fields: Vec<Field> = Vec::with_capacity(total);
+    let mut key = Vec::new();
+    for cell in header {
+        if let Cell::Header {
+            name,
+            kind,
+            pk,
+            span,
+        } = cell
+        {
+            if pk {
+                if !keyed {
+                    return Err(vector_header(
+                        span,
+                        "Only a `Tree` can have a key, like `Tree[| pk id:Int |]`",
+                    ));
+                }
+                // Many `pk` columns are a composite key, in the order of the columns
+                key.push(fields.len());
+            }
+            match name {
+                Some(name) if fields.iter().any(|x| x.name == name) => {
+                    return Err(vector_header(
+                        span,
+                        &format!("The column `{name}` is duplicated"),
+                    ))
+                }
+                Some(name) => fields.push(Field::new(&name, kind)),
+                None if total == 1 => fields.push(Field::new(FIELD_NAME_SCALAR, kind)),
+                None => {
+                    return Err(vector_header(
+                        span,
+                        "The columns need a name when are many, like `[name:Str, qty:Int]`",
+                    ))
+                }
+            }
+        }
+    }
+
+    if keyed && key.is_empty() {
+        Err(need_key())
+    } else {
+        Ok(Some(Schema::new_keyed(&key, &fields)))
+    }
+}
+
+/// The values of a relation literal, by row
+type Rows = Vec<Vec<Ast>>;
+
+/// Parse the cells from the cursor until `last`, and check they match the schema
+fn parse_relation(
+    p: &mut Checker,
+    parent: NodeId,
+    open: Span,
+    last: usize,
+    keyed: bool,
+) -> Result<(Option<Schema>, Rows, Span), ErrorParser> {
+    let (mut rows, close) = match parse_rows(p, parent, open, last) {
+        Ok(x) => x,
+        Err(err) => {
+            // Skip the rest of the relation, so only is reported the first error
+            p.cursor = last;
+            return Err(err);
+        }
+    };
+    let span = open.cover(&close);
+
+    let header = match rows.first() {
+        Some(row) if row.iter().all(Cell::is_header) => Some(rows.remove(0)),
+        _ => None,
+    };
+
+    let mut values = Vec::with_capacity(rows.len());
+    for row in rows {
+        let row = row
+            .into_iter()
+            .map(|cell| match cell {
+                Cell::Value(x) => Ok(x),
+                Cell::Header { span, .. } => Err(vector_header(
+                    span,
+                    "The types of the columns must be in the first row",
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        values.push(row);
+    }
+
+    let schema = vector_schema(header, &values, span, keyed)?;
+    let cols = schema.as_ref().map(|x| x.len()).unwrap_or(1);
+
+    for (pos, row) in values.iter().enumerate() {
+        if row.len() != cols {
+            let cell = row.get(cols).unwrap_or(&row[row.len() - 1]);
+            return Err(ErrorParser::VectorShape {
+                span: cell.span(),
+                row: pos + 1,
+                expected: cols,
+                found: row.len(),
+            });
+        }
+    }
+
+    Ok((schema, values, span))
+}
+
+/// [header; cell, cell; ...]
+///
+/// The first row is the header if all the cells are types, like `[Int; 1]` or `[name:Str; "a"]`.
+/// All the rows must have the same number of cells than the header.
+pub(crate) fn parse_vector(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+) -> Result<Ast, ErrorParser> {
+    let open = node.span(&p.cst.tokens);
+    p.check.check(node, Step::Vector, open)?;
+
+    let last = p.last_child();
+    let (schema, rows, span) = parse_relation(p, parent, open, last, false)?;
+
+    Ok(Ast::Vector { schema, rows, span })
+}
+
+/// Tree[| pk header; cell, cell; ... |]
+///
+/// Like a vector, but the header is required & one of the columns must be the key
+pub(crate) fn parse_tree(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+) -> Result<Ast, ErrorParser> {
+    let open = node.span(&p.cst.tokens);
+    p.check.check(node, Step::Vector, open)?;
+
+    let last = p.last_child();
+    let next = p.advance_and_next();
+    let t = *p.token(next.token_id());
+    let of = match next {
+        CstNode::Atom(_) if t.kind == Syntax::Ident => Span::from(&t),
+        _ => {
+            p.cursor = last;
+            return Err(vector_header(
+                open,
+                "Expected the type of the relation before `[|`, like `Tree[| pk id:Int |]`",
+            ));
+        }
+    };
+    let name = p.code(&t).to_string();
+    if name != "Tree" {
+        p.cursor = last;
+        return Err(vector_header(
+            of,
+            &format!("Unknown relation `{name}`, expected `Tree`"),
+        ));
+    }
+
+    let (schema, rows, span) = parse_relation(p, parent, of.cover(&open), last, true)?;
+    let schema = schema.expect("A Tree always has a header");
+
+    Ok(Ast::Tree { schema, rows, span })
+}
+
+/// `#name asc|desc`, a column to sort with its order
+fn parse_order(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+    order: Order,
+) -> Result<Ast, ErrorParser> {
+    let order_span = node.span(&p.cst.tokens);
+    p.check.check(node, Step::Order(order), order_span)?;
+
+    let next = p.advance_and_next();
+    match expr(p, parent, next)? {
+        Ast::Column { col, span } => Ok(Ast::Order {
+            col,
+            order,
+            span: span.cover(&order_span),
+        }),
+        x => Err(query(x.span(), "Expected a column like `#name` or `#0`")),
+    }
+}
+
+/// A argument of a query: the `?where` need a boolean expression, the others any expression
+fn parse_query_arg(
+    p: &mut Checker,
+    parent: NodeId,
+    node: CstNode,
+    op: QueryOp,
+) -> Result<Ast, ErrorParser> {
+    let span = node.span(&p.cst.tokens);
+    p.sub_task(Task::Expr, span, |p| match node {
+        CstNode::Order(t) => {
+            let order = p.token(t).kind.to_order().expect("Is `asc` or `desc`");
+            let ast = p.sub_task(Task::Order(order), span, |p| {
+                parse_order(p, parent, &node, order)
+            })?;
+            p.check.check(&node, Step::Expr, ast.span())?;
+            Ok(ast)
+        }
+        _ if op == QueryOp::Where => {
+            let check = parse_bool_expr(p, parent, &node)?;
+            p.check.check(&node, Step::Expr, check.span())?;
+            Ok(Ast::Bool(Box::new(check)))
+        }
+        _ => expr(p, parent, node),
+    })
+}
+
+/// Check the number & kind of the `args` of the operator `op`
+fn check_query_args(op: QueryOp, op_span: Span, args: &[Ast]) -> Result<(), ErrorParser> {
+    let (min, max, example) = match op {
+        QueryOp::Where => (1, Some(1), "`?where #qty > 1`"),
+        QueryOp::Select | QueryOp::Deselect => (1, None, "`?select #name, #0`"),
+        QueryOp::Sort => (0, None, "`?sort #name desc, #0`"),
+        QueryOp::Limit | QueryOp::Skip => (1, Some(1), "`?limit 10`"),
+        QueryOp::Distinct => (0, Some(0), "`?distinct`"),
+        QueryOp::Group => (1, None, "`?group #kind, sum(#qty)`"),
+    };
+    let name = op.symbol();
+
+    if op != QueryOp::Sort {
+        if let Some(arg) = args.iter().find(|x| matches!(x, Ast::Order { .. })) {
+            return Err(query(
+                arg.span(),
+                "Only `?sort` can order the columns, like `?sort #name desc`",
+            ));
+        }
+    }
+    if args.len() < min {
+        return Err(query(
+            op_span,
+            &format!("Missing the arguments of `{name}`, like {example}"),
+        ));
+    }
+    if let Some(max) = max {
+        if let Some(extra) = args.get(max) {
+            return Err(query(
+                extra.span(),
+                &format!("Too many arguments for `{name}`, like {example}"),
+            ));
+        }
+    }
+    if matches!(op, QueryOp::Select | QueryOp::Deselect | QueryOp::Sort) {
+        let is_col = |x: &Ast| matches!(x, Ast::Column { .. } | Ast::Order { .. });
+        if let Some(arg) = args.iter().find(|x| !is_col(x)) {
+            return Err(query(arg.span(), "Expected a column like `#name` or `#0`"));
+        }
+    }
+    if op == QueryOp::Group {
+        // The columns to group by, and the aggregates of a column
+        let is_group = |x: &Ast| match x {
+            Ast::Column { .. } => true,
+            Ast::Call { name, args, .. } => {
+                Aggregate::from_name(name).is_some() && matches!(&args[..], [Ast::Column { .. }])
+            }
+            _ => false,
+        };
+        if let Some(arg) = args.iter().find(|x| !is_group(x)) {
+            return Err(query(
+                arg.span(),
+                "Expected a column or the aggregate of a column, like `#kind` or `sum(#qty)`",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// rel ?op arg, arg
+///
+/// The relation is the first child, then the arguments of the operator
+pub(crate) fn parse_query(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+) -> Result<Ast, ErrorParser> {
+    let last = p.last_child();
+    let result = parse_query_parts(p, parent, node, last);
+    if result.is_err() {
+        // Skip the rest of the query, so only is reported the first error
+        p.cursor = last;
+    }
+    result
+}
+
+fn parse_query_parts(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+    last: usize,
+) -> Result<Ast, ErrorParser> {
+    let t = *p.token(node.token_id());
+    let op_span: Span = (&t).into();
+    let code = p.code(&t);
+    let op = QueryOp::from_code(code).ok_or_else(|| {
+        query(
+            op_span,
+            &format!(
+                "Unknown operator `{code}`, expected one of `?where`, `?select`, `?deselect`, `?limit`, `?skip`, `?distinct`, `?sort` or `?group`"
+            ),
+        )
+    })?;
+    p.check.check(node, Step::Query, op_span)?;
+
+    let next = p.advance_and_next();
+    let span = next.span(&p.cst.tokens);
+    let rel = p.sub_task(Task::Expr, span, |p| expr(p, parent, next))?;
+    p.check.check(&next, Step::Expr, rel.span())?;
+
+    let mut args = Vec::new();
+    while p.cursor < last {
+        let next = p.advance_and_next();
+        args.push(parse_query_arg(p, parent, next, op)?);
+    }
+    check_query_args(op, op_span, &args)?;
+
+    let end = args.last().map(|x| x.span()).unwrap_or(op_span);
+    Ok(Ast::Query {
+        op,
+        span: rel.span().cover(&end),
+        rel: Box::new(rel),
+        args,
+    })
+}
+
+/// name(arg, arg)
+///
+/// The name of the function is the first child, then the arguments. A method call like
+/// `x.name(arg)` arrive here as `name(x, arg)`
+pub(crate) fn parse_call(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+) -> Result<Ast, ErrorParser> {
+    let last = p.last_child();
+    let result = parse_call_parts(p, parent, node, last);
+    if result.is_err() {
+        // Skip the rest of the call, so only is reported the first error
+        p.cursor = last;
+    }
+    result
+}
+
+fn parse_call_parts(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+    last: usize,
+) -> Result<Ast, ErrorParser> {
+    let open = node.span(&p.cst.tokens);
+    p.check.check(node, Step::Call, open)?;
+
+    let next = p.advance_and_next();
+    let span = next.span(&p.cst.tokens);
+    let name = parse_ident(p, &next)?;
+
+    let mut args = Vec::new();
+    while p.cursor < last {
+        let next = p.advance_and_next();
+        if let CstNode::Err(_) = next {
+            return Err(call(
+                open,
+                &format!("Missing the `)` of the call to `{name}`"),
+            ));
+        }
+        let span = next.span(&p.cst.tokens);
+        args.push(p.sub_task(Task::Expr, span, |p| expr(p, parent, next))?);
+    }
+
+    let span = args
+        .iter()
+        .fold(span.cover(&open), |all, x| all.cover(&x.span()));
+    Ok(Ast::Call { name, args, span })
+}
+
 pub(crate) fn parse_if(
     p: &mut Checker,
     parent: NodeId,
@@ -272,26 +1452,28 @@ pub(crate) fn parse_if(
     p.check.check(node, Step::Kw(Kw::If), if_span)?;
 
     let next = p.advance_and_next();
-    let check = parse_bool_expr(p, &next)?;
+    let check = parse_bool_expr(p, parent, &next)?;
     p.check.check(&next, Step::Expr, check.span())?;
 
     let next = p.advance_and_next();
-    let do_span = next.span(&p.cst.tokens);
-    p.check.check(&next, Step::Kw(Kw::Do), do_span)?;
+    let do_span = expect_node(p, &next, Step::Kw(Kw::Do), matches!(next, CstNode::Do(_)))?;
 
-    let next = p.advance_and_next();
-    let if_true = expr(p, parent, next)?;
+    let if_true = parse_branch(p, parent)?;
+    p.check.check(&next, Step::Expr, if_true.span())?;
 
     let next = p.advance_and_next();
-    let else_span = next.span(&p.cst.tokens);
-    p.check.check(&next, Step::Kw(Kw::Else), else_span)?;
+    let else_span = expect_node(
+        p,
+        &next,
+        Step::Kw(Kw::Else),
+        matches!(next, CstNode::Else(_)),
+    )?;
 
-    let next = p.advance_and_next();
-    let if_false = expr(p, parent, next)?;
+    let if_false = parse_branch(p, parent)?;
+    p.check.check(&next, Step::Expr, if_false.span())?;
 
     let next = p.advance_and_next();
-    let end_span = next.span(&p.cst.tokens);
-    p.check.check(&next, Step::Kw(Kw::End), end_span)?;
+    let end_span = expect_node(p, &next, Step::Kw(Kw::End), matches!(next, CstNode::End(_)))?;
 
     Ok(Ast::IfBlock {
         if_span,
@@ -303,3 +1485,436 @@ pub(crate) fn parse_if(
         if_false: Box::new(if_false),
     })
 }
+
+/// The name of a parameter, a case or a type of a `enum`, or of a field of a pattern, without
+/// a step in the [CheckList]
+fn enum_ident(p: &Checker, node: &CstNode) -> Result<String, ErrorParser> {
+    let t = *p.token(node.token_id());
+    if matches!(node, CstNode::Atom(_)) && t.kind == Syntax::Ident {
+        Ok(p.code(&t).into())
+    } else {
+        Err(CheckError {
+            span: node.span(&p.cst.tokens),
+            found: *node,
+            expect: Some(Step::Ident),
+        }
+        .into())
+    }
+}
+
+/// The names inside `[T, U]` or `(T, Int)`, the cursor must be on the open
+fn enum_idents(p: &mut Checker, node: &CstNode, close: &str) -> Result<Vec<String>, ErrorParser> {
+    let open = node.token_id();
+    let last = p.last_child();
+    let mut names = Vec::new();
+    while p.cursor < last {
+        let next = p.advance_and_next();
+        if next == CstNode::Err(open) {
+            let msg = format!("Missing the `{close}`");
+            return Err(enums(node.span(&p.cst.tokens), &msg));
+        }
+        names.push(enum_ident(p, &next)?);
+    }
+    Ok(names)
+}
+
+/// The type of a field of a case: a parameter, a builtin type or other `enum`
+fn field_kind(name: String, params: &[String]) -> FieldKind {
+    if params.contains(&name) {
+        return FieldKind::Param(name);
+    }
+    match DataType::from_name(&name) {
+        Some(kind) => FieldKind::Kind(kind),
+        None => FieldKind::Kind(DataType::Sum(name)),
+    }
+}
+
+/// enum Name[T]: Parent do case A case B(T, Int) end
+pub(crate) fn parse_enum(p: &mut Checker, node: &CstNode) -> Result<Ast, ErrorParser> {
+    let last = p.last_child();
+    let result = parse_enum_parts(p, node);
+    match &result {
+        // Skip the rest of the enum, so only is reported the first error
+        Err(_) => p.cursor = last,
+        // Know its cases, for the patterns of the `match` after it
+        Ok(Ast::Enum {
+            name,
+            params,
+            parent,
+            cases,
+            span,
+        }) => {
+            let of = match parent {
+                Some(parent) => p
+                    .enums
+                    .iter()
+                    .rev()
+                    .find(|x| &x.name == parent)
+                    .map(|x| x.extend(name, params, cases)),
+                None => Some(SumType::new(name, params, cases)),
+            };
+            if let Some(Ok(of)) = of {
+                // A type or case declared twice is ambiguous
+                if let Err(err) = of.check_new(cases, p.enums.iter()) {
+                    return Err(enums(*span, &err.to_string()));
+                }
+                p.enums.push(of);
+            }
+        }
+        Ok(_) => {}
+    }
+    result
+}
+
+fn parse_enum_parts(p: &mut Checker, node: &CstNode) -> Result<Ast, ErrorParser> {
+    let span = node.span(&p.cst.tokens);
+    p.check.check(node, Step::Kw(Kw::Enum), span)?;
+
+    let next = p.advance_and_next();
+    let name = parse_ident(p, &next)?;
+    if DataType::from_name(&name).is_some() {
+        let msg = format!("The type `{name}` is builtin, use other name");
+        return Err(enums(next.span(&p.cst.tokens), &msg));
+    }
+
+    let mut next = p.advance_and_next();
+    let mut params = Vec::new();
+    if let CstNode::Vector(_) = next {
+        params = enum_idents(p, &next, "]")?;
+        next = p.advance_and_next();
+    }
+    let mut parent = None;
+    if let CstNode::Field(_) = next {
+        let of = p.advance_and_next();
+        parent = Some(enum_ident(p, &of)?);
+        next = p.advance_and_next();
+    }
+    expect_node(p, &next, Step::Kw(Kw::Do), matches!(next, CstNode::Do(_)))?;
+
+    let mut cases = Vec::new();
+    let end_span = loop {
+        let next = p.advance_and_next();
+        match next {
+            CstNode::Case(_) => {
+                let case = p.advance_and_next();
+                let case = enum_ident(p, &case)?;
+                let mut fields = Vec::new();
+                if let CstNode::Call(_) = p.peek() {
+                    let open = p.advance_and_next();
+                    fields = enum_idents(p, &open, ")")?
+                        .into_iter()
+                        .map(|x| field_kind(x, &params))
+                        .collect();
+                }
+                cases.push(Case::new(&case, &fields));
+            }
+            CstNode::End(_) if cases.is_empty() => {
+                return Err(enums(
+                    span,
+                    "A `enum` need at least one case, like `case None`",
+                ));
+            }
+            CstNode::End(_) => break expect_node(p, &next, Step::Kw(Kw::End), true)?,
+            _ => {
+                return Err(CheckError {
+                    span: next.span(&p.cst.tokens),
+                    found: next,
+                    expect: Some(Step::Kw(Kw::Case)),
+                }
+                .into())
+            }
+        }
+    };
+
+    Ok(Ast::Enum {
+        name,
+        params,
+        parent,
+        cases,
+        span: span.cover(&end_span),
+    })
+}
+
+/// The value of a pattern, a literal like `1` or a negative number like `-1`
+fn pattern_value(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+) -> Result<(Scalar, Span), ErrorParser> {
+    let span = node.span(&p.cst.tokens);
+    let expected = || {
+        pattern(
+            span,
+            "Expected a pattern, like `1`, `1..10`, `Some(x)` or `[| name: x |]`",
+        )
+    };
+    let ast = match node {
+        CstNode::Atom(_) | CstNode::Op(_) => {
+            p.sub_task(Task::Expr, span, |p| expr(p, parent, *node))?
+        }
+        _ => return Err(expected()),
+    };
+    match ast {
+        Ast::Scalar { val, span } => Ok((val, span)),
+        Ast::UnaryOp {
+            op: UnaryOp::Neg,
+            expr,
+            span,
+        } => match *expr {
+            Ast::Scalar { val, .. } => match kernels::neg(&Operand::Scalar(val)) {
+                Ok(Operand::Scalar(val)) => Ok((val, span)),
+                _ => Err(expected()),
+            },
+            _ => Err(expected()),
+        },
+        _ => Err(expected()),
+    }
+}
+
+/// The case `name` of the `enum` that has it, checking the `fields` are all of the case
+fn case_pattern(
+    p: &Checker,
+    name: String,
+    fields: Vec<Pattern>,
+    span: Span,
+) -> Result<Pattern, ErrorParser> {
+    let case = find_enum(&p.enums, &[&name]).and_then(|of| of.case(&name));
+    match case {
+        None => Err(pattern(span, &format!("The case `{name}` is not defined"))),
+        Some((_, case)) if case.fields.len() != fields.len() => {
+            let msg = format!(
+                "The case `{}` take {} values, but get {}",
+                name,
+                case.fields.len(),
+                fields.len()
+            );
+            Err(pattern(span, &msg))
+        }
+        Some(_) => Ok(Pattern::Case { name, fields, span }),
+    }
+}
+
+/// [| name: pattern, name: pattern |], the cursor must be on the `[|`
+fn row_pattern(p: &mut Checker, parent: NodeId, node: &CstNode) -> Result<Pattern, ErrorParser> {
+    let span = node.span(&p.cst.tokens);
+    let last = p.last_child();
+    let mut fields: Vec<(String, Pattern)> = Vec::new();
+    let mut end = span;
+    while p.cursor < last {
+        let next = p.advance_and_next();
+        match next {
+            CstNode::Field(_) => {
+                let of = p.advance_and_next();
+                let name = enum_ident(p, &of)?;
+                if fields.iter().any(|(x, _)| x == &name) {
+                    let msg = format!("The field `{name}` is duplicated");
+                    return Err(pattern(of.span(&p.cst.tokens), &msg));
+                }
+                let next = p.advance_and_next();
+                fields.push((name, parse_pattern(p, parent, &next, false)?));
+            }
+            CstNode::Close(_) => end = next.span(&p.cst.tokens),
+            CstNode::Err(t) if t == node.token_id() => {
+                return Err(pattern(span, "Missing the `|]`"));
+            }
+            _ => {
+                return Err(CheckError {
+                    span: next.span(&p.cst.tokens),
+                    found: next,
+                    expect: Some(Step::Ident),
+                }
+                .into())
+            }
+        }
+    }
+    if fields.is_empty() {
+        return Err(pattern(
+            span,
+            "Expected the fields of the row, like `[| name: x |]`",
+        ));
+    }
+    Ok(Pattern::Row {
+        fields,
+        span: span.cover(&end),
+    })
+}
+
+/// The pattern of a `case`. A ident is the case of a `enum` if one has it, else a variable that
+/// match any value. Only the whole pattern can be a row
+fn parse_pattern(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+    whole: bool,
+) -> Result<Pattern, ErrorParser> {
+    let span = node.span(&p.cst.tokens);
+    let t = *p.token(node.token_id());
+    match node {
+        CstNode::Atom(_) if t.kind == Syntax::Ident => {
+            let name: String = p.code(&t).into();
+            if find_enum(&p.enums, &[&name]).is_some() {
+                case_pattern(p, name, vec![], span)
+            } else {
+                Ok(Pattern::Any {
+                    name: Some(name),
+                    span,
+                })
+            }
+        }
+        CstNode::Call(open) => {
+            let last = p.last_child();
+            let of = p.advance_and_next();
+            let name = enum_ident(p, &of)?;
+            let mut fields = Vec::new();
+            while p.cursor < last {
+                let next = p.advance_and_next();
+                if next == CstNode::Err(*open) {
+                    return Err(pattern(span, "Missing the `)`"));
+                }
+                fields.push(parse_pattern(p, parent, &next, false)?);
+            }
+            let end = fields.last().map(|x| x.span()).unwrap_or(span);
+            case_pattern(p, name, fields, of.span(&p.cst.tokens).cover(&end))
+        }
+        CstNode::Range(_) => {
+            let next = p.advance_and_next();
+            let (start, start_span) = pattern_value(p, parent, &next)?;
+            let next = p.advance_and_next();
+            let (end, end_span) = pattern_value(p, parent, &next)?;
+            let span = start_span.cover(&end_span);
+            if start.kind() != end.kind() {
+                return Err(pattern(
+                    span,
+                    "The range need values of the same type, like `1..10`",
+                ));
+            }
+            if start >= end {
+                return Err(pattern(
+                    span,
+                    "The range is empty, the start must be less than the end",
+                ));
+            }
+            Ok(Pattern::Range { start, end, span })
+        }
+        CstNode::Rel(_) if whole => row_pattern(p, parent, node),
+        CstNode::Rel(_) => Err(pattern(
+            span,
+            "A row pattern must be the whole pattern of the case",
+        )),
+        _ => {
+            let (val, span) = pattern_value(p, parent, node)?;
+            Ok(Pattern::Scalar { val, span })
+        }
+    }
+}
+
+/// match value do case pattern do body else body end
+pub(crate) fn parse_match(
+    p: &mut Checker,
+    parent: NodeId,
+    node: &CstNode,
+) -> Result<Ast, ErrorParser> {
+    let last = p.last_child();
+    let result = parse_match_parts(p, parent, node);
+    if result.is_err() {
+        // Skip the rest of the match, so only is reported the first error
+        p.cursor = last;
+    }
+    result
+}
+
+fn parse_match_parts(p: &mut Checker, parent: NodeId, node: &CstNode) -> Result<Ast, ErrorParser> {
+    let span = node.span(&p.cst.tokens);
+    p.check.check(node, Step::Kw(Kw::Match), span)?;
+
+    let next = p.advance_and_next();
+    let value_span = next.span(&p.cst.tokens);
+    let value = p.sub_task(Task::Expr, value_span, |p| expr(p, parent, next))?;
+    p.check.check(&next, Step::Expr, value.span())?;
+
+    let next = p.advance_and_next();
+    expect_node(p, &next, Step::Kw(Kw::Do), matches!(next, CstNode::Do(_)))?;
+
+    let mut arms = Vec::new();
+    let end_span = loop {
+        let next = p.advance_and_next();
+        match next {
+            CstNode::Case(_) => {
+                let of = p.advance_and_next();
+                let pattern = parse_pattern(p, parent, &of, true)?;
+                let next = p.advance_and_next();
+                if !matches!(next, CstNode::Do(_)) {
+                    return Err(CheckError {
+                        span: next.span(&p.cst.tokens),
+                        found: next,
+                        expect: Some(Step::Kw(Kw::Do)),
+                    }
+                    .into());
+                }
+                let body = parse_branch(p, parent)?;
+                arms.push(Arm { pattern, body });
+            }
+            CstNode::Else(_) => {
+                let pattern = Pattern::Any {
+                    name: None,
+                    span: next.span(&p.cst.tokens),
+                };
+                let body = parse_branch(p, parent)?;
+                arms.push(Arm { pattern, body });
+            }
+            CstNode::End(_) if arms.is_empty() => {
+                return Err(pattern(
+                    span,
+                    "A `match` need at least one case, like `case x do x`",
+                ));
+            }
+            CstNode::End(_) => break expect_node(p, &next, Step::Kw(Kw::End), true)?,
+            _ => {
+                return Err(CheckError {
+                    span: next.span(&p.cst.tokens),
+                    found: next,
+                    expect: Some(Step::Kw(Kw::Case)),
+                }
+                .into())
+            }
+        }
+    };
+
+    // Report all the cases that are unreachable, then the values not covered
+    let patterns: Vec<_> = arms.iter().map(|x| x.pattern.clone()).collect();
+    let all: Vec<_> = patterns.iter().collect();
+    if let Err((span, msg)) = exhaustive::check_cases(&p.enums, &all) {
+        return Err(pattern(span, &msg));
+    }
+    let mut errors: Vec<_> = exhaustive::unreachable(&p.enums, &patterns)
+        .into_iter()
+        .map(|pos| {
+            pattern(
+                patterns[pos].span(),
+                "The case is unreachable, the cases before it match all its values",
+            )
+        })
+        .collect();
+    if let Some(missing) = exhaustive::missing(&p.enums, &patterns) {
+        let msg = if missing.is_empty() {
+            "The `match` not cover all the values, add a `else`".to_string()
+        } else {
+            let missing: Vec<_> = missing.iter().map(|x| format!("`{x}`")).collect();
+            format!(
+                "The `match` not cover the cases {}, add them or a `else`",
+                missing.join(", ")
+            )
+        };
+        errors.push(pattern(span.cover(&value.span()), &msg));
+    }
+    if let Some(last) = errors.pop() {
+        p.errors.extend(errors);
+        return Err(last);
+    }
+
+    Ok(Ast::Match {
+        value: Box::new(value),
+        arms,
+        span: span.cover(&end_span),
+    })
+}
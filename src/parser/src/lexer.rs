@@ -1,7 +1,7 @@
 use std::convert::TryFrom;
 use std::ops::Range as StdRange;
 
-use crate::token::{Syntax, SyntaxKind, Token, TokenId};
+use crate::token::{ExtrasLexer, Syntax, SyntaxKind, Token, TokenId};
 use corelib::prelude::*;
 use corelib::text_size::{TextRange, TextSize};
 use logos::Logos;
@@ -10,6 +10,10 @@ pub struct Lexer<'a> {
     file_id: FileId,
     lexer: logos::Lexer<'a, Syntax>,
     pub(crate) cursor: usize,
+    /// Where the input start in the source, when only lex a part of it
+    start: usize,
+    /// The column of `start`, only for the tokens in its line
+    start_col: usize,
 }
 
 impl<'a> Lexer<'a> {
@@ -18,6 +22,27 @@ impl<'a> Lexer<'a> {
             file_id,
             lexer: Syntax::lexer(input),
             cursor: 1, //Because Root is 0!
+            start: 0,
+            start_col: 0,
+        }
+    }
+
+    /// Lex only the `range` of the `source`, like the expressions inside a string, keeping the
+    /// positions of the tokens in the source
+    pub fn at(file_id: FileId, source: &'a str, range: StdRange<usize>) -> Self {
+        let before = &source[..range.start];
+        let line_start = before.rfind('\n').map(|x| x + 1).unwrap_or_default();
+        let extras = ExtrasLexer {
+            current_line: before.matches('\n').count() + 1,
+            current_initial_column: 0,
+        };
+
+        Self {
+            file_id,
+            lexer: Syntax::lexer_with_extras(&source[range.clone()], extras),
+            cursor: 1,
+            start: range.start,
+            start_col: range.start - line_start,
         }
     }
 }
@@ -39,10 +64,15 @@ impl<'a> Iterator for Lexer<'a> {
 
         let extra = self.lexer.extras;
         let line = extra.current_line as u32;
-        let col = (span.end - extra.current_initial_column) as u32;
+        let col = if extra.current_initial_column == 0 {
+            span.end + self.start_col
+        } else {
+            span.end - extra.current_initial_column
+        } as u32;
 
         let range = {
             let StdRange { start, end } = span;
+            let (start, end) = (start + self.start, end + self.start);
             let start = TextSize::try_from(start).unwrap();
             let end = TextSize::try_from(end).unwrap();
 
@@ -64,7 +94,6 @@ impl<'a> Iterator for Lexer<'a> {
 
 #[derive(Debug, Clone)]
 pub struct Scanner {
-    file_id: FileId,
     tokens: Vec<Token>,
     pub(crate) root: Token,
     pub(crate) eof: Token,
@@ -87,13 +116,15 @@ impl Scanner {
 
         tokens.push(root);
 
+        // The EOF is at the end of the source, so the diagnostics can point there
+        let end = TextSize::try_from(lexer.start + lexer.lexer.source().len()).unwrap();
         tokens.extend(lexer);
 
         let eof = Token {
             file_id,
             id: TokenId(tokens.len() + 1), //Add Root!
             kind: Syntax::Eof,
-            range: Default::default(),
+            range: TextRange::empty(end),
             line: 0,
             col: 0,
         };
@@ -101,7 +132,6 @@ impl Scanner {
         tokens.push(eof);
 
         Self {
-            file_id,
             tokens,
             root,
             eof,
@@ -124,6 +154,13 @@ impl Scanner {
         *t
     }
 
+    /// Change the kind of the token, like when `-` is found in prefix position and become [Syntax::Neg]
+    pub(crate) fn set_kind(&mut self, id: TokenId, kind: Syntax) {
+        if let Some(t) = self.tokens.get_mut(id.0) {
+            t.kind = kind;
+        }
+    }
+
     pub(crate) fn get(&self, id: TokenId) -> &Token {
         if let Some(t) = self.tokens.get(id.0) {
             t
@@ -173,6 +210,13 @@ mod tests {
         check("1234.1234d", Syntax::Decimal);
     }
 
+    #[test]
+    fn lex_money() {
+        check("12.50 USD", Syntax::Money);
+        check("12USD", Syntax::Money);
+        check("1_000 JPY", Syntax::Money);
+    }
+
     #[test]
     fn lex_floats() {
         check("123456.123456f", Syntax::Float);
@@ -194,6 +238,12 @@ mod tests {
         check("'a '", Syntax::String);
         check("\"a\n\"", Syntax::String);
         check("'a\n'", Syntax::String);
+        check(r"'it\'s'", Syntax::String);
+        check(r#""a \" b""#, Syntax::String);
+        check(r#""""a "b" c""""#, Syntax::String);
+        check("'''a\n'b'\n'''", Syntax::String);
+        check("c'a'", Syntax::Char);
+        check("c\"ñ\"", Syntax::Char);
     }
 
     #[test]
@@ -208,6 +258,8 @@ mod tests {
         check("dt\"2000-01-01 09:10:11\"", Syntax::DateTime);
         check("dt\"2000-01-01T09:10:11\"", Syntax::DateTime);
         check("dt\"trash\"", Syntax::DateTime);
+        check("i'1 day 2 hours'", Syntax::Interval);
+        check("i\"3 months\"", Syntax::Interval);
     }
 
     #[test]
@@ -220,6 +272,12 @@ mod tests {
         check("let", Syntax::LetKw);
         check("var", Syntax::VarKw);
         check("fun", Syntax::FnKw);
+        check("pk", Syntax::PkKw);
+        check("enum", Syntax::EnumKw);
+        check("case", Syntax::CaseKw);
+        check("match", Syntax::MatchKw);
+        check("asc", Syntax::AscKw);
+        check("desc", Syntax::DescKw);
     }
 
     #[test]
@@ -230,6 +288,16 @@ mod tests {
         check("x", Syntax::Ident);
     }
 
+    #[test]
+    fn lex_query() {
+        check("#name", Syntax::Column);
+        check("#0", Syntax::Column);
+        check("#12", Syntax::Column);
+        check("?where", Syntax::Query);
+        check("?select", Syntax::Query);
+        check("?", Syntax::Question);
+    }
+
     #[test]
     fn lex_ops() {
         check("+", Syntax::Plus);
@@ -237,6 +305,7 @@ mod tests {
         check("*", Syntax::Star);
         check("/", Syntax::Slash);
         check(":=", Syntax::Assign);
+        check("..", Syntax::DotDot);
     }
 
     #[test]
@@ -245,5 +314,9 @@ mod tests {
         check(")", Syntax::RParen);
         check("{", Syntax::LBrace);
         check("}", Syntax::RBrace);
+        check("[", Syntax::LSquare);
+        check("]", Syntax::RSquare);
+        check("[|", Syntax::LRel);
+        check("|]", Syntax::RRel);
     }
 }
@@ -2,6 +2,7 @@ pub mod ast;
 mod checklist;
 mod cst;
 pub mod errors;
+mod exhaustive;
 mod expr;
 pub mod files;
 mod lexer;
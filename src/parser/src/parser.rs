@@ -1,4 +1,4 @@
-use crate::ast::{Ast, ExprBool, Ty};
+use crate::ast::{Ast, ExprBool, Pattern, Ty};
 use crate::checklist::{CheckList, Task};
 use crate::cst::{src_to_cst, Cst, CstNode};
 use crate::files::FilesDb;
@@ -6,12 +6,16 @@ use crate::token::{token_eof, Syntax, Token, TokenId};
 use crate::{errors, expr};
 
 use crate::errors::ErrorParser;
+use corelib::algebraic::SumType;
+use corelib::chrono::FixedOffset;
 use corelib::errors::Span;
 use corelib::tree_flat::node::NodeId;
 use corelib::tree_flat::prelude::{Node, Tree};
+use corelib::types;
 use std::fmt;
 
 /// The points in the code where we can recover after a failed parse
+#[allow(dead_code)]
 const RECOVERY_SET: [Syntax; 2] = [Syntax::LetKw, Syntax::VarKw];
 
 pub struct ParsedPrinter<'a> {
@@ -40,10 +44,14 @@ pub(crate) struct Checker<'a> {
     pub(crate) ast: Tree<Ast>,
     pub(crate) cursor: usize,
     pub(crate) errors: Vec<ErrorParser>,
+    /// The offset of the date literals written without one
+    pub(crate) offset: FixedOffset,
+    /// The sum types declared before, in order, so the patterns of a `match` can use its cases
+    pub(crate) enums: Vec<SumType>,
 }
 
 impl<'a> Checker<'a> {
-    pub fn new(cst: Cst<'a>) -> Self {
+    pub fn new(cst: Cst<'a>, offset: FixedOffset) -> Self {
         // Start at 1 to skip Root!
         let root = cst.ast.root();
         let span = root.data.span(&cst.tokens);
@@ -53,6 +61,8 @@ impl<'a> Checker<'a> {
             cst,
             cursor: 1,
             errors: vec![],
+            offset,
+            enums: vec![],
         }
     }
 
@@ -94,7 +104,7 @@ impl<'a> Checker<'a> {
             .unwrap_or_else(|| CstNode::Eof(token_eof().id))
     }
 
-    pub(crate) fn peek(&mut self) -> CstNode {
+    pub(crate) fn peek(&self) -> CstNode {
         self.cst_peek()
             .map(|x| *x.data)
             .unwrap_or_else(|| CstNode::Eof(token_eof().id))
@@ -104,6 +114,19 @@ impl<'a> Checker<'a> {
         self.cursor += 1;
     }
 
+    /// The position of the last node of the subtree that start at the cursor
+    pub(crate) fn last_child(&self) -> usize {
+        let level = self.cst().map(|x| x.level()).unwrap_or_default();
+        let mut last = self.cursor;
+        while let Some(node) = self.cst.ast.node((last + 1).into()) {
+            if node.level() <= level {
+                break;
+            }
+            last += 1;
+        }
+        last
+    }
+
     pub(crate) fn advance_eof(&mut self) {
         self.cursor = self.cst.ast.len();
     }
@@ -131,6 +154,20 @@ impl<'a> Checker<'a> {
         }
     }
 
+    /// Run `f` with a fresh [CheckList] for the `task`, so a nested expression
+    /// not consume the steps of the enclosing one
+    pub(crate) fn sub_task<T>(
+        &mut self,
+        task: Task,
+        span: Span,
+        f: impl FnOnce(&mut Self) -> Result<T, ErrorParser>,
+    ) -> Result<T, ErrorParser> {
+        let parent = std::mem::replace(&mut self.check, CheckList::new(task, span));
+        let result = f(self);
+        self.check = parent;
+        result
+    }
+
     pub(crate) fn recover(&mut self) {}
 
     pub(crate) fn check_pending(&mut self) {
@@ -147,11 +184,30 @@ impl<'a> Checker<'a> {
 
 pub struct Parser {
     pub(crate) files: FilesDb,
+    offset: FixedOffset,
+    enums: Vec<SumType>,
 }
 
 impl Parser {
     pub fn new(files: FilesDb) -> Self {
-        Self { files }
+        Self {
+            files,
+            offset: types::utc(),
+            enums: vec![],
+        }
+    }
+
+    /// The sum types declared before the source, like in a previous line of the REPL, in the
+    /// order of declaration
+    pub fn with_enums(mut self, enums: Vec<SumType>) -> Self {
+        self.enums = enums;
+        self
+    }
+
+    /// Read the date literals without offset, like `dt'2000-01-01 10:00'`, at `offset`
+    pub fn with_offset(mut self, offset: FixedOffset) -> Self {
+        self.offset = offset;
+        self
     }
 
     pub fn from_src(source: &str) -> Self {
@@ -163,7 +219,8 @@ impl Parser {
         let root = self.files.get_root();
         let cst = src_to_cst(root.source());
 
-        let mut check = Checker::new(cst);
+        let mut check = Checker::new(cst, self.offset);
+        check.enums = self.enums.clone();
         expr::root(&mut check);
 
         Parsed {
@@ -207,7 +264,39 @@ fn fmt_bool_expr(
 ) -> fmt::Result {
     match node {
         ExprBool::Scalar { val, span } => fmt_t(f, level, kind, val, span),
+        ExprBool::Cmp { op, lhs, rhs, span } => {
+            fmt_plain(f, level, op, span)?;
+            writeln!(f)?;
+            fmt_node(lhs, level + 1, f)?;
+            writeln!(f)?;
+            fmt_node(rhs, level + 1, f)
+        }
+        ExprBool::Logic { op, lhs, rhs, span } => {
+            fmt_plain(f, level, op, span)?;
+            writeln!(f)?;
+            fmt_bool_expr(lhs, kind.clone(), level + 1, f)?;
+            writeln!(f)?;
+            fmt_bool_expr(rhs, kind, level + 1, f)
+        }
+        ExprBool::Not { expr, span } => {
+            fmt_plain(f, level, &"not", span)?;
+            writeln!(f)?;
+            fmt_bool_expr(expr, kind, level + 1, f)
+        }
+        ExprBool::Expr(x) => fmt_node(x, level, f),
+    }
+}
+
+fn fmt_rows(rows: &[Vec<Ast>], level: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (pos, row) in rows.iter().enumerate() {
+        writeln!(f)?;
+        write!(f, "{}row {}", " ".repeat(level + 2), pos + 1)?;
+        for x in row {
+            writeln!(f)?;
+            fmt_node(x, level + 2, f)?;
+        }
     }
+    Ok(())
 }
 
 fn fmt_node(node: &Ast, level: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -218,7 +307,122 @@ fn fmt_node(node: &Ast, level: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result
         Ast::Scalar { val, span } => fmt_t(f, level, kind, val, span)?,
         Ast::Pass(span) => fmt_plain(f, level, &"Pass", span)?,
         Ast::Eof(_) => write!(f, "Eof")?,
-        Ast::Cmp { op, span } => fmt_plain(f, level, &format!("{:?}", op), span)?,
+        Ast::Bool(x) => fmt_bool_expr(x, kind, level, f)?,
+        Ast::Ident { name, span } => fmt_plain(f, level, &format!("Ident {name}"), span)?,
+        Ast::Let { name, value, span } => {
+            fmt_plain(f, level, &format!("let {name}"), span)?;
+            writeln!(f)?;
+            fmt_node(value, level + 1, f)?;
+        }
+        Ast::Var { name, value, span } => {
+            fmt_plain(f, level, &format!("var {name}"), span)?;
+            writeln!(f)?;
+            fmt_node(value, level + 1, f)?;
+        }
+        Ast::Assign { name, value, span } => {
+            fmt_plain(f, level, &format!("{name} :="), span)?;
+            writeln!(f)?;
+            fmt_node(value, level + 1, f)?;
+        }
+        Ast::Block { body, span } => {
+            fmt_plain(f, level, &"do", span)?;
+            for x in body {
+                writeln!(f)?;
+                fmt_node(x, level + 1, f)?;
+            }
+            writeln!(f)?;
+            fmt_plain(f, level, &"end --do", span)?;
+        }
+        Ast::Vector { schema, rows, span } => {
+            let header = match schema {
+                Some(schema) => format!("[{schema}]"),
+                None => "[?]".to_string(),
+            };
+            fmt_plain(f, level, &header, span)?;
+            fmt_rows(rows, level, f)?;
+        }
+        Ast::Tree { schema, rows, span } => {
+            let pk: Vec<_> = schema
+                .pk
+                .iter()
+                .map(|x| schema.fields[*x].name.as_str())
+                .collect();
+            let header = format!("Tree[|{schema}|] pk {}", pk.join(", "));
+            fmt_plain(f, level, &header, span)?;
+            fmt_rows(rows, level, f)?;
+        }
+        Ast::BinOp { op, lhs, rhs, span } => {
+            fmt_plain(f, level, op, span)?;
+            writeln!(f)?;
+            fmt_node(lhs, level + 1, f)?;
+            writeln!(f)?;
+            fmt_node(rhs, level + 1, f)?;
+        }
+        Ast::UnaryOp { op, expr, span } => {
+            fmt_plain(f, level, op, span)?;
+            writeln!(f)?;
+            fmt_node(expr, level + 1, f)?;
+        }
+        Ast::Column { col, span } => fmt_plain(f, level, &format!("Column {col}"), span)?,
+        Ast::Order { col, order, span } => {
+            fmt_plain(f, level, &format!("Column {col} {order}"), span)?
+        }
+        Ast::Query {
+            op,
+            rel,
+            args,
+            span,
+        } => {
+            fmt_plain(f, level, &op.symbol(), span)?;
+            writeln!(f)?;
+            fmt_node(rel, level + 1, f)?;
+            for x in args {
+                writeln!(f)?;
+                fmt_node(x, level + 1, f)?;
+            }
+        }
+        Ast::Call { name, args, span } => {
+            fmt_plain(f, level, &format!("{name}()"), span)?;
+            for x in args {
+                writeln!(f)?;
+                fmt_node(x, level + 1, f)?;
+            }
+        }
+        Ast::Enum {
+            name,
+            params,
+            parent,
+            cases,
+            span,
+        } => {
+            let mut head = name.clone();
+            if !params.is_empty() {
+                head = format!("{head}[{}]", params.join(", "));
+            }
+            if let Some(parent) = parent {
+                head = format!("{head}: {parent}");
+            }
+            fmt_plain(f, level, &format!("enum {head}"), span)?;
+            for x in cases {
+                writeln!(f)?;
+                write!(f, "{}case {x}", " ".repeat(level + 2))?;
+            }
+        }
+        Ast::Match { value, arms, span } => {
+            fmt_plain(f, level, &"match", span)?;
+            writeln!(f)?;
+            fmt_node(value, level + 1, f)?;
+            for x in arms {
+                writeln!(f)?;
+                let head = match x.pattern {
+                    Pattern::Any { name: None, .. } => "else".to_string(),
+                    ref pattern => format!("case {pattern}"),
+                };
+                fmt_plain(f, level + 1, &head, &x.pattern.span())?;
+                writeln!(f)?;
+                fmt_node(&x.body, level + 2, f)?;
+            }
+        }
         Ast::IfBlock {
             if_span,
             do_span,
@@ -305,7 +509,7 @@ Root
             "'hello\nworld'",
             expect![[r#"
                 Root
-                  T: Utf8 @@ 0..13: Utf8(["\"hello\nworld\""])
+                  T: Utf8 @@ 0..13: Utf8(["hello\nworld"])
             "#]],
         );
 
@@ -313,7 +517,103 @@ Root
             "\"hello\nworld\"",
             expect![[r#"
                 Root
-                  T: Utf8 @@ 0..13: Utf8(["\"hello\nworld\""])
+                  T: Utf8 @@ 0..13: Utf8(["hello\nworld"])
+            "#]],
+        );
+
+        check(
+            "\"tab\\t\\\"quoted\\\" \\u{1F600} \\{braces\\}\"",
+            expect![[r#"
+                Root
+                  T: Utf8 @@ 0..38: Utf8(["tab\t\"quoted\" 😀 {braces}"])
+            "#]],
+        );
+
+        check(
+            "\"\"\"\nA long \"text\"\n\"\"\"",
+            expect![[r#"
+                Root
+                  T: Utf8 @@ 0..21: Utf8(["A long \"text\"\n"])
+            "#]],
+        );
+
+        check(
+            "'total: {price * qty}!'",
+            expect![[r#"
+                Root
+                  0..23: Add
+                   0..23: Add
+                    T: Utf8 @@ 0..23: Utf8(["total: "])
+                    9..20: "to_str()"
+                     9..20: Mul
+                      9..14: "Ident price"
+                      17..20: "Ident qty"
+                   T: Utf8 @@ 0..23: Utf8(["!"])
+            "#]],
+        );
+
+        check(
+            "'{1} & {\"a\".upper()}'",
+            expect![[r#"
+                Root
+                  0..21: Add
+                   0..21: Add
+                    2..3: "to_str()"
+                     T: I64 @@ 2..3: I64([1])
+                    T: Utf8 @@ 0..21: Utf8([" & "])
+                   8..18: "to_str()"
+                    8..18: "upper()"
+                     T: Utf8 @@ 8..11: Utf8(["a"])
+            "#]],
+        );
+
+        check(
+            "'bad \\q'",
+            expect![[r#"
+                Root
+                Errors
+                 ScalarParse { span: Span { file_id: NodeId(1), range: RangeCode(5..7), line: 1, col: 8 }, kind: Utf8, msg: "Invalid escape, expected one of `\\n \\t \\r \\0 \\\\ \\' \\\" \\{ \\} \\u{..}`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..8), line: 1, col: 8 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+
+        check(
+            "'bad \\u{110000}'",
+            expect![[r#"
+                Root
+                Errors
+                 ScalarParse { span: Span { file_id: NodeId(1), range: RangeCode(5..15), line: 1, col: 16 }, kind: Utf8, msg: "Invalid unicode escape, expected one like `\\u{1F600}`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..16), line: 1, col: 16 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+
+        check(
+            "'open { 1'",
+            expect![[r#"
+                Root
+                Errors
+                 ScalarParse { span: Span { file_id: NodeId(1), range: RangeCode(6..7), line: 1, col: 10 }, kind: Utf8, msg: "The `{` is not closed, write `\\{` for a literal brace" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..10), line: 1, col: 10 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+
+        check(
+            "'{}'",
+            expect![[r#"
+                Root
+                Errors
+                 ScalarParse { span: Span { file_id: NodeId(1), range: RangeCode(1..3), line: 1, col: 4 }, kind: Utf8, msg: "Expected a single expression inside `{}`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..4), line: 1, col: 4 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+
+        check(
+            "'{1 +}'",
+            expect![[r#"
+                Root
+                Errors
+                 NoExpr { span: Span { file_id: NodeId(1), range: RangeCode(5..5), line: 0, col: 0 }, found: "" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..7), line: 1, col: 7 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
             "#]],
         );
     }
@@ -391,6 +691,873 @@ Root
 Root
   T: I64 @@ 0..1: I64([1])
   T: Bool @@ 2..6: Bool([true])
+"##]],
+        );
+    }
+
+    #[test]
+    fn parse_ops() {
+        check(
+            "1 + 2 * 3",
+            expect![[r#"
+                Root
+                  0..9: Add
+                   T: I64 @@ 0..1: I64([1])
+                   4..9: Mul
+                    T: I64 @@ 4..5: I64([2])
+                    T: I64 @@ 8..9: I64([3])
+            "#]],
+        );
+
+        check(
+            "-(1.5 - 2.0)",
+            expect![[r#"
+                Root
+                  0..11: Neg
+                   2..11: Sub
+                    T: Decimal @@ 2..5: Decimal([1.5])
+                    T: Decimal @@ 8..11: Decimal([2.0])
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_bool() {
+        check(
+            "1 <= 2 and not false",
+            expect![[r#"
+                Root
+                  0..20: And
+                   0..6: LessEquals
+                    T: I64 @@ 0..1: I64([1])
+                    T: I64 @@ 5..6: I64([2])
+                   11..20: "not"
+                    T: Bool @@ 15..20: Bool([false])
+            "#]],
+        );
+
+        check(
+            "if 1 = 1 or true do 1 else 2 end",
+            expect![[r#"
+                Root
+                  0..2: "if"
+                   3..16: Or
+                    3..8: Equals
+                     T: I64 @@ 3..4: I64([1])
+                     T: I64 @@ 7..8: I64([1])
+                     @@ 12..16: Bool([true])
+                  17..19: "do"
+                   T: I64 @@ 20..21: I64([1])
+                  22..26: "else"
+                   T: I64 @@ 27..28: I64([2])
+                  29..32: "end --if"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_bool_err() {
+        check(
+            "true and 1",
+            expect![[r#"
+                Root
+                Errors
+                 BoolExpr { span: Span { file_id: NodeId(1), range: RangeCode(9..10), line: 1, col: 10 }, found: "1" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(5..8), line: 1, col: 8 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_op_incomplete() {
+        check(
+            "1 +",
+            expect![[r#"
+                Root
+                Errors
+                 NoExpr { span: Span { file_id: NodeId(1), range: RangeCode(3..3), line: 0, col: 0 }, found: "" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(2..3), line: 1, col: 3 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_define() {
+        check(
+            "let x := 1\nvar y := x + 1\ny := y * 2",
+            expect![[r#"
+                Root
+                  0..10: "let x"
+                   T: I64 @@ 9..10: I64([1])
+                  11..25: "var y"
+                   20..25: Add
+                    20..21: "Ident x"
+                    T: I64 @@ 24..25: I64([1])
+                  26..36: "y :="
+                   31..36: Mul
+                    31..32: "Ident y"
+                    T: I64 @@ 35..36: I64([2])
+            "#]],
+        );
+
+        check(
+            "do\n let x := 1\n x\nend",
+            expect![[r#"
+                Root
+                  0..21: "do"
+                   4..14: "let x"
+                    T: I64 @@ 13..14: I64([1])
+                   16..17: "Ident x"
+                  0..21: "end --do"
+            "#]],
+        );
+
+        check(
+            "if x do\n let y := 1\n y\nelse\n 0\nend",
+            expect![[r#"
+                Root
+                  0..2: "if"
+                   3..4: "Ident x"
+                  5..7: "do"
+                   9..22: "do"
+                    9..19: "let y"
+                     T: I64 @@ 18..19: I64([1])
+                    21..22: "Ident y"
+                   9..22: "end --do"
+                  23..27: "else"
+                   T: I64 @@ 29..30: I64([0])
+                  31..34: "end --if"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_define_err() {
+        check(
+            "let 1 := 2",
+            expect![[r#"
+                Root
+                  T: I64 @@ 9..10: I64([2])
+                Errors
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(4..5), line: 1, col: 5 }, found: Err(TokenId(2)), expect: Some(Ident) }, missing: [Ident] }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..3), line: 1, col: 3 }, found: Assign(TokenId(3)), expect: None }, missing: [Expr] }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(4..5), line: 1, col: 5 }, found: Atom(TokenId(2)), expect: Some(Ident) }, missing: [Ident] }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(6..8), line: 1, col: 8 }, found: Atom(TokenId(4)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+
+        check(
+            "let x 1",
+            expect![[r#"
+                Root
+                Errors
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(6..7), line: 1, col: 7 }, found: Atom(TokenId(3)), expect: Some(Assign) }, missing: [Assign] }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..3), line: 1, col: 3 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_vector() {
+        check(
+            "[1; 2; 3]",
+            expect![[r#"
+                Root
+                  0..9: "[?]"
+                   row 1
+                    T: I64 @@ 1..2: I64([1])
+                   row 2
+                    T: I64 @@ 4..5: I64([2])
+                   row 3
+                    T: I64 @@ 7..8: I64([3])
+            "#]],
+        );
+        check(
+            "[Int; 1; 2;]",
+            expect![[r#"
+                Root
+                  0..12: "[it:Int]"
+                   row 1
+                    T: I64 @@ 6..7: I64([1])
+                   row 2
+                    T: I64 @@ 9..10: I64([2])
+            "#]],
+        );
+        check(
+            "[name:Str, price:Dec;\n 'Soda', 3.0;\n 'Tea', 1.5 + 1.0]",
+            expect![[r#"
+                Root
+                  0..54: "[name:Str, price:Dec]"
+                   row 1
+                    T: Utf8 @@ 23..29: Utf8(["Soda"])
+                    T: Decimal @@ 31..34: Decimal([3.0])
+                   row 2
+                    T: Utf8 @@ 37..42: Utf8(["Tea"])
+                    44..53: Add
+                     T: Decimal @@ 44..47: Decimal([1.5])
+                     T: Decimal @@ 50..53: Decimal([1.0])
+            "#]],
+        );
+        check(
+            "[Int]",
+            expect![[r#"
+                Root
+                  0..5: "[it:Int]"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_vector_err() {
+        check(
+            "[1, 2; 3]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorHeader { span: Span { file_id: NodeId(1), range: RangeCode(4..5), line: 1, col: 5 }, msg: "Can't infer the names of many columns, add a header like `[a:Int, b:Int]`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "[a:Int, b:Int; 1, 2; 3]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorShape { span: Span { file_id: NodeId(1), range: RangeCode(21..22), line: 1, col: 22 }, row: 2, expected: 2, found: 1 }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "[a:Int, b:Int; 1, 2, 3]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorShape { span: Span { file_id: NodeId(1), range: RangeCode(21..22), line: 1, col: 22 }, row: 1, expected: 2, found: 3 }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "[1, ; 3]\n4",
+            expect![[r#"
+                Root
+                  T: I64 @@ 9..10: I64([4])
+                Errors
+                 NoExpr { span: Span { file_id: NodeId(1), range: RangeCode(4..5), line: 1, col: 5 }, found: ";" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Atom(TokenId(8)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "[1 2]",
+            expect![[r#"
+                Root
+                Errors
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(3..4), line: 1, col: 4 }, found: Atom(TokenId(3)), expect: Some(Sep) }, missing: [Sep] }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "[1; 2",
+            expect![[r#"
+                Root
+                Errors
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Atom(TokenId(4)), expect: Some(Close) }, missing: [Close] }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "[]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorHeader { span: Span { file_id: NodeId(1), range: RangeCode(0..2), line: 1, col: 1 }, msg: "Can't infer the type of a empty vector, add a header like `[Int]`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "[a:Int, a:Str]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorHeader { span: Span { file_id: NodeId(1), range: RangeCode(8..13), line: 1, col: 9 }, msg: "The column `a` is duplicated" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "[Int, Str]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorHeader { span: Span { file_id: NodeId(1), range: RangeCode(1..4), line: 1, col: 4 }, msg: "The columns need a name when are many, like `[name:Str, qty:Int]`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "[a:Foo]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorHeader { span: Span { file_id: NodeId(1), range: RangeCode(3..6), line: 1, col: 6 }, msg: "Unknown type `Foo`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "[1; Int]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorHeader { span: Span { file_id: NodeId(1), range: RangeCode(4..7), line: 1, col: 7 }, msg: "The types of the columns must be in the first row" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_tree() {
+        check(
+            "Tree[|\n pk id:Int, name:Str;\n 2, 'world';\n 1, 'hello'\n|]",
+            expect![[r#"
+                Root
+                  0..56: "Tree[|id:Int, name:Str|] pk id"
+                   row 1
+                    T: I64 @@ 30..31: I64([2])
+                    T: Utf8 @@ 33..40: Utf8(["world"])
+                   row 2
+                    T: I64 @@ 43..44: I64([1])
+                    T: Utf8 @@ 46..53: Utf8(["hello"])
+            "#]],
+        );
+        check(
+            "Tree[| pk invoice:Int, pk line:Int, qty:Int; 1, 1, 10; 1, 2, 5 |]",
+            expect![[r#"
+                Root
+                  0..65: "Tree[|invoice:Int, line:Int, qty:Int|] pk invoice, line"
+                   row 1
+                    T: I64 @@ 45..46: I64([1])
+                    T: I64 @@ 48..49: I64([1])
+                    T: I64 @@ 51..53: I64([10])
+                   row 2
+                    T: I64 @@ 55..56: I64([1])
+                    T: I64 @@ 58..59: I64([2])
+                    T: I64 @@ 61..62: I64([5])
+            "#]],
+        );
+        check(
+            "Tree[| pk Int; 1 |]",
+            expect![[r#"
+                Root
+                  0..19: "Tree[|it:Int|] pk it"
+                   row 1
+                    T: I64 @@ 15..16: I64([1])
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_tree_err() {
+        check(
+            "Tree[| id:Int; 1 |]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorHeader { span: Span { file_id: NodeId(1), range: RangeCode(0..19), line: 1, col: 4 }, msg: "A `Tree` need a key, add `pk` to a column like `Tree[| pk id:Int |]`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(4..6), line: 1, col: 6 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "Tree[| 1; 2 |]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorHeader { span: Span { file_id: NodeId(1), range: RangeCode(0..14), line: 1, col: 4 }, msg: "A `Tree` need a key, add `pk` to a column like `Tree[| pk id:Int |]`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(4..6), line: 1, col: 6 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "[| pk id:Int; 1 |]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorHeader { span: Span { file_id: NodeId(1), range: RangeCode(0..2), line: 1, col: 2 }, msg: "Expected the type of the relation before `[|`, like `Tree[| pk id:Int |]`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..2), line: 1, col: 2 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "Set[| pk id:Int; 1 |]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorHeader { span: Span { file_id: NodeId(1), range: RangeCode(0..3), line: 1, col: 3 }, msg: "Unknown relation `Set`, expected `Tree`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(3..5), line: 1, col: 5 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "[pk id:Int; 1]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorHeader { span: Span { file_id: NodeId(1), range: RangeCode(1..10), line: 1, col: 3 }, msg: "Only a `Tree` can have a key, like `Tree[| pk id:Int |]`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..1), line: 1, col: 1 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "Tree[| pk 1 |]",
+            expect![[r#"
+                Root
+                Errors
+                 VectorHeader { span: Span { file_id: NodeId(1), range: RangeCode(10..11), line: 1, col: 11 }, msg: "Expected a column after `pk`, like `pk id:Int`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(4..6), line: 1, col: 6 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_query() {
+        check(
+            "x ?where #qty > 1 ?select #name, #0 ?limit 3",
+            expect![[r#"
+                Root
+                  0..44: "?limit"
+                   0..35: "?select"
+                    0..17: "?where"
+                     0..1: "Ident x"
+                     9..17: Greater
+                      9..13: "Column #qty"
+                      T: I64 @@ 16..17: I64([1])
+                    26..31: "Column #name"
+                    33..35: "Column #0"
+                   T: I64 @@ 43..44: I64([3])
+            "#]],
+        );
+        check(
+            "x ?select #sales.id",
+            expect![[r#"
+            Root
+              0..19: "?select"
+               0..1: "Ident x"
+               10..19: "Column #sales.id"
+        "#]],
+        );
+        check(
+            "let y := x ?distinct ?sort",
+            expect![[r#"
+                Root
+                  0..26: "let y"
+                   9..26: "?sort"
+                    9..20: "?distinct"
+                     9..10: "Ident x"
+            "#]],
+        );
+        check(
+            "x ?sort #qty desc, #name",
+            expect![[r#"
+                Root
+                  0..24: "?sort"
+                   0..1: "Ident x"
+                   8..17: "Column #qty desc"
+                   19..24: "Column #name"
+            "#]],
+        );
+        check(
+            "x ?where flag and not #1",
+            expect![[r#"
+                Root
+                  0..24: "?where"
+                   0..1: "Ident x"
+                   9..24: And
+                    9..13: "Ident flag"
+                    18..24: "not"
+                     22..24: "Column #1"
+            "#]],
+        );
+        check(
+            "x ?group #kind, sum(#qty), count(#id)",
+            expect![[r#"
+                Root
+                  0..36: "?group"
+                   0..1: "Ident x"
+                   9..14: "Column #kind"
+                   16..24: "sum()"
+                    20..24: "Column #qty"
+                   27..36: "count()"
+                    33..36: "Column #id"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_query_err() {
+        check(
+            "x ?group #kind, sum(1)",
+            expect![[r#"
+                Root
+                Errors
+                 Query { span: Span { file_id: NodeId(1), range: RangeCode(16..21), line: 1, col: 19 }, msg: "Expected a column or the aggregate of a column, like `#kind` or `sum(#qty)`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(2..8), line: 1, col: 8 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "x ?top 3",
+            expect![[r#"
+                Root
+                Errors
+                 Query { span: Span { file_id: NodeId(1), range: RangeCode(2..6), line: 1, col: 6 }, msg: "Unknown operator `?top`, expected one of `?where`, `?select`, `?deselect`, `?limit`, `?skip`, `?distinct`, `?sort` or `?group`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(2..6), line: 1, col: 6 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "x ?select 1",
+            expect![[r#"
+                Root
+                Errors
+                 Query { span: Span { file_id: NodeId(1), range: RangeCode(10..11), line: 1, col: 11 }, msg: "Expected a column like `#name` or `#0`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(2..9), line: 1, col: 9 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "x ?select",
+            expect![[r#"
+                Root
+                Errors
+                 Query { span: Span { file_id: NodeId(1), range: RangeCode(2..9), line: 1, col: 9 }, msg: "Missing the arguments of `?select`, like `?select #name, #0`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(2..9), line: 1, col: 9 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "x ?limit 1, 2",
+            expect![[r#"
+                Root
+                Errors
+                 Query { span: Span { file_id: NodeId(1), range: RangeCode(12..13), line: 1, col: 13 }, msg: "Too many arguments for `?limit`, like `?limit 10`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(2..8), line: 1, col: 8 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "x ?where 1",
+            expect![[r#"
+                Root
+                Errors
+                 BoolExpr { span: Span { file_id: NodeId(1), range: RangeCode(9..10), line: 1, col: 10 }, found: "1" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(2..8), line: 1, col: 8 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "x ?select #a desc",
+            expect![[r#"
+                Root
+                Errors
+                 Query { span: Span { file_id: NodeId(1), range: RangeCode(10..17), line: 1, col: 12 }, msg: "Only `?sort` can order the columns, like `?sort #name desc`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(2..9), line: 1, col: 9 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "x ?sort 1 asc",
+            expect![[r#"
+                Root
+                Errors
+                 Query { span: Span { file_id: NodeId(1), range: RangeCode(8..9), line: 1, col: 9 }, msg: "Expected a column like `#name` or `#0`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(2..7), line: 1, col: 7 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "x ?select #a,",
+            expect![[r#"
+                Root
+                Errors
+                 NoExpr { span: Span { file_id: NodeId(1), range: RangeCode(12..13), line: 1, col: 13 }, found: "," }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(2..9), line: 1, col: 9 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_call() {
+        check(
+            "Int('1') + x.to_dec().to_str()",
+            expect![[r#"
+                Root
+                  0..29: Add
+                   0..7: "Int()"
+                    T: Utf8 @@ 4..7: Utf8(["1"])
+                   11..29: "to_str()"
+                    11..20: "to_dec()"
+                     11..12: "Ident x"
+            "#]],
+        );
+        check(
+            "to_int(1.5, 'up')",
+            expect![[r#"
+                Root
+                  0..16: "to_int()"
+                   T: Decimal @@ 7..10: Decimal([1.5])
+                   T: Utf8 @@ 12..16: Utf8(["up"])
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_call_err() {
+        check(
+            "Int(1",
+            expect![[r#"
+                Root
+                Errors
+                 Call { span: Span { file_id: NodeId(1), range: RangeCode(3..4), line: 1, col: 4 }, msg: "Missing the `)` of the call to `Int`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(3..4), line: 1, col: 4 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "x.to_str",
+            expect![[r#"
+                Root
+                Errors
+                 Call { span: Span { file_id: NodeId(1), range: RangeCode(1..2), line: 1, col: 2 }, msg: "Expected a call after the `.`, like `x.to_str()`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(1..2), line: 1, col: 2 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_enum() {
+        check(
+            "enum Status do\n case Active\n case Inactive\nend",
+            expect![[r#"
+                Root
+                  0..46: "enum Status"
+                   case Active
+                   case Inactive
+            "#]],
+        );
+        check(
+            "enum Option[T] do case Some(T) case None end",
+            expect![[r#"
+                Root
+                  0..44: "enum Option[T]"
+                   case Some(T)
+                   case None
+            "#]],
+        );
+        check(
+            "enum Value2: Value do\n case Pair(Str, Value2)\nend",
+            expect![[r#"
+                Root
+                  0..49: "enum Value2: Value"
+                   case Pair(Str, Value2)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_enum_err() {
+        check(
+            "enum A do case X end\nenum B do case X end",
+            expect![[r#"
+                Root
+                  0..20: "enum A"
+                   case X
+                Errors
+                 Enum { span: Span { file_id: NodeId(1), range: RangeCode(21..41), line: 2, col: 4 }, msg: "The case `X` is already defined in `A`: enum = B" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(21..25), line: 2, col: 4 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "enum Status do end",
+            expect![[r#"
+                Root
+                Errors
+                 Enum { span: Span { file_id: NodeId(1), range: RangeCode(0..4), line: 1, col: 4 }, msg: "A `enum` need at least one case, like `case None`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..4), line: 1, col: 4 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "enum Int do case A end",
+            expect![[r#"
+                Root
+                Errors
+                 Enum { span: Span { file_id: NodeId(1), range: RangeCode(5..8), line: 1, col: 8 }, msg: "The type `Int` is builtin, use other name" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..4), line: 1, col: 4 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "enum Status do\n 1\nend",
+            expect![[r#"
+                Root
+                  T: I64 @@ 16..17: I64([1])
+                Errors
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(16..17), line: 2, col: 2 }, found: Err(TokenId(5)), expect: Some(Kw(Case)) }, missing: [Kw(Case)] }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..4), line: 1, col: 4 }, found: Atom(TokenId(5)), expect: None }, missing: [Expr] }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(21..24), line: 3, col: 3 }, found: Eof(TokenId(0)), expect: None }, missing: [ExprIncomplete] }
+            "#]],
+        );
+        check(
+            "enum Pair[T do case A end",
+            expect![[r#"
+                Root
+                Errors
+                 Enum { span: Span { file_id: NodeId(1), range: RangeCode(9..10), line: 1, col: 10 }, msg: "Missing the `]`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..4), line: 1, col: 4 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "enum Option do case Some(T end",
+            expect![[r#"
+                Root
+                Errors
+                 Enum { span: Span { file_id: NodeId(1), range: RangeCode(24..25), line: 1, col: 25 }, msg: "Missing the `)`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..4), line: 1, col: 4 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "enum do case A end",
+            expect![[r#"
+                Root
+                  13..14: "Ident A"
+                Errors
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(5..7), line: 1, col: 7 }, found: Err(TokenId(2)), expect: Some(Ident) }, missing: [Ident] }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..4), line: 1, col: 4 }, found: Do(TokenId(2)), expect: None }, missing: [Expr] }
+                 NoExpr { span: Span { file_id: NodeId(1), range: RangeCode(8..12), line: 1, col: 12 }, found: "case" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(5..7), line: 1, col: 7 }, found: Atom(TokenId(4)), expect: None }, missing: [Expr] }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(18..21), line: 1, col: 18 }, found: Eof(TokenId(0)), expect: None }, missing: [ExprIncomplete] }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_match() {
+        check(
+            "match 1 do case 0 do 'zero' case 1..10 do 'small' else 'big' end",
+            expect![[r#"
+                Root
+                  0..64: "match"
+                   T: I64 @@ 6..7: I64([1])
+                   16..17: "case 0"
+                    T: Utf8 @@ 21..27: Utf8(["zero"])
+                   33..38: "case 1..10"
+                    T: Utf8 @@ 42..49: Utf8(["small"])
+                   50..54: "else"
+                    T: Utf8 @@ 55..60: Utf8(["big"])
+            "#]],
+        );
+        check(
+            "enum Option[T] do case Some(T) case None end\nmatch None do\n case Some(x) do x\n case None do 0\nend",
+            expect![[r#"
+                Root
+                  0..44: "enum Option[T]"
+                   case Some(T)
+                   case None
+                  45..97: "match"
+                   51..55: "Ident None"
+                   65..71: "case Some(x)"
+                    76..77: "Ident x"
+                   84..88: "case None"
+                    T: I64 @@ 92..93: I64([0])
+            "#]],
+        );
+        check(
+            "match Tree[| pk a:Int, b:Int; 1, 2 |] do case [| a: 1, b: x |] do x case [| a: y |] do y end",
+            expect![[r#"
+                Root
+                  0..92: "match"
+                   6..37: "Tree[|a:Int, b:Int|] pk a"
+                    row 1
+                     T: I64 @@ 30..31: I64([1])
+                     T: I64 @@ 33..34: I64([2])
+                   46..62: "case [| a: 1, b: x |]"
+                    66..67: "Ident x"
+                   73..83: "case [| a: y |]"
+                    87..88: "Ident y"
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_match_err() {
+        check(
+            "enum Option[T] do case Some(T) case None end\nmatch None do case Some(x) do x end",
+            expect![[r#"
+                Root
+                  0..44: "enum Option[T]"
+                   case Some(T)
+                   case None
+                Errors
+                 Match { span: Span { file_id: NodeId(1), range: RangeCode(45..55), line: 2, col: 5 }, msg: "The `match` not cover the cases `None`, add them or a `else`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(45..50), line: 2, col: 5 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "match 1 do case x do x case 1 do 2 end",
+            expect![[r#"
+                Root
+                Errors
+                 Match { span: Span { file_id: NodeId(1), range: RangeCode(28..29), line: 1, col: 29 }, msg: "The case is unreachable, the cases before it match all its values" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..5), line: 1, col: 5 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "match 1 do case 1 do 1 end",
+            expect![[r#"
+                Root
+                Errors
+                 Match { span: Span { file_id: NodeId(1), range: RangeCode(0..7), line: 1, col: 5 }, msg: "The `match` not cover all the values, add a `else`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..5), line: 1, col: 5 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "match true do case true do 1 case false do 0 case true do 2 end",
+            expect![[r#"
+                Root
+                Errors
+                 Match { span: Span { file_id: NodeId(1), range: RangeCode(50..54), line: 1, col: 54 }, msg: "The case is unreachable, the cases before it match all its values" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..5), line: 1, col: 5 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "match 1 do case 10..1 do 1 else 0 end",
+            expect![[r#"
+                Root
+                Errors
+                 Match { span: Span { file_id: NodeId(1), range: RangeCode(16..21), line: 1, col: 18 }, msg: "The range is empty, the start must be less than the end" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..5), line: 1, col: 5 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "match 1 do case Some(x) do x else 0 end",
+            expect![[r#"
+                Root
+                Errors
+                 Match { span: Span { file_id: NodeId(1), range: RangeCode(16..22), line: 1, col: 20 }, msg: "The case `Some` is not defined" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..5), line: 1, col: 5 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+        check(
+            "match 1 do end",
+            expect![[r#"
+                Root
+                Errors
+                 Match { span: Span { file_id: NodeId(1), range: RangeCode(0..5), line: 1, col: 5 }, msg: "A `match` need at least one case, like `case x do x`" }
+                 Incomplete { err: CheckError { span: Span { file_id: NodeId(1), range: RangeCode(0..5), line: 1, col: 5 }, found: Eof(TokenId(0)), expect: None }, missing: [Expr] }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn parse_if_lines() {
+        check(
+            "if true do\n 1\nelse\n if false do 2 else 3 end\nend",
+            expect![[r##"
+Root
+  0..2: "if"
+    @@ 3..7: Bool([true])
+  8..10: "do"
+   T: I64 @@ 12..13: I64([1])
+  14..18: "else"
+   20..22: "if"
+     @@ 23..28: Bool([false])
+   29..31: "do"
+    T: I64 @@ 32..33: I64([2])
+   34..38: "else"
+    T: I64 @@ 39..40: I64([3])
+   41..44: "end --if"
+  45..48: "end --if"
 "##]],
         );
     }
@@ -8,6 +8,7 @@
 //! only prepare the code to be linearized to the next pass
 //!
 use std::fmt;
+use std::ops::Range;
 use std::rc::Rc;
 
 use crate::lexer::{Lexer, Scanner};
@@ -41,24 +42,27 @@ impl fmt::Display for Pratt<'_> {
                 write!(f, "{}: {}", &self.code[t.range], t.kind)
             }
             S::Block(rest) => {
-                let last = rest.len() - 1;
                 for (pos, s) in rest.iter().enumerate() {
                     let p = Pratt {
                         ast: s.clone(),
                         code: self.code,
                         tokens: self.tokens.clone(),
                     };
-                    if pos < last {
-                        write!(f, "{}\n", p)?
-                    } else {
-                        write!(f, "{}", p)?
+                    if pos > 0 {
+                        writeln!(f)?
                     }
+                    write!(f, "{}", p)?
                 }
                 Ok(())
             }
             S::Cons(head, rest) => {
                 let head = self.tokens.get(*head);
-                write!(f, "({}", head.kind)?;
+                if head.kind == Syntax::Query {
+                    // Show which relational operator, not only that is one
+                    write!(f, "({}", &self.code[head.range])?;
+                } else {
+                    write!(f, "({}", head.kind)?;
+                }
                 for s in rest {
                     let p = Pratt {
                         ast: s.clone(),
@@ -81,9 +85,14 @@ impl fmt::Display for Pratt<'_> {
     }
 }
 
+/// The relational operators bind looser than any expression, so `rel ?where #a = 1 ?limit 3`
+/// is a chain of `?where` & `?limit` over `rel`
+const QUERY_BP: u8 = 2;
+
 fn prefix_binding_power(op: Syntax) -> Option<((), u8)> {
     let res = match op {
-        Syntax::Plus => ((), 9),
+        Syntax::NotKw => ((), 7),
+        Syntax::Plus | Syntax::Minus => ((), 13),
         _ => return None,
     };
     Some(res)
@@ -91,7 +100,8 @@ fn prefix_binding_power(op: Syntax) -> Option<((), u8)> {
 
 fn postfix_binding_power(op: Syntax) -> Option<(u8, ())> {
     let res = match op {
-        Syntax::LSquare => (11, ()),
+        Syntax::Query => (QUERY_BP, ()),
+        Syntax::LSquare => (15, ()),
         _ => return None,
     };
     Some(res)
@@ -99,24 +109,489 @@ fn postfix_binding_power(op: Syntax) -> Option<(u8, ())> {
 
 fn infix_binding_power(op: Syntax) -> Option<(u8, u8)> {
     let res = match op {
-        Syntax::Equals => (2, 1),
-        Syntax::Question => (4, 3),
-        Syntax::Plus | Syntax::Minus => (5, 6),
-        Syntax::Star | Syntax::Slash => (7, 8),
-        Syntax::Point => (14, 13),
+        Syntax::Assign => (1, 0),
+        Syntax::Question => (2, 1),
+        Syntax::OrKw => (3, 4),
+        Syntax::AndKw => (5, 6),
+        Syntax::Equals
+        | Syntax::NotEquals
+        | Syntax::Less
+        | Syntax::LessThan
+        | Syntax::Greater
+        | Syntax::GreaterThan => (7, 8),
+        Syntax::Plus | Syntax::Minus => (9, 10),
+        Syntax::Star | Syntax::Slash => (11, 12),
+        Syntax::Point => (17, 18),
+        Syntax::Colon => (20, 19),
         _ => return None,
     };
     Some(res)
 }
 
+/// The keywords that close an expression/block, so the parser must stop there
+fn is_terminator(op: Syntax) -> bool {
+    matches!(
+        op,
+        Syntax::DoKw | Syntax::ElseKw | Syntax::EndKw | Syntax::CaseKw
+    )
+}
+
+fn skip_lines(lexer: &mut Scanner) {
+    while lexer.peek().kind == Syntax::Cr {
+        lexer.next();
+    }
+}
+
+/// Eat the keyword `kw` if is the next token (ignoring new lines)
+fn expect_kw(lexer: &mut Scanner, kw: Syntax) -> Option<Token> {
+    skip_lines(lexer);
+    let t = lexer.peek();
+    if t.kind == kw {
+        lexer.next();
+        Some(t)
+    } else {
+        None
+    }
+}
+
+/// Parse the expressions, one by line, until found one of the `close` keywords
+fn block(lexer: &mut Scanner, close: &[Syntax]) -> S {
+    let mut lines = Vec::new();
+    loop {
+        skip_lines(lexer);
+        let next = lexer.peek();
+        if next.kind == Syntax::Eof || close.contains(&next.kind) {
+            break;
+        }
+        lines.push(expr_bp(lexer, 0));
+    }
+
+    if lines.len() == 1 {
+        lines.pop().unwrap()
+    } else {
+        S::Block(lines)
+    }
+}
+
+/// if expr do block else block end
+///
+/// Is encoded as `(if expr (do block (else block end)))` so the checker can see it linearly
+fn expr_if(lexer: &mut Scanner, t: Token) -> S {
+    let check = expr_bp(lexer, 0);
+    let mut rest = vec![check];
+
+    if let Some(do_t) = expect_kw(lexer, Syntax::DoKw) {
+        let if_true = block(lexer, &[Syntax::ElseKw, Syntax::EndKw]);
+        let mut branch = vec![if_true];
+
+        if let Some(else_t) = expect_kw(lexer, Syntax::ElseKw) {
+            let if_false = block(lexer, &[Syntax::EndKw]);
+            let mut tail = vec![if_false];
+            if let Some(end_t) = expect_kw(lexer, Syntax::EndKw) {
+                tail.push(S::Keyword(end_t.kind, end_t.id));
+            }
+            branch.push(S::Cons(else_t.id, tail));
+        } else if let Some(end_t) = expect_kw(lexer, Syntax::EndKw) {
+            branch.push(S::Keyword(end_t.kind, end_t.id));
+        }
+        rest.push(S::Cons(do_t.id, branch));
+    }
+
+    S::Cons(t.id, rest)
+}
+
+/// do block end
+///
+/// Is encoded as `(do block end)`
+fn expr_do(lexer: &mut Scanner, t: Token) -> S {
+    let body = block(lexer, &[Syntax::EndKw]);
+    let mut rest = vec![body];
+    if let Some(end_t) = expect_kw(lexer, Syntax::EndKw) {
+        rest.push(S::Keyword(end_t.kind, end_t.id));
+    }
+    S::Cons(t.id, rest)
+}
+
+/// let|var ident := expr
+///
+/// Is encoded as `(let ident := expr)` so the checker see it in the same order of the source
+fn expr_define(lexer: &mut Scanner, t: Token) -> S {
+    let name = lexer.peek();
+    if name.kind != Syntax::Ident {
+        return S::Cons(t.id, vec![S::Err(name.id)]);
+    }
+    lexer.next();
+    let mut rest = vec![S::Atom(name.id)];
+
+    let assign = lexer.peek();
+    if assign.kind == Syntax::Assign {
+        lexer.next();
+        rest.push(S::Keyword(assign.kind, assign.id));
+        rest.push(expr_bp(lexer, 0));
+    }
+    S::Cons(t.id, rest)
+}
+
+/// The `Ident` at the cursor as a atom, or a error on it without consuming it
+fn expect_ident(lexer: &mut Scanner) -> S {
+    let t = lexer.peek();
+    if t.kind == Syntax::Ident {
+        lexer.next();
+        S::Atom(t.id)
+    } else {
+        S::Err(t.id)
+    }
+}
+
+/// The idents between `open` & `close`, separated by `,`. A missing `close` is a error on
+/// the `open`
+fn ident_list(lexer: &mut Scanner, open: Token, close: Syntax) -> S {
+    let mut rest = Vec::new();
+    loop {
+        match expect_ident(lexer) {
+            S::Atom(x) => rest.push(S::Atom(x)),
+            _ if lexer.peek().kind == close && rest.is_empty() => {}
+            err => {
+                rest.push(err);
+                break;
+            }
+        }
+        let next = lexer.peek();
+        if next.kind == Syntax::Comma {
+            lexer.next();
+        } else if next.kind == close {
+            lexer.next();
+            break;
+        } else {
+            rest.push(S::Err(open.id));
+            break;
+        }
+    }
+    S::Cons(open.id, rest)
+}
+
+/// enum Name[T]: Parent do case A case B(T, Int) end
+///
+/// Is encoded as `(enum Name ([ T) (: Parent) (do (case A) (case B (( T Int)) end))`, the parameters
+/// & the parent are optional. The cases are one by line, until the `end`
+fn expr_enum(lexer: &mut Scanner, t: Token) -> S {
+    let mut rest = vec![expect_ident(lexer)];
+    if let Some(S::Err(_)) = rest.last() {
+        return S::Cons(t.id, rest);
+    }
+
+    let next = lexer.peek();
+    if next.kind == Syntax::LSquare {
+        lexer.next();
+        rest.push(ident_list(lexer, next, Syntax::RSquare));
+    }
+    let next = lexer.peek();
+    if next.kind == Syntax::Colon {
+        lexer.next();
+        rest.push(S::Cons(next.id, vec![expect_ident(lexer)]));
+    }
+
+    let do_t = match expect_kw(lexer, Syntax::DoKw) {
+        Some(do_t) => do_t,
+        None => {
+            rest.push(S::Err(lexer.peek().id));
+            return S::Cons(t.id, rest);
+        }
+    };
+    let mut cases = Vec::new();
+    loop {
+        skip_lines(lexer);
+        let next = lexer.peek();
+        match next.kind {
+            Syntax::CaseKw => {
+                lexer.next();
+                let name = expect_ident(lexer);
+                let mut case = vec![name];
+                let open = lexer.peek();
+                if matches!(case[0], S::Atom(_)) && open.kind == Syntax::LParen {
+                    lexer.next();
+                    case.push(ident_list(lexer, open, Syntax::RParen));
+                }
+                cases.push(S::Cons(next.id, case));
+            }
+            Syntax::EndKw => {
+                lexer.next();
+                cases.push(S::Keyword(next.kind, next.id));
+                break;
+            }
+            _ => {
+                cases.push(S::Err(next.id));
+                break;
+            }
+        }
+    }
+    rest.push(S::Cons(do_t.id, cases));
+    S::Cons(t.id, rest)
+}
+
+/// match value do case pattern do block else block end
+///
+/// Is encoded as `(match value (do (case pattern (do block)) (else block) end))`, the cases
+/// are one after the other until the `end`
+fn expr_match(lexer: &mut Scanner, t: Token) -> S {
+    let mut rest = vec![expr_bp(lexer, 0)];
+    let do_t = match expect_kw(lexer, Syntax::DoKw) {
+        Some(do_t) => do_t,
+        None => {
+            rest.push(S::Err(lexer.peek().id));
+            return S::Cons(t.id, rest);
+        }
+    };
+
+    let close = [Syntax::CaseKw, Syntax::ElseKw, Syntax::EndKw];
+    let mut cases = Vec::new();
+    loop {
+        skip_lines(lexer);
+        let next = lexer.peek();
+        match next.kind {
+            Syntax::CaseKw => {
+                lexer.next();
+                let mut case = vec![expr_pattern(lexer)];
+                let body_t = expect_kw(lexer, Syntax::DoKw);
+                match body_t {
+                    Some(body_t) => case.push(S::Cons(body_t.id, vec![block(lexer, &close)])),
+                    None => case.push(S::Err(lexer.peek().id)),
+                }
+                cases.push(S::Cons(next.id, case));
+                if body_t.is_none() {
+                    break;
+                }
+            }
+            Syntax::ElseKw => {
+                lexer.next();
+                cases.push(S::Cons(next.id, vec![block(lexer, &close)]));
+            }
+            Syntax::EndKw => {
+                lexer.next();
+                cases.push(S::Keyword(next.kind, next.id));
+                break;
+            }
+            _ => {
+                cases.push(S::Err(next.id));
+                break;
+            }
+        }
+    }
+    rest.push(S::Cons(do_t.id, cases));
+    S::Cons(t.id, rest)
+}
+
+/// The pattern of a `case`: a value, a range `1..10`, a case of a `enum` like `Some(x)` or
+/// a row like `[| name: x |]`.
+///
+/// The range is encoded as `(.. start end)` & the case of a `enum` like a call
+fn expr_pattern(lexer: &mut Scanner) -> S {
+    let t = lexer.next();
+    let pattern = match t.kind {
+        Syntax::Ident if lexer.peek().kind == Syntax::LParen => {
+            let open = lexer.next();
+            expr_call(lexer, open, vec![S::Atom(t.id)], expr_pattern)
+        }
+        Syntax::LRel => expr_row_pattern(lexer, t),
+        _ => expr_lhs(lexer, t),
+    };
+
+    let next = lexer.peek();
+    if next.kind == Syntax::DotDot {
+        lexer.next();
+        let t = lexer.next();
+        S::Cons(next.id, vec![pattern, expr_lhs(lexer, t)])
+    } else {
+        pattern
+    }
+}
+
+/// [| name: pattern, name: pattern |]
+///
+/// Is encoded as `([| (: name pattern) (: name pattern) |])`. If the `|]` is missing, the last
+/// child is a error on the `[|`
+fn expr_row_pattern(lexer: &mut Scanner, open: Token) -> S {
+    let mut rest = Vec::new();
+    loop {
+        let name = expect_ident(lexer);
+        if let S::Err(_) = name {
+            rest.push(name);
+            break;
+        }
+        let colon = lexer.peek();
+        if colon.kind != Syntax::Colon {
+            rest.push(S::Err(colon.id));
+            break;
+        }
+        lexer.next();
+        rest.push(S::Cons(colon.id, vec![name, expr_pattern(lexer)]));
+
+        let next = lexer.peek();
+        match next.kind {
+            Syntax::Comma => {
+                lexer.next();
+            }
+            Syntax::RRel => {
+                lexer.next();
+                rest.push(S::Keyword(next.kind, next.id));
+                break;
+            }
+            _ => {
+                rest.push(S::Err(open.id));
+                break;
+            }
+        }
+    }
+    S::Cons(open.id, rest)
+}
+
+/// [cell, cell; cell, cell]
+///
+/// Is encoded as `([ cell , cell ; cell , cell ])`, keeping the separators so the checker
+/// can rebuild the rows & report the position of a missing cell
+fn expr_vector(lexer: &mut Scanner, t: Token) -> S {
+    expr_cells(lexer, t, Vec::new(), Syntax::RSquare)
+}
+
+/// Type[| cell, cell; cell, cell |]
+///
+/// Is encoded as `([| Type cell , cell ; cell , cell |])` like a vector, but the type of
+/// the relation is the first child. If the type is missing, is an error on the `[|`
+fn expr_rel(lexer: &mut Scanner, t: Token, of: S) -> S {
+    expr_cells(lexer, t, vec![of], Syntax::RRel)
+}
+
+fn expr_cells(lexer: &mut Scanner, t: Token, mut rest: Vec<S>, close: Syntax) -> S {
+    loop {
+        skip_lines(lexer);
+        let next = lexer.peek();
+        match next.kind {
+            Syntax::Comma | Syntax::Semicolon => {
+                lexer.next();
+                rest.push(S::Keyword(next.kind, next.id));
+            }
+            kind if kind == close => {
+                lexer.next();
+                rest.push(S::Keyword(next.kind, next.id));
+                break;
+            }
+            Syntax::Eof => break,
+            kind if is_terminator(kind) => break,
+            _ => rest.push(expr_bp(lexer, 0)),
+        }
+    }
+    S::Cons(t.id, rest)
+}
+
+/// rel ?op arg, arg
+///
+/// Is encoded as `(?op rel arg arg)`, without the commas. The arguments are optional, so
+/// the operator end at the next `?op`, line or close.
+///
+/// A argument followed by `asc` or `desc`, like in `?sort #price desc`, is encoded as
+/// `(desc arg)`
+fn expr_query(lexer: &mut Scanner, t: Token, rel: S) -> S {
+    let is_end = |kind: Syntax| {
+        [SyntaxKind::Eof, SyntaxKind::Close, SyntaxKind::Line].contains(&kind.is())
+            || is_terminator(kind)
+            || matches!(kind, Syntax::Query | Syntax::Comma | Syntax::Semicolon)
+    };
+
+    let mut rest = vec![rel];
+    if !is_end(lexer.peek().kind) {
+        loop {
+            let arg = expr_bp(lexer, QUERY_BP + 1);
+            let order = lexer.peek();
+            if order.kind.to_order().is_some() {
+                lexer.next();
+                rest.push(S::Cons(order.id, vec![arg]));
+            } else {
+                rest.push(arg);
+            }
+            let sep = lexer.peek();
+            if sep.kind != Syntax::Comma {
+                break;
+            }
+            lexer.next();
+            // A trailing `,` is missing an argument
+            if is_end(lexer.peek().kind) {
+                rest.push(S::Err(sep.id));
+                break;
+            }
+        }
+    }
+    S::Cons(t.id, rest)
+}
+
+/// name(arg, arg)
+///
+/// Is encoded as `(( name arg arg)`, without the commas. If the `)` is missing, the last
+/// child is a error on the `(`. Each argument is parsed with `arg`
+fn expr_call(lexer: &mut Scanner, open: Token, mut rest: Vec<S>, arg: fn(&mut Scanner) -> S) -> S {
+    if lexer.peek().kind == Syntax::RParen {
+        lexer.next();
+        return S::Cons(open.id, rest);
+    }
+    loop {
+        rest.push(arg(lexer));
+        match lexer.peek().kind {
+            Syntax::Comma => {
+                lexer.next();
+            }
+            Syntax::RParen => {
+                lexer.next();
+                break;
+            }
+            _ => {
+                rest.push(S::Err(open.id));
+                break;
+            }
+        }
+    }
+    S::Cons(open.id, rest)
+}
+
+/// value.name(arg, arg)
+///
+/// Is the same call than `name(value, arg, arg)`, so is encoded as `(( name value arg arg)`.
+/// If the `rhs` is not a call, is left as `(. value rhs)` for the checker to report it
+fn expr_method(lexer: &Scanner, point: Token, lhs: S, rhs: S) -> S {
+    match rhs {
+        S::Cons(open, mut rest) if lexer.get(open).kind == Syntax::LParen => {
+            rest.insert(1, lhs);
+            S::Cons(open, rest)
+        }
+        rhs => S::Cons(point.id, vec![lhs, rhs]),
+    }
+}
+
 fn expr_lhs(lexer: &mut Scanner, t: Token) -> S {
     match t.kind {
         Syntax::LParen => {
             let lhs = expr_bp(lexer, 0);
-            assert_eq!(lexer.next().kind, Syntax::RParen);
-            lhs
+            if lexer.peek().kind == Syntax::RParen {
+                lexer.next();
+                lhs
+            } else {
+                S::Err(t.id)
+            }
+        }
+        Syntax::Plus | Syntax::Minus => {
+            if let Some(((), r_bp)) = prefix_binding_power(t.kind) {
+                // In prefix position is an unary operator, not a binary one
+                let kind = if t.kind == Syntax::Plus {
+                    Syntax::Add
+                } else {
+                    Syntax::Neg
+                };
+                lexer.set_kind(t.id, kind);
+                let rhs = expr_bp(lexer, r_bp);
+                S::Cons(t.id, vec![rhs])
+            } else {
+                S::Err(t.id)
+            }
         }
-        Syntax::Plus => {
+        Syntax::NotKw => {
             if let Some(((), r_bp)) = prefix_binding_power(t.kind) {
                 let rhs = expr_bp(lexer, r_bp);
                 S::Cons(t.id, vec![rhs])
@@ -124,11 +599,26 @@ fn expr_lhs(lexer: &mut Scanner, t: Token) -> S {
                 S::Err(t.id)
             }
         }
-        Syntax::IfKw | Syntax::DoKw | Syntax::ElseKw | Syntax::EndKw => {
-            // let rhs = expr_bp(lexer, 0);
-            // S::Cons(t, vec![rhs])
-            S::Keyword(t.kind, t.id)
+        Syntax::IfKw => expr_if(lexer, t),
+        Syntax::DoKw => expr_do(lexer, t),
+        Syntax::LetKw | Syntax::VarKw => expr_define(lexer, t),
+        Syntax::EnumKw => expr_enum(lexer, t),
+        Syntax::MatchKw => expr_match(lexer, t),
+        Syntax::LSquare => expr_vector(lexer, t),
+        Syntax::LRel => expr_rel(lexer, t, S::Err(t.id)),
+        Syntax::PkKw => {
+            let rhs = expr_bp(lexer, 0);
+            S::Cons(t.id, vec![rhs])
+        }
+        Syntax::Ident if lexer.peek().kind == Syntax::LParen => {
+            let open = lexer.next();
+            expr_call(lexer, open, vec![S::Atom(t.id)], |lexer| expr_bp(lexer, 0))
         }
+        Syntax::Ident if lexer.peek().kind == Syntax::LRel => {
+            let open = lexer.next();
+            expr_rel(lexer, open, S::Atom(t.id))
+        }
+        Syntax::ElseKw | Syntax::EndKw => S::Keyword(t.kind, t.id),
         s => match s.is() {
             SyntaxKind::Atom => S::Atom(t.id),
             SyntaxKind::Eof => S::Eof(t.id),
@@ -154,7 +644,9 @@ fn expr_bp(lexer: &mut Scanner, min_bp: u8) -> S {
     loop {
         let next = lexer.peek();
 
-        if [SyntaxKind::Eof, SyntaxKind::Close].contains(&next.kind.is()) {
+        if [SyntaxKind::Eof, SyntaxKind::Close, SyntaxKind::Line].contains(&next.kind.is())
+            || is_terminator(next.kind)
+        {
             break;
         };
 
@@ -166,7 +658,9 @@ fn expr_bp(lexer: &mut Scanner, min_bp: u8) -> S {
             }
             lexer.next();
 
-            lhs = if op.is() == SyntaxKind::Open {
+            lhs = if op == Syntax::Query {
+                expr_query(lexer, next, lhs)
+            } else if op.is() == SyntaxKind::Open {
                 let rhs = expr_bp(lexer, 0);
                 //assert_eq!(lexer.next(), Token::Op(']'));
                 S::Cons(next.id, vec![lhs, rhs])
@@ -183,29 +677,36 @@ fn expr_bp(lexer: &mut Scanner, min_bp: u8) -> S {
             lexer.next();
 
             let rhs = expr_bp(lexer, r_bp);
-            lhs = S::Cons(next.id, vec![lhs, rhs]);
+            lhs = if op == Syntax::Point {
+                expr_method(lexer, next, lhs, rhs)
+            } else {
+                S::Cons(next.id, vec![lhs, rhs])
+            };
 
             continue;
         }
-        lexer.next();
-        if t.kind == Syntax::Cr || op == Syntax::Cr {
-            let rhs = expr_lhs(lexer, next);
-            lhs = S::Block(vec![lhs, rhs]);
-        } else {
-            lhs = expr_lhs(lexer, next);
-            let rhs = expr_bp(lexer, 0);
-
-            lhs = S::Cons(t.id, vec![lhs, rhs]);
-        }
+        // Not an operator, so the expression is done
+        break;
     }
 
     lhs
 }
 
 pub(crate) fn expr(code: &str) -> Pratt<'_> {
-    let lexer = Lexer::new(0.into(), code);
+    parse_tokens(code, Lexer::new(0.into(), code))
+}
+
+/// Parse only the `range` of the `code`, like a expression inside a string
+pub(crate) fn expr_at(code: &str, range: Range<usize>) -> Pratt<'_> {
+    parse_tokens(code, Lexer::at(0.into(), code, range))
+}
+
+fn parse_tokens<'a>(code: &'a str, lexer: Lexer<'_>) -> Pratt<'a> {
     let mut scanner = Scanner::from(lexer);
-    let ast = expr_bp(&mut scanner, 0);
+    let ast = match block(&mut scanner, &[]) {
+        S::Block(lines) if lines.is_empty() => S::Eof(scanner.peek().id),
+        ast => ast,
+    };
     Pratt {
         ast,
         code,
@@ -245,6 +746,15 @@ mod tests {
 
         let s = expr("dt'2000-01-01 09:10:11'");
         assert_eq!(s.to_string(), "dt'2000-01-01 09:10:11': DateTime");
+
+        let s = expr("i'1 day'");
+        assert_eq!(s.to_string(), "i'1 day': Interval");
+
+        let s = expr("12.50 USD");
+        assert_eq!(s.to_string(), "12.50 USD: Money");
+
+        let s = expr("c'a'");
+        assert_eq!(s.to_string(), "c'a': Char");
     }
 
     #[test]
@@ -258,7 +768,13 @@ mod tests {
         let s = expr("if true do false\n1 else true\n2 end");
         assert_eq!(
             s.to_string(),
-            "(if true: Bool (do false: Bool 1: Integer\nelse: else\ntrue: Bool\n2: Integer\nend: end))"
+            "(if true: Bool (do false: Bool\n1: Integer (else true: Bool\n2: Integer end: end)))"
+        );
+
+        let s = expr("if true do\n 1\nelse\n 2\nend\n3");
+        assert_eq!(
+            s.to_string(),
+            "(if true: Bool (do 1: Integer (else 2: Integer end: end)))\n3: Integer"
         );
     }
 
@@ -266,6 +782,180 @@ mod tests {
     fn ops() {
         let s = expr("1 + 2 * 3");
         assert_eq!(s.to_string(), "(+ 1: Integer (* 2: Integer 3: Integer))");
+
+        let s = expr("(1 + 2) * 3");
+        assert_eq!(s.to_string(), "(* (+ 1: Integer 2: Integer) 3: Integer)");
+
+        let s = expr("1 - 2 - 3");
+        assert_eq!(s.to_string(), "(- (- 1: Integer 2: Integer) 3: Integer)");
+
+        let s = expr("-1 * -2");
+        assert_eq!(s.to_string(), "(* (Neg 1: Integer) (Neg 2: Integer))");
+
+        let s = expr("+1.5 - 2");
+        assert_eq!(s.to_string(), "(- (Add 1.5: Decimal) 2: Integer)");
+    }
+
+    #[test]
+    fn logic() {
+        let s = expr("1 + 1 = 2 and not 2 < 1");
+        assert_eq!(
+            s.to_string(),
+            "(and (= (+ 1: Integer 1: Integer) 2: Integer) (not (< 2: Integer 1: Integer)))"
+        );
+
+        let s = expr("true or false and false");
+        assert_eq!(
+            s.to_string(),
+            "(or true: Bool (and false: Bool false: Bool))"
+        );
+
+        let s = expr("1 <> 2");
+        assert_eq!(s.to_string(), "(!= 1: Integer 2: Integer)");
+    }
+
+    #[test]
+    fn define() {
+        let s = expr("let x := 1 + 2");
+        assert_eq!(
+            s.to_string(),
+            "(let x: Ident :=: := (+ 1: Integer 2: Integer))"
+        );
+
+        let s = expr("var x := 1\nx := x * 2");
+        assert_eq!(
+            s.to_string(),
+            "(var x: Ident :=: := 1: Integer)\n(:= x: Ident (* x: Ident 2: Integer))"
+        );
+
+        let s = expr("do\n let x := 1\n x\nend");
+        assert_eq!(
+            s.to_string(),
+            "(do (let x: Ident :=: := 1: Integer)\nx: Ident end: end)"
+        );
+    }
+
+    #[test]
+    fn enums() {
+        let s = expr("enum Status do\n case Active\n case Inactive\nend");
+        assert_eq!(
+            s.to_string(),
+            "(enum Status: Ident (do (case Active: Ident) (case Inactive: Ident) end: end))"
+        );
+
+        let s = expr("enum Option[T]: Base do case Some(T, Int) case None end");
+        assert_eq!(
+            s.to_string(),
+            "(enum Option: Ident ([ T: Ident) (: Base: Ident) (do (case Some: Ident (( T: Ident Int: Ident)) (case None: Ident) end: end))"
+        );
+
+        let s = expr("enum Status do\n 1\nend");
+        assert_eq!(
+            s.to_string(),
+            "(enum Status: Ident (do ERR(1)))\n1: Integer\nend: end"
+        );
+    }
+
+    #[test]
+    fn matches() {
+        let s = expr("match x do\ncase Some(y) do y\ncase None do 0\nend");
+        assert_eq!(
+            s.to_string(),
+            "(match x: Ident (do (case (( Some: Ident y: Ident) (do y: Ident)) (case None: Ident (do 0: Integer)) end: end))"
+        );
+
+        let s = expr("match x do case 1..10 do 'a' case [| a: 1, b: z |] do 'b' else 'c' end");
+        assert_eq!(
+            s.to_string(),
+            "(match x: Ident (do (case (.. 1: Integer 10: Integer) (do 'a': String)) (case ([| (: a: Ident 1: Integer) (: b: Ident z: Ident) |]: |]) (do 'b': String)) (else 'c': String) end: end))"
+        );
+
+        let s = expr("match x do case -1 1 end");
+        assert_eq!(
+            s.to_string(),
+            "(match x: Ident (do (case (Neg 1: Integer) ERR(1))))\n1: Integer\nend: end"
+        );
+    }
+
+    #[test]
+    fn vectors() {
+        let s = expr("[1; 2; 3]");
+        assert_eq!(
+            s.to_string(),
+            "([ 1: Integer ;: ; 2: Integer ;: ; 3: Integer ]: ])"
+        );
+
+        let s = expr("[name:Str, price:Dec;\n 'Soda', 3.0;\n]");
+        assert_eq!(
+            s.to_string(),
+            "([ (: name: Ident Str: Ident) ,: , (: price: Ident Dec: Ident) ;: ; 'Soda': String ,: , 3.0: Decimal ;: ; ]: ])"
+        );
+
+        let s = expr("[1 + 2, -3");
+        assert_eq!(
+            s.to_string(),
+            "([ (+ 1: Integer 2: Integer) ,: , (Neg 3: Integer))"
+        );
+    }
+
+    #[test]
+    fn trees() {
+        let s = expr("Tree[| pk id:Int, name:Str; 1, 'a' |]");
+        assert_eq!(
+            s.to_string(),
+            "([| Tree: Ident (pk (: id: Ident Int: Ident)) ,: , (: name: Ident Str: Ident) ;: ; 1: Integer ,: , 'a': String |]: |])"
+        );
+
+        let s = expr("[| 1 |]");
+        assert_eq!(s.to_string(), "([| ERR([|) 1: Integer |]: |])");
+    }
+
+    #[test]
+    fn queries() {
+        let s = expr("x ?where #a = 1 ?select #a, #0 ?limit 3 ?sort");
+        assert_eq!(
+            s.to_string(),
+            "(?sort (?limit (?select (?where x: Ident (= #a: Column 1: Integer)) #a: Column #0: Column) 3: Integer))"
+        );
+
+        let s = expr("x ?sort #price desc, #name, #0 asc");
+        assert_eq!(
+            s.to_string(),
+            "(?sort x: Ident (desc #price: Column) #name: Column (asc #0: Column))"
+        );
+
+        let s = expr("let y := x ?distinct\ny");
+        assert_eq!(
+            s.to_string(),
+            "(let y: Ident :=: := (?distinct x: Ident))\ny: Ident"
+        );
+
+        let s = expr("x ?select #a,");
+        assert_eq!(s.to_string(), "(?select x: Ident #a: Column ERR(,))");
+    }
+
+    #[test]
+    fn calls() {
+        let s = expr("Int(x) + to_dec(1, 'up')");
+        assert_eq!(
+            s.to_string(),
+            "(+ (( Int: Ident x: Ident) (( to_dec: Ident 1: Integer 'up': String))"
+        );
+
+        let s = expr("-x.to_dec().to_str() + 1");
+        assert_eq!(
+            s.to_string(),
+            "(+ (Neg (( to_str: Ident (( to_dec: Ident x: Ident))) 1: Integer)"
+        );
+
+        let s = expr("now()");
+        assert_eq!(s.to_string(), "(( now: Ident)");
+
+        let s = expr("x.y");
+        assert_eq!(s.to_string(), "(. x: Ident y: Ident)");
+
+        let s = expr("Int(1");
+        assert_eq!(s.to_string(), "(( Int: Ident 1: Integer ERR(())");
     }
 
     #[test]
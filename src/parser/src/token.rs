@@ -1,5 +1,6 @@
 use corelib::derive_more::Display;
 use corelib::prelude::*;
+use corelib::query::Order;
 
 use corelib::text_size::TextRange;
 use logos::{Lexer, Logos};
@@ -25,6 +26,32 @@ fn increase_current_line(lexer: &mut Lexer<Syntax>) {
     lexer.extras.current_initial_column = lexer.span().end;
 }
 
+/// Count the lines inside a string, so the tokens after it are in the right line
+fn multiline(lexer: &mut Lexer<Syntax>) {
+    let code = lexer.slice();
+    if let Some(pos) = code.rfind('\n') {
+        lexer.extras.current_line += code.matches('\n').count();
+        lexer.extras.current_initial_column = lexer.span().start + pos + 1;
+    }
+}
+
+/// Find the closing quotes of a triple quoted string, that start at the token
+fn long_string(lexer: &mut Lexer<Syntax>) -> bool {
+    let quotes = lexer.slice();
+    let code = lexer.remainder();
+    let mut chars = code.char_indices();
+    while let Some((pos, x)) = chars.next() {
+        if x == '\\' {
+            chars.next();
+        } else if code[pos..].starts_with(quotes) {
+            lexer.bump(pos + quotes.len());
+            multiline(lexer);
+            return true;
+        }
+    }
+    false
+}
+
 /// Classify the kind of syntax for the parse, so it knows when to apply precedence...
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SyntaxKind {
@@ -72,6 +99,9 @@ pub enum Syntax {
     #[regex(r"(?&decimal)\.(?&decimal)d")]
     #[regex(r"(?&decimal)\.(?&decimal)")]
     Decimal,
+    // A amount & the code of the currency, like `12.50 USD`
+    #[regex(r"(?&decimal)(\.(?&decimal))? ?[A-Z][A-Z][A-Z]")]
+    Money,
     #[regex(r#"d"[^"]*""#)]
     #[regex(r#"d'[^']*'"#)]
     Date,
@@ -81,9 +111,18 @@ pub enum Syntax {
     #[regex(r#"dt"[^"]*""#)]
     #[regex(r#"dt'[^']*'"#)]
     DateTime,
-    // Strings, capture with both single and double quote
-    #[regex(r#""[^"]*""#)]
-    #[regex(r#"'[^']*'"#)]
+    #[regex(r#"i"[^"]*""#)]
+    #[regex(r#"i'[^']*'"#)]
+    Interval,
+    #[regex(r#"c"([^"\\]|\\.)*""#)]
+    #[regex(r#"c'([^'\\]|\\.)*'"#)]
+    Char,
+    // Strings, capture with both single and double quote, or triple quotes for long text.
+    // The quotes inside are escaped like `\"`
+    #[regex(r#""([^"\\]|\\.)*""#, multiline)]
+    #[regex(r#"'([^'\\]|\\.)*'"#, multiline)]
+    #[token(r#"""""#, long_string)]
+    #[token("'''", long_string)]
     String,
 
     //keywords
@@ -115,9 +154,39 @@ pub enum Syntax {
     #[token("end")]
     EndKw,
 
+    #[display(fmt = "pk")]
+    #[token("pk")]
+    PkKw,
+
+    #[display(fmt = "enum")]
+    #[token("enum")]
+    EnumKw,
+
+    #[display(fmt = "case")]
+    #[token("case")]
+    CaseKw,
+
+    #[display(fmt = "match")]
+    #[token("match")]
+    MatchKw,
+
+    // The order of a column in `?sort`
+    #[display(fmt = "asc")]
+    #[token("asc")]
+    AscKw,
+
+    #[display(fmt = "desc")]
+    #[token("desc")]
+    DescKw,
+
     //idents
-    #[regex("[A-Za-z][A-Za-z0-9]*")]
+    #[regex("[A-Za-z][A-Za-z0-9_]*")]
     Ident,
+    // A column of a relation, by name or position. The name can have the prefix of the
+    // relation, like the `#sales.id` of a join
+    #[regex(r"#[A-Za-z][A-Za-z0-9_]*(\.[A-Za-z][A-Za-z0-9_]*)?")]
+    #[regex(r"#\d+")]
+    Column,
 
     //OPS
 
@@ -126,6 +195,11 @@ pub enum Syntax {
     #[token(".")]
     Point,
 
+    // The range of a pattern, like `1..10`
+    #[display(fmt = "..")]
+    #[token("..")]
+    DotDot,
+
     #[display(fmt = ",")]
     #[token(",")]
     Comma,
@@ -138,6 +212,14 @@ pub enum Syntax {
     #[token("?")]
     Question,
 
+    // A relational operator, like `?where`
+    #[regex(r"\?[a-z]+")]
+    Query,
+
+    #[display(fmt = ":")]
+    #[token(":")]
+    Colon,
+
     //Math
     #[display(fmt = "+")]
     #[token("+")]
@@ -168,6 +250,7 @@ pub enum Syntax {
 
     #[display(fmt = "!=")]
     #[token("!=")]
+    #[token("<>")]
     NotEquals,
 
     #[display(fmt = "<")]
@@ -221,6 +304,14 @@ pub enum Syntax {
     #[token("]")]
     RSquare,
 
+    #[display(fmt = "[|")]
+    #[token("[|")]
+    LRel,
+
+    #[display(fmt = "|]")]
+    #[token("|]")]
+    RRel,
+
     //Markers, not represent code!
     Root,
     Eof,
@@ -237,22 +328,34 @@ impl Syntax {
             | Syntax::Integer
             | Syntax::Float
             | Syntax::Decimal
+            | Syntax::Money
             | Syntax::Bit
             | Syntax::String
             | Syntax::Date
             | Syntax::Time
-            | Syntax::DateTime => SyntaxKind::Atom,
-            Syntax::Ident => SyntaxKind::Atom,
+            | Syntax::DateTime
+            | Syntax::Interval
+            | Syntax::Char => SyntaxKind::Atom,
+            Syntax::Ident | Syntax::Column => SyntaxKind::Atom,
+            Syntax::Query => SyntaxKind::Postfix,
             Syntax::FnKw
             | Syntax::LetKw
             | Syntax::VarKw
             | Syntax::IfKw
             | Syntax::ElseKw
             | Syntax::DoKw
-            | Syntax::EndKw => SyntaxKind::Kw,
+            | Syntax::EndKw
+            | Syntax::PkKw
+            | Syntax::EnumKw
+            | Syntax::CaseKw
+            | Syntax::MatchKw
+            | Syntax::AscKw
+            | Syntax::DescKw => SyntaxKind::Kw,
             Syntax::Point
+            | Syntax::DotDot
             | Syntax::Assign
             | Syntax::Question
+            | Syntax::Colon
             | Syntax::Plus
             | Syntax::Minus
             | Syntax::Star
@@ -269,8 +372,8 @@ impl Syntax {
             | Syntax::OrKw
             | Syntax::NotKw => SyntaxKind::Infix,
             Syntax::Neg | Syntax::Add => SyntaxKind::Prefix,
-            Syntax::LParen | Syntax::LBrace | Syntax::LSquare => SyntaxKind::Open,
-            Syntax::RParen | Syntax::RBrace | Syntax::RSquare => SyntaxKind::Close,
+            Syntax::LParen | Syntax::LBrace | Syntax::LSquare | Syntax::LRel => SyntaxKind::Open,
+            Syntax::RParen | Syntax::RBrace | Syntax::RSquare | Syntax::RRel => SyntaxKind::Close,
             Syntax::Error => SyntaxKind::Err,
             Syntax::Eof => SyntaxKind::Eof,
             Syntax::Root => SyntaxKind::Root,
@@ -293,13 +396,39 @@ impl Syntax {
     pub fn to_unary_op(self) -> Option<UnaryOp> {
         let res = match self {
             Syntax::Neg => UnaryOp::Neg,
+            Syntax::Add => UnaryOp::Pos,
+            Syntax::NotKw => UnaryOp::Not,
+            _ => return None,
+        };
+        Some(res)
+    }
+    pub fn to_cmp_op(self) -> Option<CmpOp> {
+        let res = match self {
+            Syntax::Equals => CmpOp::Equals,
+            Syntax::NotEquals => CmpOp::NotEquals,
+            Syntax::Less => CmpOp::Less,
+            Syntax::LessThan => CmpOp::LessEquals,
+            Syntax::Greater => CmpOp::Greater,
+            Syntax::GreaterThan => CmpOp::GreaterEquals,
+            _ => return None,
+        };
+        Some(res)
+    }
+    pub fn to_logic_op(self) -> Option<LogicOp> {
+        let res = match self {
+            Syntax::AndKw => LogicOp::And,
+            Syntax::OrKw => LogicOp::Or,
             _ => return None,
         };
         Some(res)
     }
 
     pub fn is_op(self) -> bool {
-        self.to_unary_op().is_some() || self.to_bin_op().is_some() || matches!(self, Self::Point)
+        self.to_unary_op().is_some()
+            || self.to_bin_op().is_some()
+            || self.to_cmp_op().is_some()
+            || self.to_logic_op().is_some()
+            || matches!(self, Self::Point)
     }
 
     pub fn to_separator(self) -> SepOp {
@@ -312,6 +441,15 @@ impl Syntax {
     pub fn is_var_let(self) -> bool {
         matches!(self, Self::LetKw | Self::VarKw)
     }
+
+    pub fn to_order(self) -> Option<Order> {
+        let res = match self {
+            Syntax::AscKw => Order::Asc,
+            Syntax::DescKw => Order::Desc,
+            _ => return None,
+        };
+        Some(res)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -319,7 +457,15 @@ pub enum CmpOp {
     Equals,
     NotEquals,
     Less,
+    LessEquals,
     Greater,
+    GreaterEquals,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicOp {
+    And,
+    Or,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -330,6 +476,84 @@ pub enum BinaryOp {
     Div,
 }
 
+impl CmpOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            CmpOp::Equals => "=",
+            CmpOp::NotEquals => "!=",
+            CmpOp::Less => "<",
+            CmpOp::LessEquals => "<=",
+            CmpOp::Greater => ">",
+            CmpOp::GreaterEquals => ">=",
+        }
+    }
+}
+
+impl BinaryOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+        }
+    }
+}
+
+impl UnaryOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            UnaryOp::Neg => "-",
+            UnaryOp::Pos => "+",
+            UnaryOp::Not => "not",
+        }
+    }
+}
+
+/// The relational operators, like `?where`, that can be chained after a relation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryOp {
+    Where,
+    Select,
+    Deselect,
+    Limit,
+    Skip,
+    Distinct,
+    Sort,
+    Group,
+}
+
+impl QueryOp {
+    /// Find the operator for the code of a [Syntax::Query], like `?where`
+    pub fn from_code(code: &str) -> Option<Self> {
+        let res = match code {
+            "?where" => QueryOp::Where,
+            "?select" => QueryOp::Select,
+            "?deselect" => QueryOp::Deselect,
+            "?limit" => QueryOp::Limit,
+            "?skip" => QueryOp::Skip,
+            "?distinct" => QueryOp::Distinct,
+            "?sort" => QueryOp::Sort,
+            "?group" => QueryOp::Group,
+            _ => return None,
+        };
+        Some(res)
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            QueryOp::Where => "?where",
+            QueryOp::Select => "?select",
+            QueryOp::Deselect => "?deselect",
+            QueryOp::Limit => "?limit",
+            QueryOp::Skip => "?skip",
+            QueryOp::Distinct => "?distinct",
+            QueryOp::Sort => "?sort",
+            QueryOp::Group => "?group",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SepOp {
     Comma,
@@ -339,6 +563,8 @@ pub enum SepOp {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UnaryOp {
     Neg,
+    Pos,
+    Not,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -1,10 +1,9 @@
+use std::env;
 use std::fs::File;
-use std::io::Read;
 use std::path::PathBuf;
-use std::{env, fs, io};
 
 use corelib::errors::Span;
-use corelib::prelude::{Scalar, VERSION};
+use corelib::prelude::{Scalar, Table, Tree, Vector, VERSION};
 use eval::code::Code;
 use eval::diagnostic::print_diagnostic;
 use eval::errors::ErrorCode;
@@ -32,14 +31,37 @@ enum Execute {
     Pass,
     Halt((ErrorCode, Span)),
     Value(Scalar),
+    Vector(Vector),
+    Table(Table),
+    Tree(Tree),
     Eof,
 }
 
-fn run_code(p: &Program) -> Execute {
+fn run_code(p: &mut Program) -> Execute {
     match p.eval() {
-        Code::Root => Execute::Pass,
+        Code::Root | Code::Pass => Execute::Pass,
         Code::Scalar { val, .. } => Execute::Value(val),
-        Code::If { .. } => Execute::Pass,
+        Code::Vector { val, .. } => Execute::Vector(*val),
+        Code::Table { val, .. } => Execute::Table(*val),
+        Code::Tree { val, .. } => Execute::Tree(*val),
+        Code::If { .. }
+        | Code::Match { .. }
+        | Code::Arm { .. }
+        | Code::BinOp { .. }
+        | Code::UnaryOp { .. }
+        | Code::Cmp { .. }
+        | Code::Logic { .. }
+        | Code::Ident { .. }
+        | Code::Define { .. }
+        | Code::Assign { .. }
+        | Code::DefineEnum { .. }
+        | Code::Block { .. }
+        | Code::NewVector { .. }
+        | Code::NewTree { .. }
+        | Code::Column { .. }
+        | Code::Order { .. }
+        | Code::Query { .. }
+        | Code::Call { .. } => Execute::Pass,
         Code::Halt { error, span } => Execute::Halt((error, span)),
         Code::Eof => Execute::Eof,
     }
@@ -54,11 +76,14 @@ fn run_file(c: &Context) {
                 let code = read_file_to_string(&mut f).expect("Fail to read file");
                 let file = create_file(path, &code);
 
-                let program = Program::from_file(file);
-                match run_code(&program) {
+                let mut program = Program::from_file(file);
+                match run_code(&mut program) {
                     Execute::Pass => {}
-                    Execute::Halt((err, span)) => eprintln!("{:?}", err),
-                    Execute::Value(_) => {}
+                    Execute::Halt((err, _span)) => eprintln!("{:?}", err),
+                    Execute::Value(_)
+                    | Execute::Vector(_)
+                    | Execute::Table(_)
+                    | Execute::Tree(_) => {}
                     Execute::Eof => {}
                 }
             }
@@ -74,7 +99,7 @@ fn run_repl(c: &Context) {
     let ps = SyntaxSet::load_defaults_newlines();
     let ts = ThemeSet::load_defaults();
     let syntax = ps.find_syntax_by_extension("rs").unwrap();
-    let mut h = HighlightLines::new(syntax, &ts.themes["base16-ocean.dark"]);
+    let _h = HighlightLines::new(syntax, &ts.themes["base16-ocean.dark"]);
 
     if !c.args.is_empty() {
         c.help();
@@ -98,15 +123,30 @@ fn run_repl(c: &Context) {
                 line => {
                     //dbg!(&line);
                     match program.append_from_src(line) {
-                        Ok(_) => match run_code(&program) {
+                        Ok(_) => match run_code(&mut program) {
                             Execute::Pass => continue,
-                            Execute::Halt((err, span)) => print_diagnostic(&program.files, &err)
+                            Execute::Halt((err, _span)) => print_diagnostic(&program.files, &err)
                                 .expect("Fail to report diagnostics"),
                             Execute::Value(x) => {
                                 rl.add_history_entry(line);
 
                                 println!("{x}");
                             }
+                            Execute::Vector(x) => {
+                                rl.add_history_entry(line);
+
+                                println!("{x}");
+                            }
+                            Execute::Table(x) => {
+                                rl.add_history_entry(line);
+
+                                println!("{x}");
+                            }
+                            Execute::Tree(x) => {
+                                rl.add_history_entry(line);
+
+                                println!("{x}");
+                            }
                             Execute::Eof => break,
                         },
                         Err(err) => print_diagnostic(&program.files, &err)